@@ -0,0 +1,180 @@
+use std::{error::Error, fs, io::Write, path::Path};
+
+use chrono::NaiveDate;
+
+use crate::{
+    TodoItem, TodoList, config::Config, create_lock_file, load_or_create_todo_list, read_todo_list,
+    todo_file_path, write_atomically,
+};
+
+/// How many days ahead `add --append-to` goes without `--force`; the app
+/// warns about future files on every start until their day has come
+const MAX_DAYS_AHEAD: i64 = 14;
+
+/// Appends a top-level item to the list of `date`, creating its file if
+/// needed. Adding to today's list starts from the newest list like the app
+/// does. Holds the lock file, since the app would overwrite the change. With
+/// `single_file` only today's list can be added to.
+pub fn run(
+    config_dir: &Path,
+    config: &Config,
+    today: NaiveDate,
+    text: String,
+    date: NaiveDate,
+    force: bool,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    if config.single_file && date != today {
+        return Err(format!(
+            "single_file keeps only today's list, so nothing can be added to {}",
+            date
+        )
+        .into());
+    }
+    if (date - today).num_days() > MAX_DAYS_AHEAD && !force {
+        return Err(format!(
+            "{} is more than {} days ahead, use --force to add to it anyway",
+            date, MAX_DAYS_AHEAD
+        )
+        .into());
+    }
+
+    let lock_file = create_lock_file(config_dir)?;
+    let result = append_item(config_dir, config, today, text, date, out);
+    fs::remove_file(lock_file)?;
+    result
+}
+
+fn append_item(
+    config_dir: &Path,
+    config: &Config,
+    today: NaiveDate,
+    text: String,
+    date: NaiveDate,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let path = todo_file_path(config_dir, config, date);
+    let mut todo_list = if date == today {
+        load_or_create_todo_list(config_dir, today, config)?
+    } else if path.exists() {
+        read_todo_list(&path, &config.format)?
+    } else {
+        let mut todo_list = TodoList::new(date);
+        todo_list.format = config.format.clone();
+        todo_list
+    };
+
+    todo_list.items.push(TodoItem::new(text, false, 0));
+    write_atomically(&path, &todo_list.to_markdown())?;
+    writeln!(out, "Added to {}", path.display())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add(dir: &Path, text: &str, date: NaiveDate, force: bool) -> Result<(), Box<dyn Error>> {
+        let today = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut out = Vec::new();
+        run(
+            dir,
+            &Config::default(),
+            today,
+            text.to_string(),
+            date,
+            force,
+            &mut out,
+        )
+    }
+
+    #[test]
+    fn test_add_creates_and_appends_to_the_day() {
+        let dir = tempfile::tempdir().unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 8, 20).unwrap();
+        add(dir.path(), "first", date, false).unwrap();
+        add(dir.path(), "second", date, false).unwrap();
+
+        let files = crate::todo_files(dir.path());
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].0, date);
+        assert_eq!(
+            fs::read_to_string(&files[0].1).unwrap(),
+            "# TODO 2025-08-20\n\n* [ ] first\n* [ ] second\n"
+        );
+        assert!(!dir.path().join("lockfile").exists());
+    }
+
+    #[test]
+    fn test_add_to_today_continues_the_newest_list() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("TODO-2025-08-12.md"),
+            "# TODO 2025-08-12\n\n* [ ] older\n",
+        )
+        .unwrap();
+        let today = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        add(dir.path(), "new", today, false).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dir.path().join("TODO-2025-08-14.md")).unwrap(),
+            "# TODO 2025-08-14\n\n* [ ] older def:1\n* [ ] new\n"
+        );
+        // The older day is left alone
+        assert_eq!(
+            fs::read_to_string(dir.path().join("TODO-2025-08-12.md")).unwrap(),
+            "# TODO 2025-08-12\n\n* [ ] older\n"
+        );
+    }
+
+    #[test]
+    fn test_add_far_ahead_needs_force() {
+        let dir = tempfile::tempdir().unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 9, 30).unwrap();
+        assert!(add(dir.path(), "later", date, false).is_err());
+        assert!(crate::todo_files(dir.path()).is_empty());
+
+        add(dir.path(), "later", date, true).unwrap();
+        assert_eq!(crate::todo_files(dir.path())[0].0, date);
+    }
+
+    #[test]
+    fn test_add_to_other_days_refuses_single_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let today = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let config = Config {
+            single_file: true,
+            ..Config::default()
+        };
+        let add_on = |date| {
+            let text = "x".to_string();
+            run(
+                dir.path(),
+                &config,
+                today,
+                text,
+                date,
+                false,
+                &mut Vec::new(),
+            )
+        };
+
+        assert!(add_on(today.succ_opt().unwrap()).is_err());
+        assert!(!dir.path().join("TODO.md").exists());
+        add_on(today).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.path().join("TODO.md")).unwrap(),
+            "# TODO 2025-08-14\n\n* [ ] x\n"
+        );
+        assert!(crate::todo_files(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_add_respects_the_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        create_lock_file(dir.path()).unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 8, 15).unwrap();
+        assert!(add(dir.path(), "blocked", date, false).is_err());
+        assert!(crate::todo_files(dir.path()).is_empty());
+    }
+}
@@ -0,0 +1,31 @@
+use std::{error::Error, fs, path::Path};
+
+use chrono::NaiveDate;
+
+use crate::{TodoApp, config::Config, create_lock_file, load_or_create_todo_list, tui};
+
+/// Opens a prompt for a new item on today's list, e.g. from a global hotkey.
+/// Holds the lock file while the prompt is open, and releases it even if
+/// capturing fails.
+pub fn run(
+    config_dir: &Path,
+    config: &Config,
+    today: NaiveDate,
+    stay: bool,
+) -> Result<(), Box<dyn Error>> {
+    let lock_file = create_lock_file(config_dir)?;
+    let result = capture(config_dir, config, today, stay);
+    fs::remove_file(lock_file)?;
+    result
+}
+
+fn capture(
+    config_dir: &Path,
+    config: &Config,
+    today: NaiveDate,
+    stay: bool,
+) -> Result<(), Box<dyn Error>> {
+    let todo_list = load_or_create_todo_list(config_dir, today, config)?;
+    let todo_app = TodoApp::new(config_dir.to_path_buf(), todo_list).with_config(config.clone());
+    tui::run_capture(todo_app, stay)
+}
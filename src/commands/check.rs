@@ -0,0 +1,174 @@
+use std::{error::Error, fs, io::Write, path::Path};
+
+use crate::{
+    TodoItem, TodoList, config::Config, parse_day_note, parse_title_comment, parse_todo_file_name,
+};
+
+/// Parses the todo file at `path`, which may live anywhere, and prints what
+/// the app reads from it: the date, title, note and the tree of items with
+/// their tokens. Lines the parser skips are listed with their line numbers,
+/// and a file that can't be read fails with the line at fault.
+pub fn run(path: &Path, config: &Config, out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| format!("Could not read {}: {}", path.display(), err))?;
+    // Only the header can make parsing fail
+    let todo_list =
+        TodoList::from_markdown_with(&content, config.format.clone()).map_err(|err| {
+            format!(
+                "{}:1: {} (expected \"# TODO YYYY-MM-DD\")",
+                path.display(),
+                err
+            )
+        })?;
+
+    writeln!(out, "date: {}", todo_list.date)?;
+    let file_date = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(parse_todo_file_name);
+    if let Some(file_date) = file_date.filter(|&date| date != todo_list.date) {
+        writeln!(
+            out,
+            "warning: the file name says {}, but the header says {}",
+            file_date, todo_list.date
+        )?;
+    }
+    if let Some(title) = &todo_list.title {
+        writeln!(out, "title: {}", title)?;
+    }
+    if let Some(note) = &todo_list.note {
+        writeln!(out, "note: {}", note)?;
+    }
+    let completed = todo_list.items.iter().filter(|i| i.completed).count();
+    writeln!(
+        out,
+        "items: {} ({} completed)",
+        todo_list.items.len(),
+        completed
+    )?;
+    for item in &todo_list.items {
+        writeln!(out, "{}", describe(item))?;
+    }
+    for (line_number, line) in ignored_lines(&content) {
+        writeln!(out, "line {} ignored: {}", line_number, line)?;
+    }
+    for warning in &todo_list.warnings {
+        writeln!(out, "warning: {}", warning)?;
+    }
+    Ok(())
+}
+
+// One line of the item tree: the item indented by its level, followed by
+// its level and the tokens read from its text
+fn describe(item: &TodoItem) -> String {
+    let marker = match (item.is_task, item.completed) {
+        (false, _) => "*",
+        (true, true) => "[x]",
+        (true, false) => "[ ]",
+    };
+    let mut details = vec![format!("level {}", item.indent_level)];
+    if item.deferred_count() > 0 {
+        details.push(format!("deferred {}", item.deferred_count()));
+    }
+    if let Some(date) = item.done_date() {
+        details.push(format!("done {}", date));
+    }
+    if let Some(date) = item.due_date() {
+        details.push(format!("due {}", date));
+    }
+    format!(
+        "{}{} {} ({})",
+        "  ".repeat(item.indent_level),
+        marker,
+        item.text,
+        details.join(", ")
+    )
+}
+
+/// Lines after the header that are neither items, blank, the title comment
+/// nor the note of the day. The parser skips them silently, which usually
+/// means a typo like a missing space after the bullet.
+pub fn ignored_lines(content: &str) -> Vec<(usize, &str)> {
+    let mut seen_item = false;
+    content
+        .lines()
+        .enumerate()
+        .skip(1)
+        .filter_map(|(index, line)| {
+            if line.trim_start_matches([' ', '\t']).starts_with("* ") {
+                seen_item = true;
+                return None;
+            }
+            let known = line.trim().is_empty()
+                || parse_title_comment(line).is_some()
+                || (!seen_item && parse_day_note(line).is_some());
+            (!known).then_some((index + 1, line))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(file_name: &str, content: &str) -> Result<String, String> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(file_name);
+        fs::write(&path, content).unwrap();
+        let mut out = Vec::new();
+        run(&path, &Config::default(), &mut out)
+            .map(|()| String::from_utf8(out).unwrap())
+            .map_err(|err| {
+                err.to_string()
+                    .replace(&*dir.path().to_string_lossy(), "DIR")
+            })
+    }
+
+    #[test]
+    fn test_check_prints_item_tree() {
+        let content = "# TODO 2025-08-14\n<!-- title: Work -->\n> focus\n\n\
+            * [x] report done:2025-08-14\n  * [ ] slides def:2 due:2025-08-20\n  * a note\n";
+        let output = check("notes.md", content).unwrap();
+        assert_eq!(
+            output,
+            "date: 2025-08-14\ntitle: Work\nnote: focus\nitems: 3 (1 completed)\n\
+            [x] report done:2025-08-14 (level 0, done 2025-08-14)\n  \
+            [ ] slides def:2 due:2025-08-20 (level 1, deferred 2, due 2025-08-20)\n  \
+            * a note (level 1)\n"
+        );
+    }
+
+    #[test]
+    fn test_check_reports_skipped_lines_and_date_mismatch() {
+        let content = "# TODO 2025-08-14\n\n*[ ] no space\n* [ ] fine\n> too late\n";
+        let output = check("TODO-2025-08-15.md", content).unwrap();
+        assert_eq!(
+            output,
+            "date: 2025-08-14\n\
+            warning: the file name says 2025-08-15, but the header says 2025-08-14\n\
+            items: 1 (0 completed)\n[ ] fine (level 0)\n\
+            line 3 ignored: *[ ] no space\nline 5 ignored: > too late\n"
+        );
+    }
+
+    #[test]
+    fn test_check_malformed_files() {
+        assert_eq!(
+            check("a.md", "TODO 2025-08-14\n* [ ] a\n").unwrap_err(),
+            "DIR/a.md:1: Invalid header format (expected \"# TODO YYYY-MM-DD\")"
+        );
+        assert_eq!(
+            check("a.md", "# TODO 2025-13-01\n").unwrap_err(),
+            "DIR/a.md:1: Invalid date format in header (expected \"# TODO YYYY-MM-DD\")"
+        );
+        assert_eq!(
+            check("a.md", "").unwrap_err(),
+            "DIR/a.md:1: Empty todo file (expected \"# TODO YYYY-MM-DD\")"
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing.md");
+        let err = run(&missing, &Config::default(), &mut Vec::new()).unwrap_err();
+        assert!(err.to_string().starts_with("Could not read "));
+    }
+}
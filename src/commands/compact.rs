@@ -0,0 +1,372 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{Datelike, Days, NaiveDate};
+
+use crate::{config::Config, create_lock_file, read_todo_list, todo_files, warn, write_atomically};
+
+/// Parses an age like `90d` (or just `90`) into a number of days
+pub fn parse_days(value: &str) -> Result<u32, String> {
+    value
+        .strip_suffix('d')
+        .unwrap_or(value)
+        .parse()
+        .map_err(|_| format!("invalid age '{}', expected something like 90d", value))
+}
+
+/// Name of the file collecting the archived lists of `year`
+fn archive_file_name(year: i32) -> String {
+    format!("ARCHIVE-{}.md", year)
+}
+
+/// Moves the lists dated before `today - older_than` into one archive file per
+/// year and deletes the originals. Today's file, anything newer than the
+/// cutoff and the newest list (which the app carries over) are never touched.
+/// Holds the lock file unless it's a dry run.
+pub fn run(
+    config_dir: &Path,
+    config: &Config,
+    today: NaiveDate,
+    older_than: u32,
+    dry_run: bool,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    if dry_run {
+        return archive(config_dir, config, today, older_than, true, out);
+    }
+    let lock_file = create_lock_file(config_dir)?;
+    let result = archive(config_dir, config, today, older_than, false, out);
+    fs::remove_file(lock_file)?;
+    result
+}
+
+// `run` without taking the lock file, for callers that already hold it
+fn archive(
+    config_dir: &Path,
+    config: &Config,
+    today: NaiveDate,
+    older_than: u32,
+    dry_run: bool,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let mut by_year = BTreeMap::new();
+    for (date, path) in expired_files(config_dir, today, older_than) {
+        by_year
+            .entry(date.year())
+            .or_insert_with(Vec::new)
+            .push(path);
+    }
+
+    for (year, paths) in by_year {
+        let archive_path = config_dir.join(archive_file_name(year));
+        let mut archive = if archive_path.exists() {
+            fs::read_to_string(&archive_path)?
+        } else {
+            String::new()
+        };
+
+        let mut archived = Vec::new();
+        for path in paths {
+            let todo_list = match read_todo_list(&path, &config.format) {
+                Ok(todo_list) => todo_list,
+                Err(err) => {
+                    warn(format!("Skipping {}: {}", path.display(), err));
+                    continue;
+                }
+            };
+            if !archive.is_empty() {
+                // Lists saved without a final newline still need one here
+                if !archive.ends_with('\n') {
+                    archive.push('\n');
+                }
+                archive.push('\n');
+            }
+            archive.push_str(&todo_list.to_markdown());
+            archived.push(path);
+        }
+        if archived.is_empty() {
+            continue;
+        }
+
+        let verb = if dry_run { "Would archive" } else { "Archived" };
+        for path in &archived {
+            writeln!(
+                out,
+                "{} {} into {}",
+                verb,
+                path.display(),
+                archive_path.display()
+            )?;
+        }
+        if !dry_run {
+            // Only delete the originals once the archive is safely written
+            write_atomically(&archive_path, &archive)?;
+            for path in &archived {
+                fs::remove_file(path)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Deletes the lists older than `max_history_days` when the app starts,
+/// archiving them like `run` does if `archive_pruned` is set. Keeps the
+/// same files as `run` and does nothing if the limit is 0.
+pub fn prune(config_dir: &Path, config: &Config, today: NaiveDate) -> Result<(), Box<dyn Error>> {
+    let days = config.max_history_days;
+    if days == 0 {
+        return Ok(());
+    }
+    if config.archive_pruned {
+        return archive(config_dir, config, today, days, false, &mut io::sink());
+    }
+    for (_, path) in expired_files(config_dir, today, days) {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// The todo files dated before `today - older_than`, oldest first, leaving
+/// out today's file, future ones and the newest list (which the app carries
+/// over). Ages reaching back before the earliest date expire nothing.
+fn expired_files(
+    config_dir: &Path,
+    today: NaiveDate,
+    older_than: u32,
+) -> Vec<(NaiveDate, PathBuf)> {
+    let cutoff = today
+        .checked_sub_days(Days::new(older_than.into()))
+        .unwrap_or(NaiveDate::MIN);
+    let files = todo_files(config_dir);
+    let newest = files
+        .iter()
+        .rfind(|(date, _)| *date <= today)
+        .map(|(date, _)| *date);
+    files
+        .into_iter()
+        .filter(|(date, _)| *date < cutoff && *date < today && Some(*date) != newest)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_util::write_fixture;
+
+    fn fixture_dir() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        write_fixture(dir.path(), "2024-12-30", "* [x] old\n");
+        write_fixture(dir.path(), "2025-01-02", "* [ ] older\n");
+        // Exactly at the cutoff, kept
+        write_fixture(dir.path(), "2025-05-16", "* [ ] boundary\n");
+        write_fixture(dir.path(), "2025-08-14", "* [ ] today\n");
+        dir
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 8, 14).unwrap()
+    }
+
+    fn file_names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_parse_days() {
+        assert_eq!(parse_days("90d"), Ok(90));
+        assert_eq!(parse_days("7"), Ok(7));
+        assert!(parse_days("3w").is_err());
+    }
+
+    #[test]
+    fn test_compact_archives_files_before_cutoff() {
+        let dir = fixture_dir();
+        let mut out = Vec::new();
+        run(dir.path(), &Config::default(), today(), 90, false, &mut out).unwrap();
+
+        assert_eq!(
+            file_names(dir.path()),
+            [
+                "ARCHIVE-2024.md",
+                "ARCHIVE-2025.md",
+                "TODO-2025-05-16.md",
+                "TODO-2025-08-14.md"
+            ]
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("ARCHIVE-2025.md")).unwrap(),
+            "# TODO 2025-01-02\n\n* [ ] older\n"
+        );
+        assert_eq!(String::from_utf8(out).unwrap().lines().count(), 2);
+        assert!(!dir.path().join("lockfile").exists());
+
+        // Archiving more days of a year appends to its archive
+        write_fixture(dir.path(), "2025-01-03", "* [x] later\n");
+        run(
+            dir.path(),
+            &Config::default(),
+            today(),
+            90,
+            false,
+            &mut Vec::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.path().join("ARCHIVE-2025.md")).unwrap(),
+            "# TODO 2025-01-02\n\n* [ ] older\n\n# TODO 2025-01-03\n\n* [x] later\n"
+        );
+    }
+
+    #[test]
+    fn test_compact_dry_run_changes_nothing() {
+        let dir = fixture_dir();
+        let before = file_names(dir.path());
+        let mut out = Vec::new();
+        run(dir.path(), &Config::default(), today(), 90, true, &mut out).unwrap();
+
+        assert_eq!(file_names(dir.path()), before);
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("Would archive"));
+        assert!(out.contains("TODO-2024-12-30.md"));
+        assert!(!out.contains("TODO-2025-05-16.md"));
+    }
+
+    #[test]
+    fn test_compact_with_huge_age_archives_nothing() {
+        let dir = fixture_dir();
+        let before = file_names(dir.path());
+        let older_than = parse_days("4000000000d").unwrap();
+        let mut out = Vec::new();
+        run(
+            dir.path(),
+            &Config::default(),
+            today(),
+            older_than,
+            false,
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(file_names(dir.path()), before);
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_compact_refuses_while_locked() {
+        let dir = fixture_dir();
+        let before = file_names(dir.path());
+        let lock_file = create_lock_file(dir.path()).unwrap();
+
+        let result = run(
+            dir.path(),
+            &Config::default(),
+            today(),
+            90,
+            false,
+            &mut Vec::new(),
+        );
+        assert!(result.is_err());
+        fs::remove_file(lock_file).unwrap();
+        assert_eq!(file_names(dir.path()), before);
+    }
+
+    #[test]
+    fn test_compact_keeps_newest_and_todays_file() {
+        let dir = tempfile::tempdir().unwrap();
+        write_fixture(dir.path(), "2025-01-02", "* [ ] a\n");
+        write_fixture(dir.path(), "2025-02-02", "* [ ] carried over\n");
+
+        // Even with a cutoff of 0 days the newest list stays
+        run(
+            dir.path(),
+            &Config::default(),
+            today(),
+            0,
+            false,
+            &mut Vec::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            file_names(dir.path()),
+            ["ARCHIVE-2025.md", "TODO-2025-02-02.md"]
+        );
+    }
+
+    #[test]
+    fn test_prune_deletes_files_before_cutoff() {
+        let dir = fixture_dir();
+        let mut config = Config::default();
+
+        // Disabled by default
+        prune(dir.path(), &config, today()).unwrap();
+        assert_eq!(file_names(dir.path()).len(), 4);
+
+        config.max_history_days = 90;
+        prune(dir.path(), &config, today()).unwrap();
+        assert_eq!(
+            file_names(dir.path()),
+            ["TODO-2025-05-16.md", "TODO-2025-08-14.md"]
+        );
+    }
+
+    #[test]
+    fn test_prune_can_archive_first() {
+        let dir = fixture_dir();
+        let config = Config {
+            max_history_days: 90,
+            archive_pruned: true,
+            ..Config::default()
+        };
+        prune(dir.path(), &config, today()).unwrap();
+        assert_eq!(
+            file_names(dir.path()),
+            [
+                "ARCHIVE-2024.md",
+                "ARCHIVE-2025.md",
+                "TODO-2025-05-16.md",
+                "TODO-2025-08-14.md"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_prune_keeps_newest_list() {
+        let dir = tempfile::tempdir().unwrap();
+        write_fixture(dir.path(), "2025-01-02", "* [ ] a\n");
+        write_fixture(dir.path(), "2025-02-02", "* [ ] carried over\n");
+        let config = Config {
+            max_history_days: 1,
+            ..Config::default()
+        };
+        prune(dir.path(), &config, today()).unwrap();
+        assert_eq!(file_names(dir.path()), ["TODO-2025-02-02.md"]);
+    }
+
+    #[test]
+    fn test_prune_with_huge_history_keeps_everything() {
+        let dir = tempfile::tempdir().unwrap();
+        write_fixture(dir.path(), "2025-01-02", "* [ ] a\n");
+        write_fixture(dir.path(), "2025-02-02", "* [ ] b\n");
+        for archive_pruned in [false, true] {
+            let config = Config {
+                max_history_days: u32::MAX,
+                archive_pruned,
+                ..Config::default()
+            };
+            prune(dir.path(), &config, today()).unwrap();
+            assert_eq!(
+                file_names(dir.path()),
+                ["TODO-2025-01-02.md", "TODO-2025-02-02.md"]
+            );
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use std::{error::Error, io::Write, path::Path};
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::{config::Config, read_todo_list, todo_files_for, warn};
+
+#[derive(Debug, Serialize)]
+struct DateEntry {
+    date: NaiveDate,
+    /// Future-dated files are listed but ignored by the app
+    future: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    completed: Option<usize>,
+}
+
+pub fn run(
+    config_dir: &Path,
+    config: &Config,
+    today: NaiveDate,
+    counts: bool,
+    json: bool,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let mut entries = Vec::new();
+    for (date, path) in todo_files_for(config_dir, config) {
+        let mut entry = DateEntry {
+            date,
+            future: date > today,
+            total: None,
+            completed: None,
+        };
+        if counts {
+            match read_todo_list(&path, &config.format) {
+                Ok(todo_list) => {
+                    entry.total = Some(todo_list.items.len());
+                    entry.completed = Some(todo_list.items.iter().filter(|i| i.completed).count());
+                }
+                Err(err) => warn(format!("Skipping {}: {}", path.display(), err)),
+            }
+        }
+        entries.push(entry);
+    }
+
+    if json {
+        serde_json::to_writer_pretty(&mut *out, &entries)?;
+        writeln!(out)?;
+        return Ok(());
+    }
+
+    for entry in &entries {
+        write!(out, "{}", entry.date)?;
+        if let (Some(total), Some(completed)) = (entry.total, entry.completed) {
+            write!(out, "  {}/{} completed", completed, total)?;
+        }
+        if entry.future {
+            write!(out, "  (future)")?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn fixture_dir() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("TODO-2025-08-14.md"),
+            "# TODO 2025-08-14\n\n* [x] a\n* [ ] b\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("TODO-2025-08-10.md"),
+            "# TODO 2025-08-10\n\n* [x] a\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("TODO-2025-09-01.md"),
+            "# TODO 2025-09-01\n\n",
+        )
+        .unwrap();
+        // Malformed names are ignored
+        fs::write(dir.path().join("TODO-2025-13-01.md"), "").unwrap();
+        fs::write(dir.path().join("notes.md"), "").unwrap();
+        dir
+    }
+
+    fn dates(counts: bool, json: bool) -> String {
+        let dir = fixture_dir();
+        let today = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut out = Vec::new();
+        run(
+            dir.path(),
+            &Config::default(),
+            today,
+            counts,
+            json,
+            &mut out,
+        )
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_dates_plain() {
+        assert_eq!(
+            dates(false, false),
+            "2025-08-10\n2025-08-14\n2025-09-01  (future)\n"
+        );
+    }
+
+    #[test]
+    fn test_dates_with_counts() {
+        assert_eq!(
+            dates(true, false),
+            "2025-08-10  1/1 completed\n\
+             2025-08-14  1/2 completed\n\
+             2025-09-01  0/0 completed  (future)\n"
+        );
+    }
+
+    #[test]
+    fn test_dates_json() {
+        let value: serde_json::Value = serde_json::from_str(&dates(true, true)).unwrap();
+        assert_eq!(value.as_array().unwrap().len(), 3);
+        assert_eq!(value[1]["date"], "2025-08-14");
+        assert_eq!(value[1]["completed"], 1);
+        assert_eq!(value[2]["future"], true);
+
+        let value: serde_json::Value = serde_json::from_str(&dates(false, true)).unwrap();
+        assert!(value[0].get("total").is_none());
+    }
+
+    #[test]
+    fn test_dates_of_single_file() {
+        let dir = fixture_dir();
+        fs::write(
+            dir.path().join("TODO.md"),
+            "# TODO 2025-08-12\n\n* [x] a\n* [ ] b\n",
+        )
+        .unwrap();
+        let config = Config {
+            single_file: true,
+            ..Config::default()
+        };
+        let today = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut out = Vec::new();
+        run(dir.path(), &config, today, true, false, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "2025-08-12  1/2 completed\n"
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use std::{error::Error, fs, io::Write, path::Path};
+
+use chrono::NaiveDate;
+
+use crate::{TodoApp, config::Config, create_lock_file, load_or_create_todo_list};
+
+/// Merges duplicate items on today's list and saves it if anything changed.
+/// Holds the lock file, since the app would overwrite the change.
+pub fn run(
+    config_dir: &Path,
+    config: &Config,
+    today: NaiveDate,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let lock_file = create_lock_file(config_dir)?;
+    let result = dedup(config_dir, config, today, out);
+    fs::remove_file(lock_file)?;
+    result
+}
+
+fn dedup(
+    config_dir: &Path,
+    config: &Config,
+    today: NaiveDate,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let todo_list = load_or_create_todo_list(config_dir, today, config)?;
+    let mut todo_app =
+        TodoApp::new(config_dir.to_path_buf(), todo_list).with_config(config.clone());
+    let merged = todo_app.todo_list.dedup(config.dedup_prefer_completed);
+    if merged > 0 {
+        todo_app.save_todo_list_on(today)?;
+    }
+    writeln!(out, "Merged {} duplicates", merged)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedup_saves_todays_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let today = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let path = dir
+            .path()
+            .join(format!("TODO-{}.md", today.format("%Y-%m-%d")));
+        let header = format!("# TODO {}\n\n", today.format("%Y-%m-%d"));
+        fs::write(&path, format!("{}* [ ] a\n* [ ] b\n* [x]  a\n", header)).unwrap();
+
+        let mut out = Vec::new();
+        run(dir.path(), &Config::default(), today, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "Merged 1 duplicates\n");
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format!("{}* [x] a\n* [ ] b\n", header)
+        );
+        assert!(!dir.path().join("lockfile").exists());
+    }
+}
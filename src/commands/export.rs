@@ -0,0 +1,444 @@
+use std::{
+    error::Error,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{TodoList, config::Config, read_todo_list, todo_file_path, todo_files_for, warn};
+
+/// Inclusive span of dates, open on either side
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DateRange {
+    pub start: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+}
+
+impl DateRange {
+    fn contains(&self, date: NaiveDate) -> bool {
+        self.start.is_none_or(|start| start <= date) && self.end.is_none_or(|end| date <= end)
+    }
+}
+
+/// Parses `A..B`, `A..` or `..B` with dates as YYYY-MM-DD
+pub fn parse_range(value: &str) -> Result<DateRange, String> {
+    let (start, end) = value
+        .split_once("..")
+        .ok_or_else(|| format!("invalid range '{}', expected A..B", value))?;
+    let parse = |date: &str| -> Result<Option<NaiveDate>, String> {
+        if date.is_empty() {
+            return Ok(None);
+        }
+        super::parse_date(date).map(Some)
+    };
+    Ok(DateRange {
+        start: parse(start)?,
+        end: parse(end)?,
+    })
+}
+
+/// One day of the JSON export
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedDay {
+    pub date: NaiveDate,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub items: Vec<ExportedItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedItem {
+    pub text: String,
+    pub completed: bool,
+    pub indent_level: usize,
+    /// False for notes without a checkbox; bundles from before notes existed
+    /// only have tasks
+    #[serde(default = "is_task_default")]
+    pub is_task: bool,
+}
+
+fn is_task_default() -> bool {
+    true
+}
+
+impl From<&TodoList> for ExportedDay {
+    fn from(todo_list: &TodoList) -> Self {
+        ExportedDay {
+            date: todo_list.date,
+            title: todo_list.title.clone(),
+            note: todo_list.note.clone(),
+            items: todo_list
+                .items
+                .iter()
+                .map(|item| ExportedItem {
+                    text: item.text.clone(),
+                    completed: item.completed,
+                    indent_level: item.indent_level,
+                    is_task: item.is_task,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Writes all lists within `range` into one document, oldest first. Future
+/// and malformed files are skipped with a warning.
+pub fn run(
+    config_dir: &Path,
+    config: &Config,
+    today: NaiveDate,
+    range: DateRange,
+    json: bool,
+    out_path: Option<&Path>,
+    stdout: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let mut lists = Vec::new();
+    for (date, path) in todo_files_for(config_dir, config) {
+        if !range.contains(date) {
+            continue;
+        }
+        if date > today {
+            warn(format!("Skipping future todo file {}", path.display()));
+            continue;
+        }
+        match read_todo_list(&path, &config.format) {
+            Ok(todo_list) => lists.push(todo_list),
+            Err(err) => warn(format!("Skipping {}: {}", path.display(), err)),
+        }
+    }
+
+    match out_path {
+        Some(path) => {
+            let mut file = BufWriter::new(File::create(path)?);
+            write_export(&lists, json, &mut file)?;
+            file.flush()?;
+        }
+        None => write_export(&lists, json, stdout)?,
+    }
+    Ok(())
+}
+
+fn write_export(
+    lists: &[TodoList],
+    json: bool,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    if json {
+        let days: Vec<ExportedDay> = lists.iter().map(ExportedDay::from).collect();
+        serde_json::to_writer_pretty(&mut *out, &days)?;
+        writeln!(out)?;
+        return Ok(());
+    }
+
+    // Every list starts with its own `# TODO <date>` header
+    for (index, todo_list) in lists.iter().enumerate() {
+        if index > 0 {
+            writeln!(out)?;
+        }
+        // Lists saved without a final newline still need one in the bundle
+        let markdown = todo_list.to_markdown();
+        write!(out, "{}", markdown)?;
+        if !markdown.ends_with('\n') {
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the list of `date` on its own, as Markdown or as a standalone
+/// HTML page
+pub fn run_day(
+    config_dir: &Path,
+    config: &Config,
+    date: NaiveDate,
+    html: bool,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let path = todo_file_path(config_dir, config, date);
+    if !path.exists() {
+        return Err(format!("There is no todo file for {}", date).into());
+    }
+    let todo_list = read_todo_list(&path, &config.format)?;
+    let document = if html {
+        to_html(&todo_list, config)
+    } else {
+        todo_list.to_markdown()
+    };
+    write!(out, "{}", document)?;
+    Ok(())
+}
+
+const HTML_STYLE: &str = "\
+body { font-family: sans-serif; max-width: 40em; margin: 2em auto; line-height: 1.5; }
+ul { list-style: none; padding-left: 1.5em; }
+body > ul { padding-left: 0; }
+li.note { list-style: disc; margin-left: 1.2em; }
+li.done > span { color: #888; text-decoration: line-through; }
+blockquote { color: #555; }
+";
+
+/// The list as a standalone HTML page: its title, the note of the day and
+/// the items as nested lists with read-only checkboxes for tasks
+fn to_html(todo_list: &TodoList, config: &Config) -> String {
+    let date = todo_list.date.format("%Y-%m-%d");
+    let title = match todo_list.title.as_ref().or(config.title.as_ref()) {
+        Some(title) => format!("{}{}{}", title, config.title_separator(), date),
+        None => format!("TODO {}", date),
+    };
+    let title = escape_html(&title);
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+        title, HTML_STYLE, title
+    );
+    if let Some(note) = &todo_list.note {
+        html.push_str(&format!("<blockquote>{}</blockquote>\n", escape_html(note)));
+    }
+
+    // Levels a file skips would need lists without items in between
+    let mut reflowed = TodoList::new(todo_list.date);
+    reflowed.items = todo_list.items.clone();
+    reflowed.reflow_indents();
+    let items = &reflowed.items;
+    for (index, item) in items.iter().enumerate() {
+        let level = item.indent_level;
+        let pad = |level: usize| "    ".repeat(level);
+        if index == 0 || level > items[index - 1].indent_level {
+            html.push_str(&format!("{}<ul>\n", pad(level)));
+        }
+        let class = match (item.is_task, item.completed) {
+            (false, _) => " class=\"note\"",
+            (true, true) => " class=\"done\"",
+            (true, false) => "",
+        };
+        let checkbox = match (item.is_task, item.completed) {
+            (false, _) => "",
+            (true, true) => "<input type=\"checkbox\" checked disabled> ",
+            (true, false) => "<input type=\"checkbox\" disabled> ",
+        };
+        html.push_str(&format!(
+            "{}  <li{}>{}<span>{}</span>",
+            pad(level),
+            class,
+            checkbox,
+            escape_html(&item.text)
+        ));
+
+        // Subitems go into the item; otherwise it ends here, along with the
+        // lists of the levels the next item is above
+        let next_level = items.get(index + 1).map(|item| item.indent_level);
+        if next_level.is_some_and(|next_level| next_level > level) {
+            html.push('\n');
+            continue;
+        }
+        html.push_str("</li>\n");
+        let next_level = next_level.unwrap_or(0);
+        for level in (next_level + 1..=level).rev() {
+            html.push_str(&format!("{}</ul>\n", pad(level)));
+            html.push_str(&format!("{}  </li>\n", pad(level - 1)));
+        }
+        if index + 1 == items.len() {
+            html.push_str("</ul>\n");
+        }
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::commands::test_util::write_fixture;
+
+    fn fixture_dir() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        write_fixture(dir.path(), "2025-08-14", "* [ ] c\n");
+        write_fixture(dir.path(), "2025-08-10", "* [x] a\n  * [ ] a1\n");
+        write_fixture(dir.path(), "2025-08-12", "* [ ] b\n");
+        write_fixture(dir.path(), "2025-09-01", "* [ ] future\n");
+        fs::write(dir.path().join("TODO-2025-08-11.md"), "garbage").unwrap();
+        dir
+    }
+
+    fn export(range: DateRange, json: bool) -> String {
+        let dir = fixture_dir();
+        let today = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut out = Vec::new();
+        run(
+            dir.path(),
+            &Config::default(),
+            today,
+            range,
+            json,
+            None,
+            &mut out,
+        )
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_parse_range() {
+        let date = |d| NaiveDate::from_ymd_opt(2025, 8, d);
+        assert_eq!(
+            parse_range("2025-08-10..2025-08-12"),
+            Ok(DateRange {
+                start: date(10),
+                end: date(12)
+            })
+        );
+        assert_eq!(
+            parse_range("..2025-08-12"),
+            Ok(DateRange {
+                start: None,
+                end: date(12)
+            })
+        );
+        assert!(parse_range("2025-08-10").is_err());
+        assert!(parse_range("2025-08-10..tomorrow").is_err());
+    }
+
+    #[test]
+    fn test_export_markdown_in_date_order() {
+        assert_eq!(
+            export(DateRange::default(), false),
+            "# TODO 2025-08-10\n\n* [x] a\n  * [ ] a1\n\n\
+             # TODO 2025-08-12\n\n* [ ] b\n\n\
+             # TODO 2025-08-14\n\n* [ ] c\n"
+        );
+    }
+
+    #[test]
+    fn test_export_without_final_newlines() {
+        let dir = fixture_dir();
+        let today = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut config = Config::default();
+        config.format.final_newline = false;
+        let range = parse_range("2025-08-12..").unwrap();
+        let mut out = Vec::new();
+        run(dir.path(), &config, today, range, false, None, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "# TODO 2025-08-12\n\n* [ ] b\n\n# TODO 2025-08-14\n\n* [ ] c\n"
+        );
+    }
+
+    #[test]
+    fn test_export_range() {
+        let range = parse_range("2025-08-11..2025-08-12").unwrap();
+        assert_eq!(export(range, false), "# TODO 2025-08-12\n\n* [ ] b\n");
+    }
+
+    #[test]
+    fn test_export_json() {
+        let days: Vec<ExportedDay> =
+            serde_json::from_str(&export(DateRange::default(), true)).unwrap();
+        let dates: Vec<String> = days.iter().map(|day| day.date.to_string()).collect();
+        assert_eq!(dates, ["2025-08-10", "2025-08-12", "2025-08-14"]);
+        assert_eq!(days[0].items[1].text, "a1");
+        assert_eq!(days[0].items[1].indent_level, 1);
+        assert!(days[0].items[0].completed);
+    }
+
+    #[test]
+    fn test_export_to_file() {
+        let dir = fixture_dir();
+        let out_path = dir.path().join("backup.md");
+        let today = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let range = parse_range("2025-08-14..").unwrap();
+        let mut stdout = Vec::new();
+        let config = Config::default();
+        run(
+            dir.path(),
+            &config,
+            today,
+            range,
+            false,
+            Some(&out_path),
+            &mut stdout,
+        )
+        .unwrap();
+
+        assert!(stdout.is_empty());
+        assert_eq!(
+            fs::read_to_string(out_path).unwrap(),
+            "# TODO 2025-08-14\n\n* [ ] c\n"
+        );
+    }
+
+    #[test]
+    fn test_export_day_as_html() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("TODO-2025-08-14.md"),
+            "# TODO 2025-08-14\n<!-- title: Home & Garden -->\n\n\
+             * [x] mow <lawn>\n  * [ ] sharpen \"blades\"\n      * [x] buy oil\n\
+             * ask Bob's neighbour\n* [ ] water\n",
+        )
+        .unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut out = Vec::new();
+        run_day(dir.path(), &Config::default(), date, true, &mut out).unwrap();
+        let html = String::from_utf8(out).unwrap();
+
+        assert!(html.starts_with("<!DOCTYPE html>\n"));
+        assert!(html.contains("<title>Home &amp; Garden — 2025-08-14</title>"));
+        let body = &html[html.find("<ul>").unwrap()..html.find("</body>").unwrap()];
+        assert_eq!(
+            body,
+            "<ul>\n\
+             \x20 <li class=\"done\"><input type=\"checkbox\" checked disabled> <span>mow &lt;lawn&gt;</span>\n\
+             \x20   <ul>\n\
+             \x20     <li><input type=\"checkbox\" disabled> <span>sharpen &quot;blades&quot;</span>\n\
+             \x20       <ul>\n\
+             \x20         <li class=\"done\"><input type=\"checkbox\" checked disabled> <span>buy oil</span></li>\n\
+             \x20       </ul>\n\
+             \x20     </li>\n\
+             \x20   </ul>\n\
+             \x20 </li>\n\
+             \x20 <li class=\"note\"><span>ask Bob&#39;s neighbour</span></li>\n\
+             \x20 <li><input type=\"checkbox\" disabled> <span>water</span></li>\n\
+             </ul>\n"
+        );
+        // Opening and closing tags match
+        assert_eq!(body.matches("<ul>").count(), body.matches("</ul>").count());
+        assert_eq!(body.matches("<li").count(), body.matches("</li>").count());
+    }
+
+    #[test]
+    fn test_export_day_as_markdown_and_missing_day() {
+        let dir = fixture_dir();
+        let date = NaiveDate::from_ymd_opt(2025, 8, 10).unwrap();
+        let mut out = Vec::new();
+        run_day(dir.path(), &Config::default(), date, false, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "# TODO 2025-08-10\n\n* [x] a\n  * [ ] a1\n"
+        );
+
+        let date = NaiveDate::from_ymd_opt(2025, 8, 13).unwrap();
+        assert!(run_day(dir.path(), &Config::default(), date, true, &mut Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,273 @@
+use std::{error::Error, fs, io::Write, path::Path};
+
+use super::export::ExportedDay;
+use crate::{TodoItem, TodoList, config::Config, create_lock_file, write_atomically};
+
+impl ExportedDay {
+    fn into_todo_list(self, config: &Config) -> TodoList {
+        let mut todo_list = TodoList::new(self.date);
+        todo_list.format = config.format.clone();
+        todo_list.title = self.title;
+        todo_list.note = self.note;
+        todo_list.items = self
+            .items
+            .into_iter()
+            .map(|item| {
+                TodoItem::builder()
+                    .text(item.text)
+                    .completed(item.completed)
+                    .indent(item.indent_level)
+                    .task(item.is_task)
+                    .build()
+            })
+            .collect();
+        todo_list
+    }
+}
+
+/// Recreates the todo files from a bundle written by `export-all`, merging
+/// duplicate items within each day if `dedup` is set. Holds the lock file,
+/// since it may write today's file. With `single_file` there are no files
+/// per day to recreate, so it refuses.
+pub fn run(
+    bundle: &Path,
+    config_dir: &Path,
+    config: &Config,
+    force: bool,
+    dedup: bool,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    if config.single_file {
+        return Err("import-all writes a file per day, which single_file doesn't use".into());
+    }
+    let content = fs::read_to_string(bundle)?;
+    let mut lists: Vec<TodoList> = if bundle.extension().is_some_and(|ext| ext == "json") {
+        let days: Vec<ExportedDay> = serde_json::from_str(&content)?;
+        days.into_iter()
+            .map(|day| day.into_todo_list(config))
+            .collect()
+    } else {
+        parse_markdown_bundle(&content, config)?
+    };
+
+    if dedup {
+        let merged: usize = lists
+            .iter_mut()
+            .map(|todo_list| todo_list.dedup(config.dedup_prefer_completed))
+            .sum();
+        writeln!(out, "Merged {} duplicates", merged)?;
+    }
+
+    let lock_file = create_lock_file(config_dir)?;
+    let result = write_lists(&lists, config_dir, force, out);
+    fs::remove_file(lock_file)?;
+    result
+}
+
+fn write_lists(
+    lists: &[TodoList],
+    config_dir: &Path,
+    force: bool,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let mut created = 0;
+    let mut skipped = 0;
+    for todo_list in lists {
+        let path = config_dir.join(todo_list.filename());
+        if path.exists() && !force {
+            writeln!(out, "Skipping existing {}", path.display())?;
+            skipped += 1;
+            continue;
+        }
+        write_atomically(&path, &todo_list.to_markdown())?;
+        created += 1;
+    }
+    writeln!(out, "Created {} files, skipped {}", created, skipped)?;
+    Ok(())
+}
+
+/// Splits a Markdown bundle into its days at the `# TODO <date>` headers
+fn parse_markdown_bundle(content: &str, config: &Config) -> Result<Vec<TodoList>, Box<dyn Error>> {
+    let mut chunks: Vec<String> = Vec::new();
+    for line in content.lines() {
+        if line.starts_with("# TODO ") {
+            chunks.push(String::new());
+        }
+        let Some(chunk) = chunks.last_mut() else {
+            if line.trim().is_empty() {
+                continue;
+            }
+            return Err(format!("Expected a '# TODO <date>' header, found: {}", line).into());
+        };
+        chunk.push_str(line);
+        chunk.push('\n');
+    }
+
+    chunks
+        .iter()
+        .map(|chunk| TodoList::from_markdown_with(chunk, config.format.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::commands::export::{self, DateRange};
+
+    const FIXTURES: [(&str, &str); 3] = [
+        (
+            "TODO-2025-08-10.md",
+            "# TODO 2025-08-10\n\n* [x] a\n  * [ ] a1\n",
+        ),
+        (
+            "TODO-2025-08-12.md",
+            "# TODO 2025-08-12\n<!-- title: Work -->\n\n* [ ] b\n",
+        ),
+        ("TODO-2025-08-14.md", "# TODO 2025-08-14\n\n* [ ] c\n"),
+    ];
+
+    fn round_trip(bundle_name: &str, json: bool) {
+        let source = tempfile::tempdir().unwrap();
+        for (name, content) in FIXTURES {
+            fs::write(source.path().join(name), content).unwrap();
+        }
+        let bundle = source.path().join(bundle_name);
+        let today = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let config = Config::default();
+        let range = DateRange::default();
+        export::run(
+            source.path(),
+            &config,
+            today,
+            range,
+            json,
+            Some(&bundle),
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        let target = tempfile::tempdir().unwrap();
+        let mut out = Vec::new();
+        run(&bundle, target.path(), &config, false, false, &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Created 3 files, skipped 0\n"
+        );
+        for (name, content) in FIXTURES {
+            assert_eq!(
+                fs::read_to_string(target.path().join(name)).unwrap(),
+                content
+            );
+        }
+        // The lock is released again
+        assert!(!target.path().join("lockfile").exists());
+    }
+
+    #[test]
+    fn test_markdown_round_trip() {
+        round_trip("backup.md", false);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        round_trip("backup.json", true);
+    }
+
+    #[test]
+    fn test_import_refuses_single_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("backup.md");
+        fs::write(&bundle, "# TODO 2025-08-10\n\n* [ ] a\n").unwrap();
+        let config = Config {
+            single_file: true,
+            ..Config::default()
+        };
+        let result = run(&bundle, dir.path(), &config, false, false, &mut Vec::new());
+        assert!(result.is_err());
+        assert!(crate::todo_files(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_import_keeps_existing_files_unless_forced() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("backup.md");
+        fs::write(
+            &bundle,
+            "# TODO 2025-08-10\n\n* [ ] new\n\n# TODO 2025-08-11\n\n* [ ] other\n",
+        )
+        .unwrap();
+        let existing = dir.path().join("TODO-2025-08-10.md");
+        fs::write(&existing, "# TODO 2025-08-10\n\n* [ ] old\n").unwrap();
+
+        let mut out = Vec::new();
+        run(
+            &bundle,
+            dir.path(),
+            &Config::default(),
+            false,
+            false,
+            &mut out,
+        )
+        .unwrap();
+        assert!(
+            String::from_utf8(out)
+                .unwrap()
+                .ends_with("Created 1 files, skipped 1\n")
+        );
+        assert!(fs::read_to_string(&existing).unwrap().contains("old"));
+
+        run(
+            &bundle,
+            dir.path(),
+            &Config::default(),
+            true,
+            false,
+            &mut Vec::new(),
+        )
+        .unwrap();
+        assert!(fs::read_to_string(&existing).unwrap().contains("new"));
+    }
+
+    #[test]
+    fn test_import_can_merge_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("backup.md");
+        fs::write(
+            &bundle,
+            "# TODO 2025-08-10\n\n* [ ] a\n* [x] a \n\n# TODO 2025-08-11\n\n* [ ] a\n",
+        )
+        .unwrap();
+
+        let mut out = Vec::new();
+        run(
+            &bundle,
+            dir.path(),
+            &Config::default(),
+            false,
+            true,
+            &mut out,
+        )
+        .unwrap();
+        assert!(
+            String::from_utf8(out)
+                .unwrap()
+                .starts_with("Merged 1 duplicates\n")
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("TODO-2025-08-10.md")).unwrap(),
+            "# TODO 2025-08-10\n\n* [x] a\n"
+        );
+        // Copies on different days aren't duplicates
+        assert!(dir.path().join("TODO-2025-08-11.md").exists());
+    }
+
+    #[test]
+    fn test_import_rejects_invalid_bundles() {
+        let config = Config::default();
+        assert!(parse_markdown_bundle("* [ ] no header\n", &config).is_err());
+        assert!(parse_markdown_bundle("# TODO 2025-13-40\n\n* [ ] a\n", &config).is_err());
+    }
+}
@@ -0,0 +1,94 @@
+use std::{error::Error, io::Write, path::Path};
+
+use chrono::NaiveDate;
+
+use crate::{TodoList, config::Config, load_or_create_todo_list};
+
+/// Which items `list` prints
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ItemFilter {
+    All,
+    CompletedOnly,
+    IncompleteOnly,
+}
+
+impl ItemFilter {
+    fn matches(self, completed: bool) -> bool {
+        match self {
+            ItemFilter::All => true,
+            ItemFilter::CompletedOnly => completed,
+            ItemFilter::IncompleteOnly => !completed,
+        }
+    }
+}
+
+pub fn run(
+    config_dir: &Path,
+    config: &Config,
+    today: NaiveDate,
+    filter: ItemFilter,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let mut todo_list = load_or_create_todo_list(config_dir, today, config)?;
+    filter_items(&mut todo_list, filter);
+    write!(out, "{}", todo_list.to_markdown())?;
+    Ok(())
+}
+
+/// Keeps only the matching items. Items keep their original indent, so a
+/// matching child of a filtered-out parent stays nested rather than being
+/// moved up a level.
+fn filter_items(todo_list: &mut TodoList, filter: ItemFilter) {
+    todo_list
+        .items
+        .retain(|item| filter.matches(item.completed));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn fixture_dir() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("TODO-2025-08-14.md"),
+            "# TODO 2025-08-14\n\n\
+             * [x] done\n\
+             * [ ] open parent\n  * [x] done child\n  * [ ] open child\n\
+             * [x] done parent\n  * [ ] open child 2\n",
+        )
+        .unwrap();
+        dir
+    }
+
+    fn list(filter: ItemFilter) -> String {
+        let dir = fixture_dir();
+        let today = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut out = Vec::new();
+        run(dir.path(), &Config::default(), today, filter, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_list_completed_only() {
+        assert_eq!(
+            list(ItemFilter::CompletedOnly),
+            "# TODO 2025-08-14\n\n* [x] done\n  * [x] done child\n* [x] done parent\n"
+        );
+    }
+
+    #[test]
+    fn test_list_incomplete_only() {
+        assert_eq!(
+            list(ItemFilter::IncompleteOnly),
+            "# TODO 2025-08-14\n\n* [ ] open parent\n  * [ ] open child\n  * [ ] open child 2\n"
+        );
+    }
+
+    #[test]
+    fn test_list_all() {
+        assert_eq!(list(ItemFilter::All).lines().count(), 8);
+    }
+}
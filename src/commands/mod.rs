@@ -0,0 +1,404 @@
+//! Subcommands besides the full interactive app. Unless noted otherwise
+//! these only read the todo files in the config directory and therefore run
+//! without taking the lock file.
+
+use std::{
+    error::Error,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{Local, NaiveDate};
+use clap::Subcommand;
+
+use crate::{config::Config, write_atomically};
+
+mod add;
+mod capture;
+mod check;
+mod compact;
+mod dates;
+mod dedup;
+mod export;
+mod import;
+mod list;
+mod normalize;
+mod query;
+mod reflow;
+mod reindent;
+#[cfg(feature = "serve")]
+mod serve;
+mod stats;
+
+pub use compact::prune;
+use export::DateRange;
+use list::ItemFilter;
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Append an item to today's list, or another day's (holds the lock file)
+    Add {
+        /// Text of the new item
+        text: String,
+
+        /// Add to this day (YYYY-MM-DD) instead of today, creating its file if needed
+        #[arg(long, value_parser = parse_date)]
+        append_to: Option<NaiveDate>,
+
+        /// Allow adding to a day more than two weeks ahead
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Write a new item for today's list in a minimal prompt and quit (holds the lock file)
+    Capture {
+        /// Keep capturing items until one is confirmed empty
+        #[arg(long)]
+        stay: bool,
+    },
+
+    /// Parse a todo file anywhere and print what is read from it, or why it can't be read
+    Check {
+        /// Todo file to check
+        file: PathBuf,
+    },
+
+    /// Print today's list as Markdown
+    List {
+        /// Only print completed items
+        #[arg(long, conflicts_with = "incomplete_only")]
+        completed_only: bool,
+
+        /// Only print items that are not completed yet
+        #[arg(long)]
+        incomplete_only: bool,
+    },
+
+    /// Merge old todo files into one ARCHIVE-YYYY.md per year and delete them (holds the lock file)
+    Compact {
+        /// Archive files older than this many days, e.g. 90d
+        #[arg(long, value_parser = compact::parse_days)]
+        older_than: u32,
+
+        /// Only print what would be archived
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// List the dates that have a todo file, oldest first
+    Dates {
+        /// Also print the number of items and completed items per day
+        #[arg(long)]
+        counts: bool,
+
+        /// Print the dates as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Merge items on today's list that have the same text under the same parent (holds the lock file)
+    Dedup,
+
+    /// Write all todo lists into a single Markdown or JSON document
+    ExportAll {
+        /// File to write to instead of stdout
+        #[arg(long)]
+        out: Option<PathBuf>,
+
+        /// Export a JSON array of days instead of Markdown
+        #[arg(long)]
+        json: bool,
+
+        /// Only export the days in this inclusive range, e.g. 2025-01-01..2025-06-30
+        #[arg(long, value_parser = export::parse_range)]
+        range: Option<DateRange>,
+    },
+
+    /// Write one day's list as Markdown, or as an HTML page with --html
+    Export {
+        /// Day (YYYY-MM-DD) of the list to export instead of today
+        #[arg(long, value_parser = parse_date)]
+        date: Option<NaiveDate>,
+
+        /// Write a standalone HTML page with nested lists and checkboxes
+        #[arg(long)]
+        html: bool,
+    },
+
+    /// Recreate todo files from a bundle written by export-all (holds the lock file; not with single_file)
+    ImportAll {
+        /// Bundle to import; `.json` files are read as JSON, anything else as Markdown
+        bundle: PathBuf,
+
+        /// Overwrite existing todo files
+        #[arg(long)]
+        force: bool,
+
+        /// Merge duplicate items within each imported day
+        #[arg(long)]
+        dedup: bool,
+    },
+
+    /// Print a one-line summary of today's list, e.g. for status bars
+    Query {
+        /// Print the summary as a JSON object on one line
+        #[arg(long)]
+        json: bool,
+
+        /// Print the summary again whenever a todo file changes
+        #[cfg(feature = "watch")]
+        #[arg(long)]
+        watch: bool,
+    },
+
+    /// Rewrite a todo file with canonical spacing, indentation and line breaks (holds the lock file)
+    Normalize {
+        /// Day (YYYY-MM-DD) of the file to rewrite instead of today
+        #[arg(long, value_parser = parse_date)]
+        date: Option<NaiveDate>,
+
+        /// Print the rewritten file instead of writing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Close gaps in the indentation of a todo file so no item is more than one level below the one before it (holds the lock file)
+    Reflow {
+        /// Day (YYYY-MM-DD) of the file to rewrite instead of today
+        #[arg(long, value_parser = parse_date)]
+        date: Option<NaiveDate>,
+
+        /// Print the rewritten file instead of writing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Rewrite a todo file with a different number of spaces per indentation level (holds the lock file)
+    Reindent {
+        /// Spaces per indentation level to write
+        #[arg(long)]
+        width: usize,
+
+        /// Spaces per indentation level the file uses now, if not `indent_width` from the config
+        #[arg(long)]
+        from: Option<usize>,
+
+        /// Day (YYYY-MM-DD) of the file to rewrite instead of today
+        #[arg(long, value_parser = parse_date)]
+        date: Option<NaiveDate>,
+
+        /// Print the rewritten file instead of writing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Show completion statistics over the most recent days
+    Stats {
+        /// Number of most recent todo files to analyze
+        #[arg(long, default_value_t = 30)]
+        days: usize,
+
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Accept commands for today's list on a Unix socket (holds the lock file)
+    #[cfg(feature = "serve")]
+    Serve {
+        /// Path of the socket to listen on
+        #[arg(long)]
+        socket: PathBuf,
+    },
+}
+
+/// Runs `command`, writing what it prints to the file `output` instead of
+/// stdout if given. The file is only written once the command succeeded.
+pub fn run(
+    command: Command,
+    config_dir: &Path,
+    config: &Config,
+    output: Option<&Path>,
+) -> Result<(), Box<dyn Error>> {
+    let Some(output) = output else {
+        return run_with_output(command, config_dir, config, &mut io::stdout().lock());
+    };
+    #[cfg(feature = "watch")]
+    if let Command::Query { watch: true, .. } = command {
+        return Err("--output can't be combined with query --watch".into());
+    }
+
+    let mut buffer = Vec::new();
+    run_with_output(command, config_dir, config, &mut buffer)?;
+    write_output(output, &String::from_utf8(buffer)?)
+}
+
+/// Writes `content` to `path` atomically, creating missing parent directories
+fn write_output(path: &Path, content: &str) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)
+            .map_err(|err| format!("Could not create {}: {}", parent.display(), err))?;
+    }
+    write_atomically(path, content)
+        .map_err(|err| format!("Could not write {}: {}", path.display(), err).into())
+}
+
+fn run_with_output(
+    command: Command,
+    config_dir: &Path,
+    config: &Config,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let today = Local::now().date_naive();
+
+    match command {
+        Command::Add {
+            text,
+            append_to,
+            force,
+        } => add::run(
+            config_dir,
+            config,
+            today,
+            text,
+            append_to.unwrap_or(today),
+            force,
+            out,
+        ),
+        Command::Capture { stay } => capture::run(config_dir, config, today, stay),
+        Command::Check { file } => check::run(&file, config, out),
+        Command::List {
+            completed_only,
+            incomplete_only,
+        } => {
+            let filter = if completed_only {
+                ItemFilter::CompletedOnly
+            } else if incomplete_only {
+                ItemFilter::IncompleteOnly
+            } else {
+                ItemFilter::All
+            };
+            list::run(config_dir, config, today, filter, out)
+        }
+        Command::Compact {
+            older_than,
+            dry_run,
+        } => compact::run(config_dir, config, today, older_than, dry_run, out),
+        Command::Dates { counts, json } => dates::run(config_dir, config, today, counts, json, out),
+        Command::Dedup => dedup::run(config_dir, config, today, out),
+        Command::ExportAll {
+            out: out_path,
+            json,
+            range,
+        } => export::run(
+            config_dir,
+            config,
+            today,
+            range.unwrap_or_default(),
+            json,
+            out_path.as_deref(),
+            out,
+        ),
+        Command::Export { date, html } => {
+            export::run_day(config_dir, config, date.unwrap_or(today), html, out)
+        }
+        Command::ImportAll {
+            bundle,
+            force,
+            dedup,
+        } => import::run(&bundle, config_dir, config, force, dedup, out),
+        Command::Query {
+            json,
+            #[cfg(feature = "watch")]
+            watch,
+        } => {
+            #[cfg(feature = "watch")]
+            if watch {
+                return query::watch(config_dir, config, json, out);
+            }
+            query::run(config_dir, config, today, json, out)
+        }
+        Command::Normalize { date, dry_run } => {
+            normalize::run(config_dir, config, date.unwrap_or(today), dry_run, out)
+        }
+        Command::Reflow { date, dry_run } => {
+            reflow::run(config_dir, config, date.unwrap_or(today), dry_run, out)
+        }
+        Command::Reindent {
+            width,
+            from,
+            date,
+            dry_run,
+        } => reindent::run(
+            config_dir,
+            config,
+            date.unwrap_or(today),
+            width,
+            from,
+            dry_run,
+            out,
+        ),
+        Command::Stats { days, json } => stats::run(config_dir, config, today, days, json, out),
+        #[cfg(feature = "serve")]
+        Command::Serve { socket } => serve::run(&socket, config_dir, config, today),
+    }
+}
+
+/// Parses a date given as YYYY-MM-DD on the command line
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("invalid date '{}', expected YYYY-MM-DD", value))
+}
+
+/// Helpers shared by the tests of the subcommands
+#[cfg(test)]
+mod test_util {
+    use std::{fs, path::Path};
+
+    /// Writes the todo file of `date` (YYYY-MM-DD) with the given item lines
+    pub fn write_fixture(dir: &Path, date: &str, items: &str) {
+        fs::write(
+            dir.join(format!("TODO-{}.md", date)),
+            format!("# TODO {}\n\n{}", date, items),
+        )
+        .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_writes_what_would_be_printed() {
+        let dir = tempfile::tempdir().unwrap();
+        let today = Local::now().date_naive();
+        fs::write(
+            dir.path()
+                .join(format!("TODO-{}.md", today.format("%Y-%m-%d"))),
+            format!(
+                "# TODO {}\n\n* [x] a\n  * [ ] b\n",
+                today.format("%Y-%m-%d")
+            ),
+        )
+        .unwrap();
+        let config = Config::default();
+        let list = || Command::List {
+            completed_only: false,
+            incomplete_only: false,
+        };
+
+        let mut printed = Vec::new();
+        run_with_output(list(), dir.path(), &config, &mut printed).unwrap();
+        let output = dir.path().join("reports/today.md");
+        run(list(), dir.path(), &config, Some(&output)).unwrap();
+        assert_eq!(fs::read(&output).unwrap(), printed);
+
+        // A directory can't be written as a file
+        assert!(run(list(), dir.path(), &config, Some(dir.path())).is_err());
+    }
+}
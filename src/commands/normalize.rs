@@ -0,0 +1,181 @@
+use std::{error::Error, fs, io::Write, path::Path};
+
+use chrono::NaiveDate;
+
+use super::check::ignored_lines;
+use crate::{TodoList, config::Config, create_lock_file, todo_file_path, write_atomically};
+
+/// Rewrites the list of `date` the way the app writes files: one space after
+/// the bullet and checkbox, `indent_width` spaces per level, no trailing
+/// whitespace and the configured blank lines and final newline. Text,
+/// completion and levels stay the same. With `dry_run` the result is printed
+/// instead of written. Holds the lock file unless it's a dry run.
+pub fn run(
+    config_dir: &Path,
+    config: &Config,
+    date: NaiveDate,
+    dry_run: bool,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let path = todo_file_path(config_dir, config, date);
+    if !path.exists() {
+        return Err(format!("There is no todo file for {}", date).into());
+    }
+
+    if dry_run {
+        write!(out, "{}", normalized(&path, config)?.1)?;
+        return Ok(());
+    }
+
+    let lock_file = create_lock_file(config_dir)?;
+    let result = normalized(&path, config).and_then(|(content, normalized)| {
+        if content != normalized {
+            write_atomically(&path, &normalized)?;
+        }
+        Ok(content != normalized)
+    });
+    fs::remove_file(lock_file)?;
+    if result? {
+        writeln!(out, "Normalized {}", path.display())?;
+    } else {
+        writeln!(out, "{} is already normalized", path.display())?;
+    }
+    Ok(())
+}
+
+/// Reads the file at `path` to write it back changed, returning its content
+/// and the parsed list. Lines the parser skips would be lost by rewriting
+/// the file, and lines it had to guess the level of would be moved, so
+/// either makes it fail instead.
+pub fn read_for_rewrite(
+    path: &Path,
+    config: &Config,
+) -> Result<(String, TodoList), Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+    if let Some((line_number, line)) = ignored_lines(&content).first() {
+        return Err(format!(
+            "{}:{}: not a todo item, fix or remove it first: {}",
+            path.display(),
+            line_number,
+            line
+        )
+        .into());
+    }
+    let todo_list = TodoList::from_markdown_with(&content, config.format.clone())?;
+    if let Some(warning) = todo_list.warnings.first() {
+        return Err(format!("{}: {}, fix it first", path.display(), warning).into());
+    }
+    Ok((content, todo_list))
+}
+
+// The file's content and its normalized form
+fn normalized(path: &Path, config: &Config) -> Result<(String, String), Box<dyn Error>> {
+    let (content, mut todo_list) = read_for_rewrite(path, config)?;
+    for item in &mut todo_list.items {
+        item.text = item.text.trim().to_string();
+    }
+    // Empty tasks are written as `* [ ] ` with a trailing space
+    let markdown = todo_list.to_markdown();
+    let mut normalized: String = markdown
+        .lines()
+        .map(|line| format!("{}\n", line.trim_end()))
+        .collect();
+    if !markdown.ends_with('\n') {
+        normalized.pop();
+    }
+    Ok((content, normalized))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSY: &str =
+        "# TODO 2025-08-14\n\n\n* [x]   a  \n  * [ ] b\t\n\t* [ ]\n  *  spaced note \n* [ ] d";
+    const NORMALIZED: &str =
+        "# TODO 2025-08-14\n\n* [x] a\n  * [ ] b\n    * [ ]\n  * spaced note\n* [ ] d\n";
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 8, 14).unwrap()
+    }
+
+    #[test]
+    fn test_normalize_is_idempotent() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("TODO-2025-08-14.md");
+        fs::write(&path, MESSY).unwrap();
+        let config = Config::default();
+
+        let mut out = Vec::new();
+        run(dir.path(), &config, date(), false, &mut out).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), NORMALIZED);
+        assert!(String::from_utf8(out).unwrap().starts_with("Normalized "));
+        assert!(!dir.path().join("lockfile").exists());
+
+        let mut out = Vec::new();
+        run(dir.path(), &config, date(), false, &mut out).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), NORMALIZED);
+        assert!(
+            String::from_utf8(out)
+                .unwrap()
+                .ends_with("is already normalized\n")
+        );
+    }
+
+    #[test]
+    fn test_normalize_dry_run_only_prints() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("TODO-2025-08-14.md");
+        fs::write(&path, MESSY).unwrap();
+
+        let mut out = Vec::new();
+        run(dir.path(), &Config::default(), date(), true, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), NORMALIZED);
+        assert_eq!(fs::read_to_string(&path).unwrap(), MESSY);
+    }
+
+    #[test]
+    fn test_normalize_keeps_lines_it_cannot_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("TODO-2025-08-14.md");
+        let content = "# TODO 2025-08-14\n\n* [ ] a\n*[ ] typo\n";
+        fs::write(&path, content).unwrap();
+
+        let err = run(
+            dir.path(),
+            &Config::default(),
+            date(),
+            false,
+            &mut Vec::new(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains(":4: not a todo item"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+
+        // Lines whose level had to be guessed aren't moved either
+        let content = "# TODO 2025-08-14\n\n* [ ] a\n   * [ ] odd\n";
+        fs::write(&path, content).unwrap();
+        let err = run(
+            dir.path(),
+            &Config::default(),
+            date(),
+            false,
+            &mut Vec::new(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().ends_with(
+            "line 4: indentation of 3 spaces isn't a multiple of 2, read as level 1, fix it first"
+        ));
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+        assert!(
+            run(
+                dir.path(),
+                &Config::default(),
+                date(),
+                true,
+                &mut Vec::new()
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use std::{error::Error, io::Write, path::Path};
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::{TodoList, config::Config, load_or_create_todo_list};
+
+/// Summary of today's list for status bars
+#[derive(Debug, PartialEq, Serialize)]
+struct Snapshot {
+    date: NaiveDate,
+    total: usize,
+    completed: usize,
+    open: usize,
+    /// Text of the first open task
+    next: Option<String>,
+}
+
+impl From<&TodoList> for Snapshot {
+    fn from(todo_list: &TodoList) -> Self {
+        let tasks: Vec<_> = todo_list.items.iter().filter(|item| item.is_task).collect();
+        let completed = tasks.iter().filter(|item| item.completed).count();
+        Snapshot {
+            date: todo_list.date,
+            total: tasks.len(),
+            completed,
+            open: tasks.len() - completed,
+            next: tasks
+                .iter()
+                .find(|item| !item.completed)
+                .map(|item| item.text.clone()),
+        }
+    }
+}
+
+/// Prints a one-line summary of today's list
+pub fn run(
+    config_dir: &Path,
+    config: &Config,
+    today: NaiveDate,
+    json: bool,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let todo_list = load_or_create_todo_list(config_dir, today, config)?;
+    let snapshot = Snapshot::from(&todo_list);
+    if json {
+        // One line per snapshot, so watchers can read them as they come
+        serde_json::to_writer(&mut *out, &snapshot)?;
+        writeln!(out)?;
+    } else {
+        write!(out, "{}/{} done", snapshot.completed, snapshot.total)?;
+        if let Some(next) = &snapshot.next {
+            write!(out, ", next: {}", next)?;
+        }
+        writeln!(out)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Prints the summary, then again after every change to the todo files until
+/// interrupted
+#[cfg(feature = "watch")]
+pub fn watch(
+    config_dir: &Path,
+    config: &Config,
+    json: bool,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    use chrono::Local;
+    use notify::{RecursiveMode, Watcher};
+
+    run(config_dir, config, Local::now().date_naive(), json, out)?;
+
+    let (sender, events) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    watcher.watch(config_dir, RecursiveMode::NonRecursive)?;
+    changes::wait_for_changes(&events, changes::DEBOUNCE, config.single_file, || {
+        run(config_dir, config, Local::now().date_naive(), json, out)
+    })
+}
+
+#[cfg(feature = "watch")]
+mod changes {
+    use std::{error::Error, sync::mpsc::Receiver, time::Duration};
+
+    use notify::Event;
+
+    use crate::{SINGLE_FILE_NAME, parse_todo_file_name};
+
+    /// How long the todo files have to stay untouched before a change is
+    /// reported; a save alone creates, writes and renames a file
+    pub const DEBOUNCE: Duration = Duration::from_millis(200);
+
+    /// Calls `on_change` once for every burst of events touching a todo file
+    /// (or `TODO.md` with `single_file`), after `debounce` passed without further
+    /// events. Returns when the watcher goes away.
+    pub fn wait_for_changes(
+        events: &Receiver<notify::Result<Event>>,
+        debounce: Duration,
+        single_file: bool,
+        mut on_change: impl FnMut() -> Result<(), Box<dyn Error>>,
+    ) -> Result<(), Box<dyn Error>> {
+        while let Ok(event) = events.recv() {
+            if !touches_todo_file(&event?, single_file) {
+                continue;
+            }
+            // Wait for the burst to end
+            while let Ok(event) = events.recv_timeout(debounce) {
+                event?;
+            }
+            on_change()?;
+        }
+        Ok(())
+    }
+
+    // Temporary files written while saving don't count, only the rename
+    // that puts them in place
+    fn touches_todo_file(event: &Event, single_file: bool) -> bool {
+        event.paths.iter().any(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| {
+                    parse_todo_file_name(name).is_some()
+                        || (single_file && name == SINGLE_FILE_NAME)
+                })
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::{path::PathBuf, sync::mpsc, thread};
+
+        use notify::{
+            EventKind,
+            event::{CreateKind, ModifyKind},
+        };
+
+        use super::*;
+
+        fn event(kind: EventKind, name: &str) -> notify::Result<Event> {
+            Ok(Event::new(kind).add_path(PathBuf::from("/todos").join(name)))
+        }
+
+        // Counts the changes reported for two quick saves of a dated file,
+        // followed by changes to other files and to `TODO.md`
+        fn count_changes(single_file: bool) -> usize {
+            let (sender, events) = mpsc::channel();
+            let writer = thread::spawn(move || {
+                // What a save looks like, twice in quick succession
+                for _ in 0..2 {
+                    let tmp = ".TODO-2025-08-14.md.tmp";
+                    sender
+                        .send(event(EventKind::Create(CreateKind::File), tmp))
+                        .unwrap();
+                    sender
+                        .send(event(EventKind::Modify(ModifyKind::Any), tmp))
+                        .unwrap();
+                    let rename = EventKind::Modify(ModifyKind::Name(notify::event::RenameMode::To));
+                    sender.send(event(rename, "TODO-2025-08-14.md")).unwrap();
+                    thread::sleep(Duration::from_millis(10));
+                }
+                thread::sleep(Duration::from_millis(300));
+                // Other files in the directory are ignored
+                sender
+                    .send(event(EventKind::Modify(ModifyKind::Any), "config.toml"))
+                    .unwrap();
+                sender
+                    .send(event(EventKind::Create(CreateKind::File), "lockfile"))
+                    .unwrap();
+                thread::sleep(Duration::from_millis(300));
+                sender
+                    .send(event(EventKind::Modify(ModifyKind::Any), "TODO.md"))
+                    .unwrap();
+            });
+
+            let mut changes = 0;
+            wait_for_changes(&events, Duration::from_millis(100), single_file, || {
+                changes += 1;
+                Ok(())
+            })
+            .unwrap();
+            writer.join().unwrap();
+            changes
+        }
+
+        #[test]
+        fn test_burst_of_changes_is_reported_once() {
+            assert_eq!(count_changes(false), 1);
+        }
+
+        #[test]
+        fn test_single_file_changes_are_reported() {
+            assert_eq!(count_changes(true), 2);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_query_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("TODO-2025-08-13.md"),
+            "# TODO 2025-08-13\n\n* [x] done\n* a note\n* [ ] next up\n* [ ] later\n",
+        )
+        .unwrap();
+        let today = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+
+        let mut out = Vec::new();
+        run(dir.path(), &Config::default(), today, true, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "{\"date\":\"2025-08-14\",\"total\":3,\"completed\":1,\"open\":2,\"next\":\"next up def:1\"}\n"
+        );
+
+        let mut out = Vec::new();
+        run(dir.path(), &Config::default(), today, false, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "1/3 done, next: next up def:1\n"
+        );
+    }
+}
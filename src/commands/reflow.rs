@@ -0,0 +1,89 @@
+use std::{error::Error, fs, io::Write, path::Path};
+
+use chrono::NaiveDate;
+
+use super::normalize::read_for_rewrite;
+use crate::{config::Config, create_lock_file, todo_file_path, write_atomically};
+
+/// Closes gaps in the indentation of the list of `date`, e.g. after editing
+/// it by hand, so every item is at most one level deeper than the one
+/// before it. With `dry_run` the result is printed instead of written. Holds
+/// the lock file unless it's a dry run.
+pub fn run(
+    config_dir: &Path,
+    config: &Config,
+    date: NaiveDate,
+    dry_run: bool,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let path = todo_file_path(config_dir, config, date);
+    if !path.exists() {
+        return Err(format!("There is no todo file for {}", date).into());
+    }
+
+    if dry_run {
+        let (_, mut todo_list) = read_for_rewrite(&path, config)?;
+        todo_list.reflow_indents();
+        write!(out, "{}", todo_list.to_markdown())?;
+        return Ok(());
+    }
+
+    let lock_file = create_lock_file(config_dir)?;
+    let result = read_for_rewrite(&path, config).and_then(|(_, mut todo_list)| {
+        let changed = todo_list.reflow_indents();
+        if changed > 0 {
+            write_atomically(&path, &todo_list.to_markdown())?;
+        }
+        Ok(changed)
+    });
+    fs::remove_file(lock_file)?;
+    match result? {
+        0 => writeln!(out, "{} has no indentation gaps", path.display())?,
+        1 => writeln!(out, "Moved 1 item in {}", path.display())?,
+        changed => writeln!(out, "Moved {} items in {}", changed, path.display())?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAPPY: &str = "# TODO 2025-08-14\n\n* [ ] a\n      * [ ] b\n      * [ ] c\n* [ ] d\n";
+    const REFLOWED: &str = "# TODO 2025-08-14\n\n* [ ] a\n  * [ ] b\n  * [ ] c\n* [ ] d\n";
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 8, 14).unwrap()
+    }
+
+    #[test]
+    fn test_reflow_closes_gaps() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("TODO-2025-08-14.md");
+        fs::write(&path, GAPPY).unwrap();
+        let config = Config::default();
+
+        let mut out = Vec::new();
+        run(dir.path(), &config, date(), true, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), REFLOWED);
+        assert_eq!(fs::read_to_string(&path).unwrap(), GAPPY);
+
+        let mut out = Vec::new();
+        run(dir.path(), &config, date(), false, &mut out).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), REFLOWED);
+        assert!(
+            String::from_utf8(out)
+                .unwrap()
+                .starts_with("Moved 2 items in ")
+        );
+        assert!(!dir.path().join("lockfile").exists());
+
+        let mut out = Vec::new();
+        run(dir.path(), &config, date(), false, &mut out).unwrap();
+        assert!(
+            String::from_utf8(out)
+                .unwrap()
+                .ends_with("has no indentation gaps\n")
+        );
+    }
+}
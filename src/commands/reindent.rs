@@ -0,0 +1,140 @@
+use std::{error::Error, fs, io::Write, path::Path};
+
+use chrono::NaiveDate;
+
+use crate::{
+    TodoList,
+    config::{Config, MarkdownFormat},
+    create_lock_file, read_todo_list, todo_file_path, write_atomically,
+};
+
+/// Rewrites the list of `date` with `width` spaces per indentation level,
+/// reading it with `from` spaces per level (the configured width if unset).
+/// Only the leading whitespace changes. With `dry_run` the result is printed
+/// instead of written. Holds the lock file unless it's a dry run.
+pub fn run(
+    config_dir: &Path,
+    config: &Config,
+    date: NaiveDate,
+    width: usize,
+    from: Option<usize>,
+    dry_run: bool,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    if width == 0 {
+        return Err("The indentation width must be at least 1".into());
+    }
+    let path = todo_file_path(config_dir, config, date);
+    if !path.exists() {
+        return Err(format!("There is no todo file for {}", date).into());
+    }
+
+    let old_format = MarkdownFormat {
+        indent_width: from.unwrap_or(config.format.indent_width),
+        ..config.format.clone()
+    };
+    if dry_run {
+        let todo_list = reindented(&path, &old_format, width)?;
+        write!(out, "{}", todo_list.to_markdown())?;
+        return Ok(());
+    }
+
+    let lock_file = create_lock_file(config_dir)?;
+    let result = reindented(&path, &old_format, width)
+        .and_then(|todo_list| write_atomically(&path, &todo_list.to_markdown()));
+    fs::remove_file(lock_file)?;
+    result?;
+    writeln!(
+        out,
+        "Reindented {} from {} to {} spaces per level",
+        path.display(),
+        old_format.indent_width,
+        width
+    )?;
+    Ok(())
+}
+
+fn reindented(
+    path: &Path,
+    old_format: &MarkdownFormat,
+    width: usize,
+) -> Result<TodoList, Box<dyn Error>> {
+    let mut todo_list = read_todo_list(path, old_format)?;
+    todo_list.format.indent_width = width;
+    Ok(todo_list)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TWO_SPACES: &str =
+        "# TODO 2025-08-14\n\n* [x] a\n  * [ ] b\n    * [x] c\n  * note\n* [ ] d\n";
+    const FOUR_SPACES: &str =
+        "# TODO 2025-08-14\n\n* [x] a\n    * [ ] b\n        * [x] c\n    * note\n* [ ] d\n";
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 8, 14).unwrap()
+    }
+
+    #[test]
+    fn test_reindent_keeps_levels() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("TODO-2025-08-14.md");
+        fs::write(&path, TWO_SPACES).unwrap();
+        let config = Config::default();
+
+        run(dir.path(), &config, date(), 4, None, false, &mut Vec::new()).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), FOUR_SPACES);
+        assert!(!dir.path().join("lockfile").exists());
+
+        // And back, telling it the file's current width
+        run(
+            dir.path(),
+            &config,
+            date(),
+            2,
+            Some(4),
+            false,
+            &mut Vec::new(),
+        )
+        .unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), TWO_SPACES);
+    }
+
+    #[test]
+    fn test_reindent_dry_run_only_prints() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("TODO-2025-08-14.md");
+        fs::write(&path, TWO_SPACES).unwrap();
+
+        let mut out = Vec::new();
+        run(
+            dir.path(),
+            &Config::default(),
+            date(),
+            4,
+            None,
+            true,
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), FOUR_SPACES);
+        assert_eq!(fs::read_to_string(&path).unwrap(), TWO_SPACES);
+    }
+
+    #[test]
+    fn test_reindent_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = run(
+            dir.path(),
+            &Config::default(),
+            date(),
+            4,
+            None,
+            false,
+            &mut Vec::new(),
+        );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,213 @@
+//! A tiny line-based protocol on a Unix socket, for adding tasks from other
+//! tools. Each request is one line, each response one or more lines with the
+//! last one being either `OK` or `ERR <message>`:
+//!
+//! * `ADD <text>` appends a new top-level item to today's list
+//! * `LIST` returns today's items as markdown lines
+//! * `STOP` shuts the server down
+//!
+//! The server holds the lock file while running, so it never writes
+//! concurrently with an interactive instance.
+
+use std::{
+    error::Error,
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    os::unix::{
+        fs::FileTypeExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::Path,
+};
+
+use chrono::NaiveDate;
+
+use crate::{TodoApp, config::Config, create_lock_file, load_or_create_todo_list, warn};
+
+pub fn run(
+    socket: &Path,
+    config_dir: &Path,
+    config: &Config,
+    today: NaiveDate,
+) -> Result<(), Box<dyn Error>> {
+    let lock_file = create_lock_file(config_dir)?;
+    let result = serve(socket, config_dir, config, today);
+    fs::remove_file(lock_file)?;
+    result
+}
+
+fn serve(
+    socket: &Path,
+    config_dir: &Path,
+    config: &Config,
+    today: NaiveDate,
+) -> Result<(), Box<dyn Error>> {
+    let todo_list = load_or_create_todo_list(config_dir, today, config)?;
+    let mut todo_app =
+        TodoApp::new(config_dir.to_path_buf(), todo_list).with_config(config.clone());
+
+    // We hold the lock, so a leftover socket can only be from a crashed
+    // server. Anything else at that path isn't ours to delete.
+    match fs::symlink_metadata(socket) {
+        Ok(metadata) if metadata.file_type().is_socket() => fs::remove_file(socket)?,
+        Ok(_) => {
+            return Err(format!("{} exists and is not a socket", socket.display()).into());
+        }
+        Err(_) => {}
+    }
+    let listener = UnixListener::bind(socket)?;
+    let result = accept_clients(&listener, &mut todo_app);
+    fs::remove_file(socket)?;
+    result
+}
+
+// Serves one client after the other until one sends `STOP`. A client that
+// fails is dropped without stopping the server for the others.
+fn accept_clients(listener: &UnixListener, todo_app: &mut TodoApp) -> Result<(), Box<dyn Error>> {
+    for stream in listener.incoming() {
+        let stopped = stream.and_then(|stream| handle_client(&stream, todo_app));
+        match stopped {
+            Ok(true) => return Ok(()),
+            Ok(false) => {}
+            Err(err) => warn(format!("Dropped client: {}", err)),
+        }
+    }
+    Ok(())
+}
+
+// Answers the lines of one client, returning whether it stopped the server
+fn handle_client(stream: &UnixStream, todo_app: &mut TodoApp) -> io::Result<bool> {
+    let mut writer = stream;
+    for line in BufReader::new(stream).lines() {
+        let (response, stop) = handle_command(todo_app, &line?);
+        writeln!(writer, "{}", response)?;
+        if stop {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Applies a single protocol line, returning the response and whether the
+/// server should stop
+fn handle_command(todo_app: &mut TodoApp, line: &str) -> (String, bool) {
+    let (command, argument) = line
+        .trim_end()
+        .split_once(' ')
+        .unwrap_or((line.trim_end(), ""));
+
+    match command {
+        "ADD" if argument.trim().is_empty() => ("ERR missing text".to_string(), false),
+        "ADD" => match todo_app.append_item(argument.trim().to_string()) {
+            Ok(_) => ("OK".to_string(), false),
+            Err(err) => (format!("ERR {}", err), false),
+        },
+        "LIST" => {
+            let mut response = String::new();
+            for item in &todo_app.todo_list.items {
+                response.push_str(&item.to_markdown_line_with(&todo_app.todo_list.format));
+                response.push('\n');
+            }
+            response.push_str("OK");
+            (response, false)
+        }
+        "STOP" => ("OK".to_string(), true),
+        _ => (format!("ERR unknown command: {}", command), false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        os::unix::net::UnixStream,
+        thread,
+        time::Duration,
+    };
+
+    use super::*;
+    use crate::TodoList;
+
+    #[test]
+    fn test_handle_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_app = TodoApp::new(dir.path().to_path_buf(), TodoList::new(date));
+
+        assert_eq!(
+            handle_command(&mut todo_app, "ADD buy milk"),
+            ("OK".to_string(), false)
+        );
+        assert_eq!(
+            handle_command(&mut todo_app, "ADD  "),
+            ("ERR missing text".to_string(), false)
+        );
+        assert_eq!(
+            handle_command(&mut todo_app, "LIST"),
+            ("* [ ] buy milk\nOK".to_string(), false)
+        );
+        assert_eq!(
+            handle_command(&mut todo_app, "DROP TABLE"),
+            ("ERR unknown command: DROP".to_string(), false)
+        );
+        assert_eq!(
+            handle_command(&mut todo_app, "STOP"),
+            ("OK".to_string(), true)
+        );
+
+        // Every ADD is saved right away
+        assert_eq!(todo_app.todo_list.items.len(), 1);
+        assert!(fs::read_dir(dir.path()).unwrap().count() > 0);
+    }
+
+    #[test]
+    fn test_serve_over_socket_holds_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_dir = dir.path().to_path_buf();
+        let socket = dir.path().join("todui.sock");
+        let today = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+
+        let server = {
+            let (socket, config_dir) = (socket.clone(), config_dir.clone());
+            thread::spawn(move || run(&socket, &config_dir, &Config::default(), today).unwrap())
+        };
+        while !socket.exists() {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        // The interactive instance can't start while the server runs
+        assert!(create_lock_file(&config_dir).is_err());
+
+        let stream = UnixStream::connect(&socket).unwrap();
+        let mut reader = BufReader::new(&stream);
+        let mut writer = &stream;
+        let mut response = String::new();
+
+        writeln!(writer, "ADD from socket").unwrap();
+        reader.read_line(&mut response).unwrap();
+        assert_eq!(response, "OK\n");
+
+        writeln!(writer, "STOP").unwrap();
+        server.join().unwrap();
+
+        // Saving moves the list to the current date, so look up whichever file was written
+        let (_, path) = crate::todo_files(&config_dir).pop().unwrap();
+        let saved = fs::read_to_string(path).unwrap();
+        assert!(saved.ends_with("* [ ] from socket\n"));
+        assert!(!config_dir.join("lockfile").exists());
+        assert!(!socket.exists());
+    }
+
+    #[test]
+    fn test_serve_keeps_files_that_are_not_sockets() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("notes.txt");
+        fs::write(&socket, "precious").unwrap();
+        let today = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+
+        let err = run(&socket, dir.path(), &Config::default(), today).unwrap_err();
+        assert!(err.to_string().contains("is not a socket"));
+        assert_eq!(fs::read_to_string(&socket).unwrap(), "precious");
+        assert!(!dir.path().join("lockfile").exists());
+    }
+}
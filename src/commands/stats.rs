@@ -0,0 +1,243 @@
+use std::{collections::HashMap, error::Error, io::Write, path::Path};
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::{TodoList, config::Config, read_todo_list, todo_files_for, warn};
+
+/// Number of carried-over items listed in the report
+const MAX_CARRIED_OVER: usize = 5;
+
+#[derive(Debug, Serialize)]
+struct DayStats {
+    date: NaiveDate,
+    total: usize,
+    completed: usize,
+    completion_rate: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct CarriedOverItem {
+    text: String,
+    days: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct StatsReport {
+    days: Vec<DayStats>,
+    streak: usize,
+    carried_over: Vec<CarriedOverItem>,
+}
+
+pub fn run(
+    config_dir: &Path,
+    config: &Config,
+    today: NaiveDate,
+    days: usize,
+    json: bool,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let files: Vec<_> = todo_files_for(config_dir, config)
+        .into_iter()
+        .filter(|(date, _)| *date <= today)
+        .collect();
+
+    let mut lists = Vec::new();
+    for (_, path) in &files[files.len().saturating_sub(days)..] {
+        match read_todo_list(path, &config.format) {
+            Ok(todo_list) => lists.push(todo_list),
+            Err(err) => warn(format!("Skipping {}: {}", path.display(), err)),
+        }
+    }
+
+    let report = compute_stats(&lists);
+    if json {
+        serde_json::to_writer_pretty(&mut *out, &report)?;
+        writeln!(out)?;
+    } else {
+        write_text_report(&report, out)?;
+    }
+    Ok(())
+}
+
+/// Computes the report for the given lists, which must be sorted by date
+fn compute_stats(lists: &[TodoList]) -> StatsReport {
+    let days: Vec<DayStats> = lists
+        .iter()
+        .map(|todo_list| {
+            let total = todo_list.items.len();
+            let completed = todo_list.items.iter().filter(|i| i.completed).count();
+            let completion_rate = if total == 0 {
+                0.0
+            } else {
+                completed as f64 / total as f64
+            };
+            DayStats {
+                date: todo_list.date,
+                total,
+                completed,
+                completion_rate,
+            }
+        })
+        .collect();
+
+    // Count on how many days each (trimmed) text was left incomplete
+    let mut incomplete_days: HashMap<&str, usize> = HashMap::new();
+    for todo_list in lists {
+        let mut seen = Vec::new();
+        for item in todo_list.items.iter().filter(|i| !i.completed) {
+            let text = item.text.trim();
+            if !text.is_empty() && !seen.contains(&text) {
+                seen.push(text);
+                *incomplete_days.entry(text).or_default() += 1;
+            }
+        }
+    }
+    let mut carried_over: Vec<CarriedOverItem> = incomplete_days
+        .into_iter()
+        .filter(|(_, days)| *days > 1)
+        .map(|(text, days)| CarriedOverItem {
+            text: text.to_string(),
+            days,
+        })
+        .collect();
+    carried_over.sort_by(|a, b| b.days.cmp(&a.days).then_with(|| a.text.cmp(&b.text)));
+    carried_over.truncate(MAX_CARRIED_OVER);
+
+    StatsReport {
+        streak: completion_streak(&days),
+        days,
+        carried_over,
+    }
+}
+
+/// Number of consecutive calendar days, counting back from the most recent
+/// file, on which at least one item was completed. A day without a file ends
+/// the streak.
+fn completion_streak(days: &[DayStats]) -> usize {
+    let mut streak = 0;
+    let mut expected_date = None;
+
+    for day in days.iter().rev() {
+        if day.completed == 0 || expected_date.is_some_and(|date| date != day.date) {
+            break;
+        }
+        streak += 1;
+        expected_date = day.date.pred_opt();
+    }
+
+    streak
+}
+
+fn write_text_report(report: &StatsReport, out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    if report.days.is_empty() {
+        writeln!(out, "No todo files found")?;
+        return Ok(());
+    }
+
+    writeln!(out, "Completion by day:")?;
+    for day in &report.days {
+        writeln!(
+            out,
+            "  {}  {:>3}/{:<3} {:>3.0}%",
+            day.date.format("%Y-%m-%d"),
+            day.completed,
+            day.total,
+            day.completion_rate * 100.0
+        )?;
+    }
+
+    writeln!(out)?;
+    writeln!(out, "Current streak: {} day(s)", report.streak)?;
+
+    if !report.carried_over.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "Most carried over:")?;
+        for item in &report.carried_over {
+            writeln!(out, "  {:>3} days  {}", item.days, item.text)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::commands::test_util::write_fixture;
+
+    fn fixture_dir() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        write_fixture(dir.path(), "2025-08-10", "* [x] a\n* [ ] report\n");
+        write_fixture(dir.path(), "2025-08-12", "* [ ] report\n* [ ] taxes\n");
+        write_fixture(
+            dir.path(),
+            "2025-08-13",
+            "* [x] b\n* [ ] report\n* [ ] taxes\n",
+        );
+        write_fixture(dir.path(), "2025-08-14", "* [x] c\n* [x] d\n* [ ] report\n");
+        // Future files are ignored
+        write_fixture(dir.path(), "2025-09-01", "* [x] future\n");
+        // Files that don't parse are skipped
+        fs::write(dir.path().join("TODO-2025-08-11.md"), "garbage").unwrap();
+        dir
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 8, 14).unwrap()
+    }
+
+    #[test]
+    fn test_stats_over_fixture_directory() {
+        let dir = fixture_dir();
+        let mut out = Vec::new();
+        run(dir.path(), &Config::default(), today(), 30, true, &mut out).unwrap();
+
+        let report: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        let days = report["days"].as_array().unwrap();
+        assert_eq!(days.len(), 4);
+        assert_eq!(days[0]["date"], "2025-08-10");
+        assert_eq!(days[0]["completion_rate"], 0.5);
+        assert_eq!(days[1]["completed"], 0);
+        assert_eq!(days[3]["total"], 3);
+
+        // 08-13 and 08-14 had completions; 08-12 had none
+        assert_eq!(report["streak"], 2);
+
+        let carried = report["carried_over"].as_array().unwrap();
+        assert_eq!(carried.len(), 2);
+        assert_eq!(carried[0]["text"], "report");
+        assert_eq!(carried[0]["days"], 4);
+        assert_eq!(carried[1]["text"], "taxes");
+        assert_eq!(carried[1]["days"], 2);
+    }
+
+    #[test]
+    fn test_stats_limits_number_of_days() {
+        let dir = fixture_dir();
+        let mut out = Vec::new();
+        run(dir.path(), &Config::default(), today(), 2, true, &mut out).unwrap();
+
+        let report: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        let days = report["days"].as_array().unwrap();
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0]["date"], "2025-08-13");
+    }
+
+    #[test]
+    fn test_streak_requires_consecutive_days() {
+        let dir = tempfile::tempdir().unwrap();
+        write_fixture(dir.path(), "2025-08-11", "* [x] a\n");
+        write_fixture(dir.path(), "2025-08-13", "* [x] b\n");
+        write_fixture(dir.path(), "2025-08-14", "* [x] c\n");
+
+        let mut out = Vec::new();
+        run(dir.path(), &Config::default(), today(), 30, false, &mut out).unwrap();
+
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("  2025-08-13    1/1   100%"));
+        assert!(text.contains("Current streak: 2 day(s)"));
+        assert!(!text.contains("Most carried over"));
+    }
+}
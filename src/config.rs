@@ -0,0 +1,618 @@
+use std::{error::Error, fs, path::Path};
+
+use clap::ValueEnum;
+use serde::Deserialize;
+
+/// User settings, read from `config.toml` in the config directory.
+///
+/// Every setting is optional; missing ones fall back to the defaults below.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    #[serde(flatten)]
+    pub format: MarkdownFormat,
+    /// Deepest indentation level items can be indented to, unlimited if unset
+    pub max_indent: Option<usize>,
+    /// What happens to the subitems of an item that gets unindented, which
+    /// would otherwise end up two levels below it
+    pub unindent_subitems: UnindentSubitems,
+    /// Title shown before the date for lists that don't set their own
+    pub title: Option<String>,
+    /// Color preset of the terminal UI
+    pub theme: Theme,
+    /// When the terminal UI uses colors; `auto` is resolved at startup
+    pub color: ColorMode,
+    /// Draw the terminal UI with ASCII characters only, for terminals that
+    /// render box drawing characters and emoji poorly
+    pub plain_ascii: bool,
+    /// Drawn left of the selected item in the terminal UI besides the
+    /// highlight color; defaults to `>` in plain ASCII mode and to nothing
+    /// otherwise
+    pub selection_marker: Option<String>,
+    /// Color the list border and name the mode in the title of the terminal
+    /// UI while editing or deleting
+    pub mode_border: bool,
+    /// Show the path of the todo file in the top right corner of the list
+    /// border in the terminal UI
+    pub show_file_path: bool,
+    /// Up on the first row of the terminal UI goes to the last one and Down
+    /// on the last row to the first one
+    pub wrap_navigation: bool,
+    /// Color open items in the terminal UI by their indentation level
+    pub indent_colors: bool,
+    /// Rows of the status bar in the terminal UI, 1 or 2; key hints that
+    /// don't fit on the first row continue on the second
+    pub status_lines: u16,
+    /// Show dates near today as "Today", "Yesterday", "3 days ago" and so on
+    /// in the title, followed by the date itself
+    pub relative_dates: bool,
+    /// Bulk deletes removing more items than this need a second confirmation
+    pub bulk_delete_threshold: usize,
+    /// Ring the bell and show a message when the last open item gets completed
+    pub celebrate: bool,
+    /// Sort applied when a list is loaded and after an item is edited
+    pub auto_sort: SortMode,
+    /// Order among the completed items when sorting them last
+    pub completed_secondary: CompletedOrder,
+    /// Move items (with their subtree) below their open siblings when they
+    /// get completed, and back up when they are reopened
+    pub sink_completed: bool,
+    /// Moving an item in the GUI takes its subitems along; otherwise they
+    /// stay where they were
+    pub move_subtree: bool,
+    /// Strike through completed items in the terminal UI
+    pub strikethrough_completed: bool,
+    /// What the terminal UI does when it is still open at midnight
+    pub rollover: RolloverMode,
+    /// Move open items with a `due:` date up to today from the files of the
+    /// previous days into today's list when the app starts
+    pub pull_overdue: bool,
+    /// How many days back `pull_overdue` looks
+    pub pull_overdue_days: u32,
+    /// Remove pulled items from the files they were found in
+    pub pull_overdue_remove: bool,
+    /// Drop an item's `def:N` deferral count when it gets completed
+    pub reset_deferred_on_complete: bool,
+    /// Drop an item's `def:N` deferral count when its text is changed
+    pub reset_deferred_on_edit: bool,
+    /// Keep the list in one `TODO.md` whose header date only tells the day
+    /// it was last saved on, instead of one file per day
+    pub single_file: bool,
+    /// Close gaps in the indentation of the list when loading it, so every
+    /// item is at most one level deeper than the one before it
+    pub reflow_on_load: bool,
+    /// Delete todo files older than this many days when the app starts;
+    /// 0 keeps them all
+    pub max_history_days: u32,
+    /// Move pruned files into the yearly archives instead of just deleting them
+    pub archive_pruned: bool,
+    /// When merging duplicate items, keep them completed if any copy was
+    pub dedup_prefer_completed: bool,
+    /// Ctrl+q and Ctrl+c quit the terminal UI from any mode, without asking
+    pub force_quit: bool,
+    /// Ask before quitting the terminal UI while items are still open
+    pub confirm_quit_if_incomplete: bool,
+    /// Greet the first start of the terminal UI on a new day with a screen
+    /// that any key dismisses
+    pub new_day_greeting: bool,
+    /// Start the terminal UI with the first open item selected
+    pub start_on_first_incomplete: bool,
+    /// Lines wider than this (including indentation) get a warning after
+    /// editing; 0 disables the check
+    pub max_line_length: usize,
+    /// Indentation of items inserted with `i` or the GUI's add button
+    pub new_item_indent: NewItemIndent,
+    /// Indentation level used by `NewItemIndent::Fixed`
+    pub new_item_base_indent: usize,
+    /// Insert new items already checked off
+    pub new_item_completed: bool,
+    /// Toggling a note (a line without a checkbox) turns it into an open task
+    /// instead of doing nothing
+    pub promote_notes_on_toggle: bool,
+    /// Most characters an item being edited can hold, unlimited if unset
+    pub max_edit_length: Option<usize>,
+    /// What pasting several lines into an item being edited does
+    pub multiline_paste: MultilinePaste,
+    /// What pressing Enter on an item whose text was left or made empty does
+    pub empty_confirm: EmptyConfirm,
+    /// How `Y` copies the whole list to the clipboard
+    pub clipboard_format: ClipboardFormat,
+    /// Keys confirming a single delete in the terminal UI
+    pub delete_confirm_keys: Vec<String>,
+    /// Keys cancelling a single delete in the terminal UI
+    pub delete_cancel_keys: Vec<String>,
+}
+
+/// Built-in color presets for the terminal UI
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    /// For terminals with a dark background
+    #[default]
+    Dark,
+    /// For terminals with a light background
+    Light,
+}
+
+/// When to use colors
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ColorMode {
+    /// Colors on a terminal, unless `NO_COLOR` is set
+    #[default]
+    Auto,
+    Always,
+    /// Text attributes like bold and reverse video only
+    Never,
+}
+
+impl ColorMode {
+    /// `Always` or `Never` as decided for output to a terminal or not, with
+    /// `no_color` telling whether the `NO_COLOR` environment variable is set
+    pub fn resolve(self, no_color: bool, is_terminal: bool) -> Self {
+        match self {
+            ColorMode::Auto if is_terminal && !no_color => ColorMode::Always,
+            ColorMode::Auto => ColorMode::Never,
+            mode => mode,
+        }
+    }
+}
+
+/// Order for the items within each group of siblings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortMode {
+    /// Keep the order the items were put in
+    #[default]
+    None,
+    /// Move completed items below the open ones
+    CompletedLast,
+}
+
+/// Secondary order of completed items for `SortMode::CompletedLast`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CompletedOrder {
+    /// Keep the order the items were put in
+    #[default]
+    None,
+    /// Alphabetically, ignoring case
+    Text,
+    /// By their `done:YYYY-MM-DD` date, oldest first; items without a date
+    /// follow, ordered by text
+    #[serde(alias = "done_date")]
+    DoneDate,
+}
+
+/// Rolling the open list over to the new day at midnight
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RolloverMode {
+    /// Keep showing the old day until the next save moves it to today
+    #[default]
+    Off,
+    /// Move all items to the new day, like a restart would
+    All,
+    /// Only carry over the open items (and the parents they need)
+    Incomplete,
+}
+
+/// Where new items take their indentation from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NewItemIndent {
+    /// Same level as the item above the insertion point
+    #[default]
+    Previous,
+    /// Same level as the item the new one is inserted in front of
+    Next,
+    /// Always `new_item_base_indent`
+    Fixed,
+}
+
+/// Subitems of an item that is unindented
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum UnindentSubitems {
+    /// Leave them where they are, skipping a level below the item
+    #[default]
+    Keep,
+    /// Unindent them along with the item
+    Move,
+    /// Don't unindent items that have subitems
+    Refuse,
+    /// Like `Keep`, but the terminal UI asks first when the item has
+    /// subitems
+    Confirm,
+}
+
+/// Handling of line breaks in text pasted into the terminal UI
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MultilinePaste {
+    /// Join the lines with spaces into the edited item
+    #[default]
+    Join,
+    /// Start a new item below for every further line
+    Split,
+    /// Keep the first line in the edited item and add the further lines
+    /// below it, nested by their leading whitespace
+    Outline,
+}
+
+/// Level read for leading whitespace that isn't a multiple of the indent width
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IndentRounding {
+    /// The level of the whole indent widths, dropping the extra spaces
+    #[default]
+    Floor,
+    /// The nearest level, rounding half a width up
+    Nearest,
+}
+
+/// What confirming an item with empty text does
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EmptyConfirm {
+    /// Keep the item with empty text
+    #[default]
+    Keep,
+    /// Delete the item, like cancelling a new one does
+    Delete,
+}
+
+/// Text put on the clipboard when copying the whole list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClipboardFormat {
+    /// The list as it is stored in the todo file
+    #[default]
+    Markdown,
+    /// Just the item texts, indented by two spaces per level
+    Plain,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            format: MarkdownFormat::default(),
+            max_indent: None,
+            unindent_subitems: UnindentSubitems::Keep,
+            title: None,
+            theme: Theme::Dark,
+            color: ColorMode::Auto,
+            plain_ascii: false,
+            selection_marker: None,
+            mode_border: true,
+            show_file_path: false,
+            wrap_navigation: false,
+            indent_colors: false,
+            status_lines: 1,
+            relative_dates: false,
+            bulk_delete_threshold: 5,
+            celebrate: false,
+            auto_sort: SortMode::None,
+            completed_secondary: CompletedOrder::None,
+            sink_completed: false,
+            move_subtree: true,
+            strikethrough_completed: false,
+            rollover: RolloverMode::Off,
+            pull_overdue: false,
+            pull_overdue_days: 7,
+            pull_overdue_remove: false,
+            reset_deferred_on_complete: false,
+            reset_deferred_on_edit: false,
+            single_file: false,
+            reflow_on_load: false,
+            max_history_days: 0,
+            archive_pruned: false,
+            dedup_prefer_completed: true,
+            force_quit: true,
+            confirm_quit_if_incomplete: false,
+            new_day_greeting: false,
+            start_on_first_incomplete: false,
+            max_line_length: 120,
+            new_item_indent: NewItemIndent::Previous,
+            new_item_base_indent: 0,
+            new_item_completed: false,
+            promote_notes_on_toggle: false,
+            max_edit_length: None,
+            multiline_paste: MultilinePaste::Join,
+            empty_confirm: EmptyConfirm::Keep,
+            clipboard_format: ClipboardFormat::Markdown,
+            delete_confirm_keys: vec!["y".to_string(), "Enter".to_string()],
+            delete_cancel_keys: vec!["Esc".to_string()],
+        }
+    }
+}
+
+/// Settings controlling how todo list files are read and written
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MarkdownFormat {
+    /// Number of spaces per indentation level
+    pub indent_width: usize,
+    /// Number of spaces a leading tab counts as when reading a file
+    pub tab_width: usize,
+    /// Number of blank lines written between the header and the items
+    pub blank_lines_after_header: usize,
+    /// End the file with a line break
+    pub final_newline: bool,
+    /// Deepest indentation level read from a file; deeper items are clamped
+    /// to it so a malformed file can't push items off the screen
+    pub max_parsed_indent: usize,
+    /// Level read for indentation between two levels, which is also warned about
+    pub indent_rounding: IndentRounding,
+    /// Marks inside a checkbox read as done, e.g. `X` or `✓` from other
+    /// tools; the first one is written
+    pub done_markers: Vec<String>,
+    /// Marks inside a checkbox read as open; the first one is written
+    pub open_markers: Vec<String>,
+}
+
+impl Default for MarkdownFormat {
+    fn default() -> Self {
+        Self {
+            indent_width: 2,
+            tab_width: 4,
+            blank_lines_after_header: 1,
+            final_newline: true,
+            max_parsed_indent: 16,
+            indent_rounding: IndentRounding::Floor,
+            done_markers: vec!["x".to_string(), "X".to_string()],
+            open_markers: vec![" ".to_string()],
+        }
+    }
+}
+
+impl MarkdownFormat {
+    /// Width of leading whitespace in `line`, with tabs expanded to `tab_width` spaces
+    pub fn leading_width(&self, line: &str) -> usize {
+        line.chars()
+            .map_while(|c| match c {
+                ' ' => Some(1),
+                '\t' => Some(self.tab_width),
+                _ => None,
+            })
+            .sum()
+    }
+
+    /// Indentation level for a leading whitespace width
+    pub fn indent_level(&self, width: usize) -> usize {
+        let indent_width = self.indent_width.max(1);
+        match self.indent_rounding {
+            IndentRounding::Floor => width / indent_width,
+            IndentRounding::Nearest => (width + indent_width / 2) / indent_width,
+        }
+    }
+
+    /// Whether the mark inside a checkbox means done, or `None` if it's
+    /// neither a done nor an open marker
+    pub fn marker_completed(&self, marker: &str) -> Option<bool> {
+        if self.done_markers.iter().any(|done| done == marker) {
+            Some(true)
+        } else if self.open_markers.iter().any(|open| open == marker) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Checkbox written for a done or open task, with the first configured
+    /// marker or `[x]` and `[ ]` if there is none
+    pub fn checkbox(&self, completed: bool) -> String {
+        let (markers, fallback) = if completed {
+            (&self.done_markers, "x")
+        } else {
+            (&self.open_markers, " ")
+        };
+        format!("[{}]", markers.first().map_or(fallback, String::as_str))
+    }
+
+    /// Leading whitespace written for an indentation level
+    pub fn indent(&self, indent_level: usize) -> String {
+        " ".repeat(self.indent_width * indent_level)
+    }
+}
+
+impl Config {
+    pub fn load(config_dir: &Path) -> Result<Self, Box<dyn Error>> {
+        let path = config_dir.join("config.toml");
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+
+        let content = fs::read_to_string(&path)?;
+        let config: Config = toml::from_str(&content)
+            .map_err(|err| format!("Invalid config file {}: {}", path.display(), err))?;
+
+        let keys = config.delete_confirm_keys.iter();
+        if let Some(key) = keys
+            .chain(&config.delete_cancel_keys)
+            .find(|key| !is_valid_key_name(key))
+        {
+            return Err(
+                format!("Invalid key \"{}\" in config file {}", key, path.display()).into(),
+            );
+        }
+        if !(1..=2).contains(&config.status_lines) {
+            return Err(format!(
+                "Invalid status_lines {} in config file {}, expected 1 or 2",
+                config.status_lines,
+                path.display()
+            )
+            .into());
+        }
+        if config.format.indent_width == 0 {
+            return Err(format!(
+                "The indentation width must be at least 1 (indent_width in config file {})",
+                path.display()
+            )
+            .into());
+        }
+        Ok(config)
+    }
+
+    /// The classic checklist behavior of `--classic-complete`, made of the
+    /// individual options: completed items are struck through and slide to
+    /// the bottom of their sibling group, ordered by when they were completed
+    pub fn apply_classic_complete(&mut self) {
+        self.strikethrough_completed = true;
+        self.sink_completed = true;
+    }
+
+    /// Separator between a custom title and the date, or a mode name
+    pub fn title_separator(&self) -> &'static str {
+        if self.plain_ascii { " - " } else { " — " }
+    }
+}
+
+/// Named keys that can be bound in the config file besides single characters
+const KEY_NAMES: [&str; 5] = ["enter", "esc", "space", "tab", "backspace"];
+
+/// Whether `name` is a single character or one of `KEY_NAMES` (ignoring case)
+fn is_valid_key_name(name: &str) -> bool {
+    name.chars().count() == 1 || KEY_NAMES.contains(&name.to_ascii_lowercase().as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_missing_config_uses_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::load(dir.path()).unwrap();
+        assert_eq!(config.format.indent_width, 2);
+        assert_eq!(config.format.tab_width, 4);
+    }
+
+    #[test]
+    fn test_load_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("config.toml"),
+            "indent_width = 4\ntab_width = 8\n",
+        )
+        .unwrap();
+
+        let config = Config::load(dir.path()).unwrap();
+        assert_eq!(config.format.indent_width, 4);
+        assert_eq!(config.format.tab_width, 8);
+    }
+
+    #[test]
+    fn test_load_rejects_zero_indent_width() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("config.toml"), "indent_width = 0\n").unwrap();
+
+        let err = Config::load(dir.path()).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("The indentation width must be at least 1")
+        );
+    }
+
+    #[test]
+    fn test_load_sort_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("config.toml"),
+            "auto_sort = \"completed-last\"\n",
+        )
+        .unwrap();
+
+        let config = Config::load(dir.path()).unwrap();
+        assert_eq!(config.auto_sort, SortMode::CompletedLast);
+        assert_eq!(config.completed_secondary, CompletedOrder::None);
+
+        fs::write(
+            dir.path().join("config.toml"),
+            "completed_secondary = \"done_date\"\n",
+        )
+        .unwrap();
+        let config = Config::load(dir.path()).unwrap();
+        assert_eq!(config.completed_secondary, CompletedOrder::DoneDate);
+    }
+
+    #[test]
+    fn test_load_new_item_indent() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("config.toml"),
+            "new_item_indent = \"fixed\"\nnew_item_base_indent = 1\n",
+        )
+        .unwrap();
+
+        let config = Config::load(dir.path()).unwrap();
+        assert_eq!(config.new_item_indent, NewItemIndent::Fixed);
+        assert_eq!(config.new_item_base_indent, 1);
+        assert!(!config.new_item_completed);
+    }
+
+    #[test]
+    fn test_load_delete_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::load(dir.path()).unwrap();
+        assert_eq!(config.delete_confirm_keys, ["y", "Enter"]);
+        assert_eq!(config.delete_cancel_keys, ["Esc"]);
+
+        fs::write(
+            dir.path().join("config.toml"),
+            "delete_confirm_keys = [\"j\", \"SPACE\"]\ndelete_cancel_keys = [\"n\"]\n",
+        )
+        .unwrap();
+        let config = Config::load(dir.path()).unwrap();
+        assert_eq!(config.delete_confirm_keys, ["j", "SPACE"]);
+        assert_eq!(config.delete_cancel_keys, ["n"]);
+
+        fs::write(
+            dir.path().join("config.toml"),
+            "delete_confirm_keys = [\"ctrl-y\"]\n",
+        )
+        .unwrap();
+        assert!(Config::load(dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_classic_complete_preset() {
+        let mut config = Config::default();
+        config.apply_classic_complete();
+        assert!(config.strikethrough_completed);
+        assert!(config.sink_completed);
+        // Everything else stays as configured
+        assert_eq!(config.completed_secondary, CompletedOrder::None);
+        assert_eq!(config.auto_sort, SortMode::None);
+        assert_eq!(config.theme, Theme::Dark);
+    }
+
+    #[test]
+    fn test_color_mode_resolve() {
+        assert_eq!(ColorMode::Auto.resolve(false, true), ColorMode::Always);
+        assert_eq!(ColorMode::Auto.resolve(true, true), ColorMode::Never);
+        assert_eq!(ColorMode::Auto.resolve(false, false), ColorMode::Never);
+        // An explicit choice wins over NO_COLOR and the terminal check
+        assert_eq!(ColorMode::Always.resolve(true, false), ColorMode::Always);
+        assert_eq!(ColorMode::Never.resolve(false, true), ColorMode::Never);
+    }
+
+    #[test]
+    fn test_load_status_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("config.toml"), "status_lines = 2\n").unwrap();
+        assert_eq!(Config::load(dir.path()).unwrap().status_lines, 2);
+
+        fs::write(dir.path().join("config.toml"), "status_lines = 3\n").unwrap();
+        assert!(Config::load(dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_leading_width_expands_tabs() {
+        let format = MarkdownFormat::default();
+        assert_eq!(format.leading_width("* item"), 0);
+        assert_eq!(format.leading_width("  * item"), 2);
+        assert_eq!(format.leading_width("\t* item"), 4);
+        assert_eq!(format.leading_width("\t  * item"), 6);
+    }
+}
@@ -1,6 +1,6 @@
 use std::error::Error;
 
-use crate::{TodoApp, TodoItem};
+use crate::{TodoApp, TodoItem, warn};
 
 slint::include_modules!();
 
@@ -8,7 +8,7 @@ pub fn run_gui(todo_app: TodoApp) -> Result<(), Box<dyn Error>> {
     let ui = AppWindow::new()?;
 
     // Set initial title
-    let title = format!("TODO {}", todo_app.todo_list.date.format("%Y-%m-%d"));
+    let title = todo_app.display_title();
     ui.set_window_title(title.into());
 
     // Convert TodoItems to TodoItemData for Slint
@@ -40,11 +40,16 @@ pub fn run_gui(todo_app: TodoApp) -> Result<(), Box<dyn Error>> {
         ui.on_toggle_item_completed(move |index| {
             if let Ok(mut app) = todo_app_rc.try_borrow_mut() {
                 let _ = app.toggle_item_completed(index as usize);
+                let new_index = app.sink_item(index as usize).unwrap_or(index as usize);
                 if let Some(ui) = ui_weak.upgrade() {
                     let items: slint::ModelRc<TodoItemData> = slint::ModelRc::new(
                         slint::VecModel::from(convert_items(&app.todo_list.items)),
                     );
                     ui.set_todo_items(items);
+                    // The selection follows the item when it sinks
+                    if ui.get_selected_index() == index {
+                        ui.set_selected_index(new_index as i32);
+                    }
                 }
             }
         });
@@ -56,6 +61,15 @@ pub fn run_gui(todo_app: TodoApp) -> Result<(), Box<dyn Error>> {
         let ui_weak = ui_weak.clone();
         ui.on_item_text_changed(move |index, text| {
             if let Ok(mut app) = todo_app_rc.try_borrow_mut() {
+                // Losing focus after Enter commits the same text again
+                let unchanged = app
+                    .todo_list
+                    .items
+                    .get(index as usize)
+                    .is_some_and(|item| item.text == text.as_str());
+                if unchanged {
+                    return;
+                }
                 let _ = app.update_item_text(index as usize, text.to_string());
                 if let Some(ui) = ui_weak.upgrade() {
                     let items: slint::ModelRc<TodoItemData> = slint::ModelRc::new(
@@ -113,6 +127,8 @@ pub fn run_gui(todo_app: TodoApp) -> Result<(), Box<dyn Error>> {
                         slint::VecModel::from(convert_items(&app.todo_list.items)),
                     );
                     ui.set_todo_items(items);
+                    let last = app.todo_list.items.len().saturating_sub(1) as i32;
+                    ui.set_selected_index(ui.get_selected_index().min(last));
                 }
             }
         });
@@ -130,6 +146,7 @@ pub fn run_gui(todo_app: TodoApp) -> Result<(), Box<dyn Error>> {
                         slint::VecModel::from(convert_items(&app.todo_list.items)),
                     );
                     ui.set_todo_items(items);
+                    ui.set_selected_index(0);
                 }
             }
         });
@@ -147,11 +164,96 @@ pub fn run_gui(todo_app: TodoApp) -> Result<(), Box<dyn Error>> {
                         slint::VecModel::from(convert_items(&app.todo_list.items)),
                     );
                     ui.set_todo_items(items);
+                    ui.set_selected_index(to_index);
                 }
             }
         });
     }
 
+    // Save an edit still in progress and anything a failed save left behind
+    // when the window is closed
+    {
+        let todo_app_rc = todo_app_rc.clone();
+        let ui_weak = ui_weak.clone();
+        ui.window().on_close_requested(move || {
+            if let (Some(ui), Ok(mut app)) = (ui_weak.upgrade(), todo_app_rc.try_borrow_mut()) {
+                if let Err(err) =
+                    save_on_close(&mut app, ui.get_editing_index(), &ui.get_editing_text())
+                {
+                    warn(format!(
+                        "Could not save {}: {}",
+                        app.file_path().display(),
+                        err
+                    ));
+                }
+            }
+            slint::CloseRequestResponse::HideWindow
+        });
+    }
+
     ui.run()?;
     Ok(())
 }
+
+// Commits the text of the field being edited, if any, and saves the list if
+// it differs from the file
+fn save_on_close(
+    app: &mut TodoApp,
+    editing_index: i32,
+    editing_text: &str,
+) -> Result<(), Box<dyn Error>> {
+    if let Ok(index) = usize::try_from(editing_index) {
+        app.update_item_text(index, editing_text.to_string())?;
+    }
+    if app.has_unsaved_changes() {
+        app.save_todo_list()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use chrono::Local;
+
+    use super::save_on_close;
+    use crate::{TodoApp, TodoItem, TodoList};
+
+    #[test]
+    fn test_save_on_close_commits_pending_edit() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let mut todo_list = TodoList::new(Local::now().date_naive());
+        todo_list
+            .items
+            .push(TodoItem::new("draft".to_string(), false, 0));
+        let mut todo_app = TodoApp::new(config_dir.path().to_path_buf(), todo_list);
+        todo_app.save_todo_list().unwrap();
+
+        save_on_close(&mut todo_app, 0, "final").unwrap();
+        let saved = fs::read_to_string(todo_app.file_path()).unwrap();
+        assert!(saved.contains("* [ ] final\n"));
+        assert!(!saved.contains("draft"));
+    }
+
+    #[test]
+    fn test_save_on_close_saves_unsaved_changes() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let todo_list = TodoList::new(Local::now().date_naive());
+        let mut todo_app = TodoApp::new(config_dir.path().to_path_buf(), todo_list);
+        todo_app.save_todo_list().unwrap();
+
+        // As left behind by a failed save
+        todo_app
+            .todo_list
+            .items
+            .push(TodoItem::new("pending".to_string(), false, 0));
+        assert!(todo_app.has_unsaved_changes());
+
+        // Nothing is being edited
+        save_on_close(&mut todo_app, -1, "").unwrap();
+        let saved = fs::read_to_string(todo_app.file_path()).unwrap();
+        assert!(saved.contains("* [ ] pending\n"));
+        assert!(!todo_app.has_unsaved_changes());
+    }
+}
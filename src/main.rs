@@ -1,23 +1,117 @@
-use chrono::{Local, NaiveDate};
+use chrono::{Days, Local, NaiveDate};
 use clap::Parser;
+use commands::Command;
 
 use std::{
+    collections::HashMap,
     error::Error,
+    fmt,
     fs::{self, File, OpenOptions},
-    io::{self, Write},
-    path::PathBuf,
+    io::{self, IsTerminal, Write},
+    ops::Range,
+    path::{Path, PathBuf},
     process,
+    sync::atomic::{AtomicBool, Ordering},
 };
 
+use config::{
+    ColorMode, CompletedOrder, Config, MarkdownFormat, NewItemIndent, RolloverMode, SortMode,
+    Theme, UnindentSubitems,
+};
+
+mod commands;
+mod config;
 mod gui;
 mod tui;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Start with graphical user interface
     #[arg(long)]
     gui: bool,
+
+    /// Directory holding the todo files and config (overrides TODUI_HOME)
+    #[arg(long, global = true)]
+    config_dir: Option<PathBuf>,
+
+    /// Color preset of the terminal UI (overrides `theme` in the config file)
+    #[arg(long, value_enum)]
+    theme: Option<Theme>,
+
+    /// When to use colors; `auto` means on a terminal unless `NO_COLOR` is
+    /// set (overrides `color` in the config file)
+    #[arg(long, value_enum, global = true)]
+    color: Option<ColorMode>,
+
+    /// Draw the terminal UI with ASCII characters only (same as `plain_ascii`
+    /// in the config file)
+    #[arg(long)]
+    plain_ascii: bool,
+
+    /// Show the path of the todo file in the list border (same as
+    /// `show_file_path` in the config file)
+    #[arg(long)]
+    show_path: bool,
+
+    /// Strike through completed items and move them below their open
+    /// siblings, in the order they were completed
+    #[arg(long)]
+    classic_complete: bool,
+
+    /// Draw the terminal UI in the normal screen buffer instead of the
+    /// alternate screen, leaving the final state visible on exit
+    #[arg(long)]
+    no_alt_screen: bool,
+
+    /// Keep the list in one TODO.md instead of a file per day (same as
+    /// `single_file` in the config file)
+    #[arg(long, global = true)]
+    single_file: bool,
+
+    /// Close gaps in the indentation of the list when loading it (same as
+    /// `reflow_on_load` in the config file)
+    #[arg(long)]
+    reflow: bool,
+
+    /// Remove a stale lock file left behind by a crashed instance without asking
+    #[arg(long)]
+    force: bool,
+
+    /// Keep old todo files this time even if `max_history_days` is set
+    #[arg(long)]
+    no_prune: bool,
+
+    /// Write the output of a subcommand to this file instead of stdout
+    #[arg(long, global = true)]
+    output: Option<PathBuf>,
+
+    /// Suppress warnings on stderr (errors are still reported)
+    #[arg(long, global = true)]
+    quiet: bool,
+}
+
+/// Name of the file holding the list with `single_file`
+const SINGLE_FILE_NAME: &str = "TODO.md";
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Prints a warning to stderr unless `--quiet` was given
+fn warn(message: impl std::fmt::Display) {
+    if !QUIET.load(Ordering::Relaxed) {
+        eprintln!("Warning: {}", message);
+    }
+}
+
+/// Value of `word` if it is a `key:value` token. Words escaped with a
+/// backslash, like `\def:3`, are plain text; the backslash stays in the text,
+/// so the escape survives saving and loading.
+fn token_value<'a>(word: &'a str, key: &str) -> Option<&'a str> {
+    // An escaped word starts with the backslash and so never matches the key
+    word.strip_prefix(key)?.strip_prefix(':')
 }
 
 #[derive(Debug, Clone)]
@@ -25,21 +119,172 @@ pub struct TodoItem {
     pub text: String,
     pub completed: bool,
     pub indent_level: usize,
+    /// False for plain notes, written as `* text` without a checkbox
+    pub is_task: bool,
+    /// Carried over from the previous day by a rollover and not touched since;
+    /// only kept in memory
+    pub carried: bool,
 }
 
 impl TodoItem {
     pub fn new(text: String, completed: bool, indent_level: usize) -> Self {
-        Self {
-            text,
-            completed,
-            indent_level,
+        Self::builder()
+            .text(text)
+            .completed(completed)
+            .indent(indent_level)
+            .build()
+    }
+
+    /// A plain note without a checkbox
+    pub fn note(text: String, indent_level: usize) -> Self {
+        Self::builder()
+            .text(text)
+            .task(false)
+            .indent(indent_level)
+            .build()
+    }
+
+    /// Starts an open top-level task with empty text, for setting only the
+    /// fields that differ
+    pub fn builder() -> TodoItemBuilder {
+        TodoItemBuilder {
+            item: Self {
+                text: String::new(),
+                completed: false,
+                indent_level: 0,
+                is_task: true,
+                carried: false,
+            },
+            due: None,
+        }
+    }
+
+    /// Date of a `done:YYYY-MM-DD` token in the text
+    pub fn done_date(&self) -> Option<NaiveDate> {
+        self.text
+            .split_whitespace()
+            .filter_map(|word| token_value(word, "done"))
+            .find_map(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+    }
+
+    /// Date of a `due:YYYY-MM-DD` token in the text
+    pub fn due_date(&self) -> Option<NaiveDate> {
+        self.text
+            .split_whitespace()
+            .filter_map(|word| token_value(word, "due"))
+            .find_map(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+    }
+
+    /// Number of times the item was carried over to a new day, from a `def:N`
+    /// token in the text
+    pub fn deferred_count(&self) -> u32 {
+        self.text
+            .split_whitespace()
+            .find_map(|word| token_value(word, "def")?.parse().ok())
+            .unwrap_or(0)
+    }
+
+    /// Counts one more day an open task was put off to, in its `def:N`
+    /// token; notes and completed tasks are left alone
+    pub fn defer(&mut self) {
+        if self.is_task && !self.completed {
+            self.set_deferred_count(self.deferred_count().saturating_add(1));
+        }
+    }
+
+    /// Sets the `def:N` token, replacing an existing one in place; a count of
+    /// 0 removes it
+    pub fn set_deferred_count(&mut self, count: u32) {
+        let value = (count > 0).then(|| count.to_string());
+        self.set_token("def", |n| n.parse::<u32>().is_ok(), value);
+    }
+
+    /// Sets the `due:YYYY-MM-DD` token, replacing an existing one in place;
+    /// `None` removes it
+    pub fn set_due_date(&mut self, date: Option<NaiveDate>) {
+        let value = date.map(|date| date.format("%Y-%m-%d").to_string());
+        let is_date = |date: &str| NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok();
+        self.set_token("due", is_date, value);
+    }
+
+    /// Replaces the `key:value` token whose value passes `is_valid` with one
+    /// holding `value`, or removes it. A new token goes at the end.
+    fn set_token(&mut self, key: &str, is_valid: impl Fn(&str) -> bool, value: Option<String>) {
+        let is_token = |word: &&str| token_value(word, key).is_some_and(&is_valid);
+        let mut words: Vec<&str> = self.text.split(' ').collect();
+        let position = words.iter().position(is_token);
+        words.retain(|word| !is_token(word));
+
+        let token = value.map(|value| format!("{}:{}", key, value));
+        if let Some(token) = &token {
+            match position {
+                Some(position) => words.insert(position, token),
+                None if self.text.is_empty() => words = vec![token],
+                None => words.push(token),
+            }
         }
+        self.text = words.join(" ");
     }
 
     pub fn to_markdown_line(&self) -> String {
-        let indent = "  ".repeat(self.indent_level);
-        let checkbox = if self.completed { "[x]" } else { "[ ]" };
-        format!("{}* {} {}", indent, checkbox, self.text)
+        self.to_markdown_line_with(&MarkdownFormat::default())
+    }
+
+    pub fn to_markdown_line_with(&self, format: &MarkdownFormat) -> String {
+        let indent = format.indent(self.indent_level);
+        if !self.is_task {
+            return format!("{}* {}", indent, self.text);
+        }
+        format!(
+            "{}* {} {}",
+            indent,
+            format.checkbox(self.completed),
+            self.text
+        )
+    }
+}
+
+/// Builds a `TodoItem` field by field, see `TodoItem::builder`
+#[derive(Debug, Clone)]
+pub struct TodoItemBuilder {
+    item: TodoItem,
+    due: Option<NaiveDate>,
+}
+
+impl TodoItemBuilder {
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.item.text = text.into();
+        self
+    }
+
+    pub fn completed(mut self, completed: bool) -> Self {
+        self.item.completed = completed;
+        self
+    }
+
+    pub fn indent(mut self, indent_level: usize) -> Self {
+        self.item.indent_level = indent_level;
+        self
+    }
+
+    /// False for a plain note without a checkbox
+    pub fn task(mut self, is_task: bool) -> Self {
+        self.item.is_task = is_task;
+        self
+    }
+
+    /// Adds a `due:YYYY-MM-DD` token to the text, replacing one it has
+    pub fn due(mut self, date: Option<NaiveDate>) -> Self {
+        self.due = date;
+        self
+    }
+
+    pub fn build(self) -> TodoItem {
+        let mut item = self.item;
+        if self.due.is_some() {
+            item.set_due_date(self.due);
+        }
+        item
     }
 }
 
@@ -47,6 +292,15 @@ impl TodoItem {
 pub struct TodoList {
     pub date: NaiveDate,
     pub items: Vec<TodoItem>,
+    pub format: MarkdownFormat,
+    /// Custom title, stored as a `<!-- title: ... -->` comment below the header
+    pub title: Option<String>,
+    /// Free text about the whole day, stored as a `> ...` line below the
+    /// header
+    pub note: Option<String>,
+    /// Problems found while reading the file that didn't stop it from
+    /// loading, with their line numbers
+    pub warnings: Vec<String>,
 }
 
 impl TodoList {
@@ -54,10 +308,19 @@ impl TodoList {
         Self {
             date,
             items: Vec::new(),
+            format: MarkdownFormat::default(),
+            title: None,
+            note: None,
+            warnings: Vec::new(),
         }
     }
 
+    #[cfg(test)]
     fn from_markdown(content: &str) -> Result<Self, Box<dyn Error>> {
+        Self::from_markdown_with(content, MarkdownFormat::default())
+    }
+
+    fn from_markdown_with(content: &str, format: MarkdownFormat) -> Result<Self, Box<dyn Error>> {
         let lines: Vec<&str> = content.lines().collect();
 
         if lines.is_empty() {
@@ -76,83 +339,465 @@ impl TodoList {
 
         let mut todo_list = TodoList::new(date);
 
-        // Parse todo items starting from line 2 (skip header and empty line)
-        for line in lines.iter().skip(2) {
+        // Parse todo items following the header
+        for (index, line) in lines.iter().enumerate().skip(1) {
             if line.trim().is_empty() {
                 continue;
             }
 
-            let trimmed = line.trim_start();
-            let indent_level = (line.len() - trimmed.len()) / 2;
+            if let Some(title) = parse_title_comment(line) {
+                todo_list.title = Some(title.to_string());
+                continue;
+            }
+
+            // The day note comes before the items; a note spread over several
+            // quote lines is joined into one
+            if let Some(note) = parse_day_note(line).filter(|_| todo_list.items.is_empty()) {
+                todo_list.note = Some(match todo_list.note.take() {
+                    Some(previous) => format!("{} {}", previous, note),
+                    None => note.to_string(),
+                });
+                continue;
+            }
+
+            let trimmed = line.trim_start_matches([' ', '\t']);
+            let width = format.leading_width(line);
+            let mut indent_level = format.indent_level(width);
 
             if !trimmed.starts_with("* ") {
                 continue;
             }
 
+            // Usually a space too many or too few from editing by hand
+            if width % format.indent_width.max(1) != 0 {
+                let spaces = if width == 1 { "space" } else { "spaces" };
+                todo_list.warnings.push(format!(
+                    "line {}: indentation of {} {} isn't a multiple of {}, read as level {}",
+                    index + 1,
+                    width,
+                    spaces,
+                    format.indent_width,
+                    indent_level
+                ));
+            }
+
+            if indent_level > format.max_parsed_indent {
+                todo_list.warnings.push(format!(
+                    "line {}: indentation level {} capped at {}",
+                    index + 1,
+                    indent_level,
+                    format.max_parsed_indent
+                ));
+                indent_level = format.max_parsed_indent;
+            }
+
             let content = trimmed.strip_prefix("* ").unwrap();
 
-            let (completed, text) = if content.starts_with("[x] ") {
-                (true, content.strip_prefix("[x] ").unwrap().to_string())
-            } else if content.starts_with("[ ] ") {
-                (false, content.strip_prefix("[ ] ").unwrap().to_string())
-            } else {
-                (false, content.to_string())
+            // Bullets without a checkbox are notes. Editors that strip trailing
+            // whitespace turn empty tasks into a bare `* [ ]`.
+            let checkbox = content
+                .strip_prefix('[')
+                .and_then(|rest| rest.split_once(']'))
+                .and_then(|(marker, rest)| Some((format.marker_completed(marker)?, rest)))
+                .filter(|(_, rest)| rest.is_empty() || rest.starts_with(' '));
+            let item = match checkbox {
+                Some((completed, rest)) => {
+                    let text = rest.strip_prefix(' ').unwrap_or("").to_string();
+                    TodoItem::new(text, completed, indent_level)
+                }
+                None => TodoItem::note(content.to_string(), indent_level),
             };
 
-            todo_list
-                .items
-                .push(TodoItem::new(text, completed, indent_level));
+            todo_list.items.push(item);
         }
 
+        todo_list.format = format;
         Ok(todo_list)
     }
 
     fn to_markdown(&self) -> String {
-        let mut content = format!("# TODO {}\n\n", self.date.format("%Y-%m-%d"));
+        let mut content = format!("# TODO {}\n", self.date.format("%Y-%m-%d"));
+        if let Some(title) = &self.title {
+            content.push_str(&format!("<!-- title: {} -->\n", title));
+        }
+        if let Some(note) = &self.note {
+            content.push_str(&format!("> {}\n", note));
+        }
+        content.push_str(&"\n".repeat(self.format.blank_lines_after_header));
 
         for item in &self.items {
-            content.push_str(&item.to_markdown_line());
+            content.push_str(&item.to_markdown_line_with(&self.format));
             content.push('\n');
         }
 
+        if !self.format.final_newline {
+            content.pop();
+        }
         content
     }
 
     fn filename(&self) -> String {
         format!("TODO-{}.md", self.date.format("%Y-%m-%d"))
     }
+
+    /// Sorts each group of siblings, keeping subtrees attached to their parent.
+    /// The sort is stable. Returns the original index of each item in its new
+    /// position.
+    pub fn sort(&mut self, mode: SortMode, completed_order: CompletedOrder) -> Vec<usize> {
+        let items: Vec<(usize, TodoItem)> = std::mem::take(&mut self.items)
+            .into_iter()
+            .enumerate()
+            .collect();
+        let (order, items) = sort_siblings(items, mode, completed_order)
+            .into_iter()
+            .unzip();
+        self.items = items;
+        order
+    }
+
+    /// Counts one more deferral on each open task, for a list that is taken
+    /// over as a whole to a new day
+    pub fn defer_open_items(&mut self) {
+        self.items.iter_mut().for_each(TodoItem::defer);
+    }
+
+    /// Items to carry over to a new day: the open ones, plus the completed
+    /// ones that still have open descendants so the tree stays intact. The
+    /// open ones get their `def:N` deferral count increased.
+    pub fn carry_over_incomplete(&self) -> Vec<TodoItem> {
+        (0..self.items.len())
+            .filter(|&index| {
+                self.items[self.subtree_range(index)]
+                    .iter()
+                    .any(|item| !item.completed)
+            })
+            .map(|index| {
+                let mut item = self.items[index].clone();
+                // Completed parents only come along to keep the tree intact
+                if !item.completed {
+                    item.carried = true;
+                    item.defer();
+                }
+                item
+            })
+            .collect()
+    }
+
+    /// Removes items with the same trimmed text, indentation level and kind
+    /// (task or note) as an earlier sibling, which is kept. Copies with
+    /// subitems of their own are left alone. With `prefer_completed` the kept item is
+    /// completed if any of its copies was. Returns the number of removed items.
+    pub fn dedup(&mut self, prefer_completed: bool) -> usize {
+        // Keyed by the start of the sibling group, so only copies under the
+        // same parent are merged
+        let mut kept: HashMap<(String, usize, usize, bool), usize> = HashMap::new();
+        let mut items: Vec<TodoItem> = Vec::with_capacity(self.items.len());
+        for (index, item) in self.items.iter().enumerate() {
+            let key = (
+                item.text.trim().to_string(),
+                item.indent_level,
+                self.sibling_range(index).start,
+                item.is_task,
+            );
+            let has_subitems = self.subtree_range(index).len() > 1;
+            match kept.get(&key) {
+                Some(&first) if !has_subitems => {
+                    if prefer_completed && item.completed {
+                        items[first].completed = true;
+                    }
+                }
+                _ => {
+                    kept.entry(key).or_insert(items.len());
+                    items.push(item.clone());
+                }
+            }
+        }
+        let removed = self.items.len() - items.len();
+        self.items = items;
+        removed
+    }
+
+    /// Whether the list has tasks and all of them are completed (notes don't
+    /// count)
+    pub fn all_completed(&self) -> bool {
+        let mut tasks = self.items.iter().filter(|item| item.is_task).peekable();
+        tasks.peek().is_some() && tasks.all(|item| item.completed)
+    }
+
+    /// Range of the item at `index` together with all of its descendants
+    pub fn subtree_range(&self, index: usize) -> Range<usize> {
+        let level = self.items[index].indent_level;
+        let end = self.items[index + 1..]
+            .iter()
+            .position(|item| item.indent_level <= level)
+            .map_or(self.items.len(), |pos| index + 1 + pos);
+        index..end
+    }
+
+    /// Number of direct subitems of the item at `index`, which are left two
+    /// levels below their new parent if the item is unindented alone
+    pub fn child_count(&self, index: usize) -> usize {
+        let mut shallowest = usize::MAX;
+        self.items[self.subtree_range(index)]
+            .iter()
+            .skip(1)
+            .filter(|item| {
+                // Deeper items are below a child, not directly below the item
+                let is_child = item.indent_level <= shallowest;
+                shallowest = shallowest.min(item.indent_level);
+                is_child
+            })
+            .count()
+    }
+
+    /// Closes gaps in the indentation so no item is more than one level
+    /// deeper than the one before it. Items keep their place in the tree:
+    /// an item moves up to the level of the nearest item above it with a
+    /// lower level, plus one, and siblings stay siblings. Returns the number
+    /// of items whose level changed.
+    pub fn reflow_indents(&mut self) -> usize {
+        reflow_levels(&mut self.items)
+    }
+
+    /// Range of all siblings of the item at `index` (including their subtrees),
+    /// i.e. the children of the item's parent
+    pub fn sibling_range(&self, index: usize) -> Range<usize> {
+        let level = self.items[index].indent_level;
+        let start = self.items[..index]
+            .iter()
+            .rposition(|item| item.indent_level < level)
+            .map_or(0, |pos| pos + 1);
+        let end = self.items[index + 1..]
+            .iter()
+            .position(|item| item.indent_level < level)
+            .map_or(self.items.len(), |pos| index + 1 + pos);
+        start..end
+    }
+}
+
+// Sorts a group of items whose first item starts the sibling group. Every
+// item at or above the first item's level starts a new block, deeper items
+// belong to the block (and get sorted recursively).
+fn sort_siblings(
+    items: Vec<(usize, TodoItem)>,
+    mode: SortMode,
+    completed_order: CompletedOrder,
+) -> Vec<(usize, TodoItem)> {
+    let Some(base_level) = items.first().map(|(_, item)| item.indent_level) else {
+        return items;
+    };
+
+    let mut blocks: Vec<Vec<(usize, TodoItem)>> = Vec::new();
+    for entry in items {
+        match blocks.last_mut() {
+            Some(block) if entry.1.indent_level > base_level => block.push(entry),
+            _ => blocks.push(vec![entry]),
+        }
+    }
+
+    for block in &mut blocks {
+        let children = block.split_off(1);
+        block.extend(sort_siblings(children, mode, completed_order));
+    }
+
+    match mode {
+        SortMode::None => {}
+        SortMode::CompletedLast => blocks.sort_by(|a, b| {
+            let (a, b) = (&a[0].1, &b[0].1);
+            a.completed.cmp(&b.completed).then_with(|| {
+                if a.completed {
+                    compare_completed(a, b, completed_order)
+                } else {
+                    std::cmp::Ordering::Equal
+                }
+            })
+        }),
+    }
+    blocks.concat()
+}
+
+// Secondary order between two completed items
+fn compare_completed(a: &TodoItem, b: &TodoItem, order: CompletedOrder) -> std::cmp::Ordering {
+    let text = |item: &TodoItem| item.text.to_lowercase();
+    match order {
+        CompletedOrder::None => std::cmp::Ordering::Equal,
+        CompletedOrder::Text => text(a).cmp(&text(b)),
+        // Dated items first, so that the order stays total
+        CompletedOrder::DoneDate => {
+            let key = |item: &TodoItem| {
+                let date = item.done_date();
+                (date.is_none(), date, text(item))
+            };
+            key(a).cmp(&key(b))
+        }
+    }
+}
+
+/// Extracts the title from a `<!-- title: ... -->` comment line
+fn parse_title_comment(line: &str) -> Option<&str> {
+    let title = line
+        .trim()
+        .strip_prefix("<!-- title:")?
+        .strip_suffix("-->")?
+        .trim();
+    (!title.is_empty()).then_some(title)
+}
+
+/// Extracts the text of a `> ...` day note line
+fn parse_day_note(line: &str) -> Option<&str> {
+    let note = line.strip_prefix('>')?.trim();
+    (!note.is_empty()).then_some(note)
+}
+
+/// Days within which `relative_day_label` still names a date relative to today
+const RELATIVE_DAY_RANGE: i64 = 6;
+
+/// "Today", "Yesterday", "Tomorrow", "3 days ago" or "in 3 days" for `date`
+/// seen from `today`, or `None` for dates further away than a week
+fn relative_day_label(date: NaiveDate, today: NaiveDate) -> Option<String> {
+    match (date - today).num_days() {
+        0 => Some("Today".to_string()),
+        -1 => Some("Yesterday".to_string()),
+        1 => Some("Tomorrow".to_string()),
+        days if days < 0 && -days <= RELATIVE_DAY_RANGE => Some(format!("{} days ago", -days)),
+        days if days > 0 && days <= RELATIVE_DAY_RANGE => Some(format!("in {} days", days)),
+        _ => None,
+    }
 }
 
 // Shared core for both TUI and GUI
 pub struct TodoApp {
     todo_list: TodoList,
     config_dir: PathBuf,
+    config: Config,
+    /// Number of saves so far, so the UIs can tell whether the list changed
+    /// since they last looked at it
+    revision: u64,
+    /// The list as last written to or read from its file, to tell whether
+    /// there are changes a reload would throw away
+    saved_markdown: String,
 }
 
 impl TodoApp {
     fn new(config_dir: PathBuf, todo_list: TodoList) -> Self {
         TodoApp {
+            saved_markdown: todo_list.to_markdown(),
             todo_list,
             config_dir,
+            config: Config::default(),
+            revision: 0,
+        }
+    }
+
+    fn with_config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Title shown in the TUI block and the GUI window: the list's own title or
+    /// the configured one followed by the date, or just `TODO <date>`
+    pub fn display_title(&self) -> String {
+        self.display_title_at(Local::now().date_naive())
+    }
+
+    /// `display_title` as of `today`, which decides the relative day label
+    fn display_title_at(&self, today: NaiveDate) -> String {
+        let absolute = self.todo_list.date.format("%Y-%m-%d").to_string();
+        let label = self
+            .config
+            .relative_dates
+            .then(|| relative_day_label(self.todo_list.date, today))
+            .flatten();
+        let date = match label {
+            Some(label) => format!("{} ({})", label, absolute),
+            None => absolute,
+        };
+        match self.todo_list.title.as_ref().or(self.config.title.as_ref()) {
+            Some(title) => format!("{}{}{}", title, self.config.title_separator(), date),
+            None => format!("TODO {}", date),
         }
     }
 
+    /// Path of the file the list is saved to
+    pub fn file_path(&self) -> PathBuf {
+        todo_file_path(&self.config_dir, &self.config, self.todo_list.date)
+    }
+
     pub fn save_todo_list(&mut self) -> Result<(), Box<dyn Error>> {
-        // Update date to current date if needed
-        let current_date = Local::now().date_naive();
-        if self.todo_list.date != current_date {
-            self.todo_list.date = current_date;
+        self.save_todo_list_on(Local::now().date_naive())
+    }
+
+    /// `save_todo_list` as of `today`, the date the list is saved under
+    fn save_todo_list_on(&mut self, today: NaiveDate) -> Result<(), Box<dyn Error>> {
+        if self.todo_list.date != today {
+            self.todo_list.date = today;
         }
 
         // Save to file
-        let filename = self.config_dir.join(self.todo_list.filename());
-        fs::write(filename, self.todo_list.to_markdown())?;
+        self.revision += 1;
+        let markdown = self.todo_list.to_markdown();
+        write_atomically(&self.file_path(), &markdown).map_err(SaveError)?;
+        self.saved_markdown = markdown;
         Ok(())
     }
 
+    /// Whether the list differs from what was last saved, e.g. because saving
+    /// failed
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.todo_list.to_markdown() != self.saved_markdown
+    }
+
+    /// Replaces the list with the current content of its file, e.g. after it
+    /// was edited elsewhere. Returns the warnings about the file's content.
+    pub fn reload(&mut self) -> Result<Vec<String>, Box<dyn Error>> {
+        let path = self.file_path();
+        if !path.exists() {
+            return Err(format!("{} has not been saved yet", path.display()).into());
+        }
+        let (mut todo_list, warnings) = read_todo_list_quietly(&path, &self.config.format)?;
+        todo_list.sort(self.config.auto_sort, self.config.completed_secondary);
+        self.saved_markdown = todo_list.to_markdown();
+        self.todo_list = todo_list;
+        self.revision += 1;
+        Ok(warnings)
+    }
+
+    /// Moves the list over to `today` if configured and the day has changed,
+    /// keeping the old day's file as it was. Returns whether it rolled over.
+    pub fn roll_over(&mut self, today: NaiveDate) -> Result<bool, Box<dyn Error>> {
+        if self.config.rollover == RolloverMode::Off || today <= self.todo_list.date {
+            return Ok(false);
+        }
+
+        // Save the old day under its own date first
+        write_atomically(&self.file_path(), &self.todo_list.to_markdown())?;
+        if self.config.rollover == RolloverMode::Incomplete {
+            self.todo_list.items = self.todo_list.carry_over_incomplete();
+        } else {
+            self.todo_list.defer_open_items();
+        }
+        self.todo_list.date = today;
+        self.revision += 1;
+        let markdown = self.todo_list.to_markdown();
+        write_atomically(&self.file_path(), &markdown)?;
+        self.saved_markdown = markdown;
+        Ok(true)
+    }
+
     pub fn toggle_item_completed(&mut self, index: usize) -> Result<bool, Box<dyn Error>> {
         if index < self.todo_list.items.len() {
-            self.todo_list.items[index].completed = !self.todo_list.items[index].completed;
+            let item = &mut self.todo_list.items[index];
+            if item.is_task {
+                item.completed = !item.completed;
+            } else if self.config.promote_notes_on_toggle {
+                item.is_task = true;
+            } else {
+                return Ok(false);
+            }
+            item.carried = false;
+            if item.completed && self.config.reset_deferred_on_complete {
+                item.set_deferred_count(0);
+            }
             self.save_todo_list()?;
         }
         Ok(self.todo_list.items[index].completed)
@@ -160,12 +805,81 @@ impl TodoApp {
 
     pub fn update_item_text(&mut self, index: usize, text: String) -> Result<(), Box<dyn Error>> {
         if index < self.todo_list.items.len() {
-            self.todo_list.items[index].text = text;
+            let item = &mut self.todo_list.items[index];
+            let changed = item.text != text;
+            item.text = text;
+            item.carried = false;
+            if changed && self.config.reset_deferred_on_edit {
+                item.set_deferred_count(0);
+            }
+            self.save_todo_list()?;
+        }
+        Ok(())
+    }
+
+    /// Sets the note of the day, removing it if `note` is blank
+    pub fn set_note(&mut self, note: &str) -> Result<(), Box<dyn Error>> {
+        let note = note.trim();
+        self.todo_list.note = (!note.is_empty()).then(|| note.to_string());
+        self.save_todo_list()
+    }
+
+    /// Sets or, with `None`, removes the due date of the item at `index`
+    pub fn set_due_date(
+        &mut self,
+        index: usize,
+        date: Option<NaiveDate>,
+    ) -> Result<(), Box<dyn Error>> {
+        if index < self.todo_list.items.len() {
+            self.todo_list.items[index].set_due_date(date);
+            self.save_todo_list()?;
+        }
+        Ok(())
+    }
+
+    /// Inserts `items` in front of the item at `at` (or at the end)
+    pub fn insert_items(&mut self, at: usize, items: Vec<TodoItem>) -> Result<(), Box<dyn Error>> {
+        let at = at.min(self.todo_list.items.len());
+        self.todo_list.items.splice(at..at, items);
+        self.save_todo_list()
+    }
+
+    pub fn split_item(
+        &mut self,
+        index: usize,
+        head: String,
+        tail: String,
+    ) -> Result<(), Box<dyn Error>> {
+        if index < self.todo_list.items.len() {
+            let indent_level = self.todo_list.items[index].indent_level;
+            self.todo_list.items[index].text = head;
+            self.todo_list
+                .items
+                .insert(index + 1, TodoItem::new(tail, false, indent_level));
             self.save_todo_list()?;
         }
         Ok(())
     }
 
+    /// Appends the next item's text to the item at `index` and removes the
+    /// next item. Joining is refused (returning `false`) when the next item
+    /// has subitems, since there is no obvious place for them to go.
+    pub fn join_with_next(&mut self, index: usize) -> Result<bool, Box<dyn Error>> {
+        let next = index + 1;
+        if next >= self.todo_list.items.len() || self.todo_list.subtree_range(next).len() > 1 {
+            return Ok(false);
+        }
+
+        let absorbed = self.todo_list.items.remove(next);
+        let text = &mut self.todo_list.items[index].text;
+        if !text.is_empty() && !absorbed.text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(&absorbed.text);
+        self.save_todo_list()?;
+        Ok(true)
+    }
+
     pub fn indent_item_left(&mut self, index: usize) -> Result<(), Box<dyn Error>> {
         self.indent_item_by(index, -1)
     }
@@ -176,8 +890,29 @@ impl TodoApp {
 
     fn indent_item_by(&mut self, index: usize, delta: isize) -> Result<(), Box<dyn Error>> {
         if index < self.todo_list.items.len() {
-            let level = &mut self.todo_list.items[index].indent_level;
-            *level = level.saturating_add_signed(delta);
+            let level = self.todo_list.items[index].indent_level;
+            let at_max = self.config.max_indent.is_some_and(|max| level >= max);
+            if delta > 0 && at_max {
+                return Ok(());
+            }
+
+            // Unindenting an item leaves a gap of two levels above its
+            // subitems unless they come along
+            let mut moved = index..index + 1;
+            if delta < 0 && level > 0 {
+                let subtree = self.todo_list.subtree_range(index);
+                match self.config.unindent_subitems {
+                    UnindentSubitems::Keep | UnindentSubitems::Confirm => {}
+                    UnindentSubitems::Move => moved = subtree,
+                    UnindentSubitems::Refuse if subtree.len() > 1 => {
+                        return Err("Can't unindent an item with subitems".into());
+                    }
+                    UnindentSubitems::Refuse => {}
+                }
+            }
+            for item in &mut self.todo_list.items[moved] {
+                item.indent_level = item.indent_level.saturating_add_signed(delta);
+            }
             self.save_todo_list()?;
         }
         Ok(())
@@ -191,142 +926,756 @@ impl TodoApp {
         Ok(())
     }
 
-    pub fn insert_new_item(&mut self, at: usize) -> Result<(), Box<dyn Error>> {
-        let indent_level = if let Some(last_item) = self.todo_list.items.last() {
-            last_item.indent_level
-        } else {
-            0
-        };
+    /// Applies the configured automatic sort, returning the new index of the
+    /// item that was at `follow_index`
+    pub fn apply_auto_sort(&mut self, follow_index: usize) -> Result<usize, Box<dyn Error>> {
+        if self.config.auto_sort == SortMode::None {
+            return Ok(follow_index);
+        }
 
-        let new_item = TodoItem::new(String::new(), false, indent_level);
-        self.todo_list.items.insert(at, new_item);
-        self.save_todo_list()?;
-        Ok(())
+        let order = self
+            .todo_list
+            .sort(self.config.auto_sort, self.config.completed_secondary);
+        if order.iter().enumerate().any(|(new, old)| new != *old) {
+            self.save_todo_list()?;
+        }
+        Ok(order
+            .iter()
+            .position(|old| *old == follow_index)
+            .unwrap_or(follow_index))
     }
 
-    pub fn move_item(&mut self, from_index: usize, to_index: usize) -> Result<(), Box<dyn Error>> {
-        if from_index < self.todo_list.items.len()
-            && to_index < self.todo_list.items.len()
-            && from_index != to_index
-        {
-            let item = self.todo_list.items.remove(from_index);
-            self.todo_list.items.insert(to_index, item);
+    /// Removes all completed items, returning how many were removed. Incomplete
+    /// children of completed items are kept.
+    pub fn clear_completed(&mut self) -> Result<usize, Box<dyn Error>> {
+        let before = self.todo_list.items.len();
+        self.todo_list.items.retain(|item| !item.completed);
+        let removed = before - self.todo_list.items.len();
+        if removed > 0 {
             self.save_todo_list()?;
         }
-        Ok(())
+        Ok(removed)
     }
-}
 
-impl Drop for TodoApp {
-    fn drop(&mut self) {
-        let _ = self.save_todo_list();
+    /// Appends a new top-level item to the end of the list, returning its index
+    pub fn append_item(&mut self, text: String) -> Result<usize, Box<dyn Error>> {
+        self.todo_list.items.push(TodoItem::new(text, false, 0));
+        self.save_todo_list()?;
+        Ok(self.todo_list.items.len() - 1)
     }
-}
 
-fn get_config_dir() -> Result<PathBuf, Box<dyn Error>> {
-    let home_dir = dirs::home_dir().ok_or("Unable to find home directory")?;
-    Ok(home_dir.join(".todui"))
-}
+    /// Indentation level for a new item inserted at `index`, following the
+    /// configured `new_item_indent` policy and capped at `max_indent`
+    pub fn default_indent_for(&self, index: usize) -> usize {
+        let items = &self.todo_list.items;
+        let previous = index
+            .checked_sub(1)
+            .and_then(|i| items.get(i))
+            .map_or(0, |item| item.indent_level);
+        let indent_level = match self.config.new_item_indent {
+            NewItemIndent::Previous => previous,
+            NewItemIndent::Next => items.get(index).map_or(previous, |item| item.indent_level),
+            NewItemIndent::Fixed => self.config.new_item_base_indent,
+        };
+        self.capped_indent(indent_level)
+    }
 
-fn create_lock_file(config_dir: &PathBuf) -> Result<PathBuf, Box<dyn Error>> {
-    let lock_path = config_dir.join("lockfile");
+    fn capped_indent(&self, indent_level: usize) -> usize {
+        match self.config.max_indent {
+            Some(max) => indent_level.min(max),
+            None => indent_level,
+        }
+    }
 
-    if lock_path.exists() {
-        return Err(format!(
-            "Another instance of todui appears to be running. Lock file exists at: {}",
-            lock_path.display()
-        )
-        .into());
+    /// An empty item to insert at `indent_level` (capped at `max_indent`),
+    /// completed if `new_item_completed` is set
+    pub fn new_empty_item(&self, indent_level: usize) -> TodoItem {
+        TodoItem::builder()
+            .completed(self.config.new_item_completed)
+            .indent(self.capped_indent(indent_level))
+            .build()
     }
 
-    let mut file = OpenOptions::new()
-        .write(true)
-        .create_new(true)
-        .open(&lock_path)?;
+    pub fn insert_new_item(&mut self, at: usize) -> Result<(), Box<dyn Error>> {
+        let new_item = self.new_empty_item(self.default_indent_for(at));
+        self.todo_list.items.insert(at, new_item);
+        self.save_todo_list()?;
+        Ok(())
+    }
 
-    let pid = process::id();
-    writeln!(file, "{}", pid)?;
-    file.flush()?;
+    /// Inserts an empty item as the first child of the item at `parent`,
+    /// returning the new item's index
+    pub fn insert_child_item(&mut self, parent: usize) -> Result<usize, Box<dyn Error>> {
+        let Some(parent_item) = self.todo_list.items.get(parent) else {
+            let at = self.todo_list.items.len();
+            self.insert_new_item(at)?;
+            return Ok(at);
+        };
 
-    Ok(lock_path)
-}
+        let at = parent + 1;
+        let new_item = self.new_empty_item(parent_item.indent_level + 1);
+        self.todo_list.items.insert(at, new_item);
+        self.save_todo_list()?;
+        Ok(at)
+    }
 
-fn load_or_create_todo_list(
-    config_dir: &PathBuf,
-    target_date: NaiveDate,
-) -> Result<TodoList, Box<dyn Error>> {
-    // Find the newest todo file that's not in the future
-    let mut newest_file: Option<(NaiveDate, PathBuf)> = None;
+    /// Moves the item and its subtree to the top of its sibling group,
+    /// returning the item's new index
+    pub fn move_item_to_top(&mut self, index: usize) -> Result<usize, Box<dyn Error>> {
+        self.move_subtree_within_siblings(index, false)
+    }
 
-    if let Ok(entries) = fs::read_dir(config_dir) {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
-                    if file_name.starts_with("TODO-") && file_name.ends_with(".md") {
-                        let date_part = file_name
-                            .strip_prefix("TODO-")
-                            .unwrap()
-                            .strip_suffix(".md")
-                            .unwrap();
-
-                        if let Ok(file_date) = NaiveDate::parse_from_str(date_part, "%Y-%m-%d") {
-                            if file_date <= target_date {
-                                if newest_file.is_none()
-                                    || file_date > newest_file.as_ref().unwrap().0
-                                {
-                                    newest_file = Some((file_date, path));
-                                }
-                            } else {
-                                eprintln!(
-                                    "Warning: Found todo file with future date: {}",
-                                    file_name
-                                );
-                            }
-                        }
-                    }
-                }
-            }
-        }
+    /// Moves the item and its subtree to the bottom of its sibling group,
+    /// returning the item's new index
+    pub fn move_item_to_bottom(&mut self, index: usize) -> Result<usize, Box<dyn Error>> {
+        self.move_subtree_within_siblings(index, true)
     }
 
-    if let Some((file_date, path)) = newest_file {
-        let content = fs::read_to_string(&path)?;
-        let mut todo_list = TodoList::from_markdown(&content)?;
-        // Update the date to current date if it's different
-        if file_date != target_date {
-            todo_list.date = target_date;
+    fn move_subtree_within_siblings(
+        &mut self,
+        index: usize,
+        to_bottom: bool,
+    ) -> Result<usize, Box<dyn Error>> {
+        if index >= self.todo_list.items.len() {
+            return Ok(index);
         }
-        Ok(todo_list)
+
+        let subtree = self.todo_list.subtree_range(index);
+        let siblings = self.todo_list.sibling_range(index);
+        let block: Vec<TodoItem> = self.todo_list.items.drain(subtree.clone()).collect();
+        let new_index = if to_bottom {
+            siblings.end - block.len()
+        } else {
+            siblings.start
+        };
+        self.todo_list.items.splice(new_index..new_index, block);
+
+        if new_index != index {
+            self.save_todo_list()?;
+        }
+        Ok(new_index)
+    }
+
+    /// With `sink_completed` set, moves a completed item and its subtree to the
+    /// bottom of its sibling group, or a reopened one back above its completed
+    /// siblings. Returns the item's new index.
+    pub fn sink_item(&mut self, index: usize) -> Result<usize, Box<dyn Error>> {
+        if !self.config.sink_completed || index >= self.todo_list.items.len() {
+            return Ok(index);
+        }
+        if self.todo_list.items[index].completed {
+            return self.move_subtree_within_siblings(index, true);
+        }
+
+        let level = self.todo_list.items[index].indent_level;
+        let siblings = self.todo_list.sibling_range(index);
+        let first_completed = (siblings.start..index).find(|&i| {
+            let item = &self.todo_list.items[i];
+            item.indent_level == level && item.completed
+        });
+        let Some(new_index) = first_completed else {
+            return Ok(index);
+        };
+
+        let subtree = self.todo_list.subtree_range(index);
+        let block: Vec<TodoItem> = self.todo_list.items.drain(subtree).collect();
+        self.todo_list.items.splice(new_index..new_index, block);
+        self.save_todo_list()?;
+        Ok(new_index)
+    }
+
+    /// Moves the item at `from_index` so that it ends up at `to_index`,
+    /// together with its subtree unless `move_subtree` is off (moving down,
+    /// the subtree ends there instead). Afterwards the moved items are
+    /// reflowed (see `TodoList::reflow_indents`) to fit below their new
+    /// neighbour, and so are the items following both places as far as they
+    /// no longer fit, like subitems left behind. The rest of the list keeps
+    /// its levels.
+    pub fn move_item(&mut self, from_index: usize, to_index: usize) -> Result<(), Box<dyn Error>> {
+        let len = self.todo_list.items.len();
+        if from_index >= len || to_index >= len || from_index == to_index {
+            return Ok(());
+        }
+        let moved = if self.config.move_subtree {
+            self.todo_list.subtree_range(from_index)
+        } else {
+            from_index..from_index + 1
+        };
+        if moved.contains(&to_index) {
+            return Ok(());
+        }
+
+        let items = &mut self.todo_list.items;
+        let block: Vec<TodoItem> = items.drain(moved.clone()).collect();
+        let insert_at = if to_index > from_index {
+            to_index + 1 - block.len()
+        } else {
+            to_index
+        };
+        items.splice(insert_at..insert_at, block);
+
+        // Fit the block at its new place and whatever now follows its old
+        // place, top to bottom
+        let new_range = insert_at..insert_at + moved.len();
+        let old_place = if insert_at > from_index {
+            from_index..from_index
+        } else {
+            moved.end..moved.end
+        };
+        let mut ranges = [old_place, new_range];
+        ranges.sort_by_key(|range| range.start);
+        for range in ranges {
+            reflow_levels_within(items, range);
+        }
+
+        self.save_todo_list()?;
+        Ok(())
+    }
+}
+
+impl Drop for TodoApp {
+    fn drop(&mut self) {
+        let _ = self.save_todo_list();
+    }
+}
+
+fn get_config_dir(args: &Args) -> Result<PathBuf, Box<dyn Error>> {
+    resolve_config_dir(
+        args.config_dir.clone(),
+        std::env::var_os("TODUI_HOME").map(PathBuf::from),
+        dirs::config_dir().map(|dir| dir.join("todui")),
+        dirs::home_dir(),
+    )
+}
+
+/// Picks the config directory with precedence `--config-dir` > `TODUI_HOME` >
+/// `$XDG_CONFIG_HOME/todui` (only if it already exists) > `~/.todui`
+fn resolve_config_dir(
+    arg: Option<PathBuf>,
+    env: Option<PathBuf>,
+    xdg_dir: Option<PathBuf>,
+    home_dir: Option<PathBuf>,
+) -> Result<PathBuf, Box<dyn Error>> {
+    if let Some(dir) = arg {
+        return Ok(dir);
+    }
+    if let Some(dir) = env.filter(|dir| !dir.as_os_str().is_empty()) {
+        return Ok(dir);
+    }
+    if let Some(dir) = xdg_dir.filter(|dir| dir.is_dir()) {
+        return Ok(dir);
+    }
+    let home_dir = home_dir.ok_or(
+        "Unable to determine the config directory: pass --config-dir, set TODUI_HOME, \
+         create $XDG_CONFIG_HOME/todui, or make sure a home directory is available",
+    )?;
+    Ok(home_dir.join(".todui"))
+}
+
+fn create_lock_file(config_dir: &Path) -> Result<PathBuf, Box<dyn Error>> {
+    let lock_path = config_dir.join("lockfile");
+
+    if lock_path.exists() {
+        return Err(format!(
+            "Another instance of todui appears to be running. Lock file exists at: {}",
+            lock_path.display()
+        )
+        .into());
+    }
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&lock_path)?;
+
+    let pid = process::id();
+    writeln!(file, "{}", pid)?;
+    file.flush()?;
+
+    Ok(lock_path)
+}
+
+/// What an existing lock file tells about the instance that created it
+#[derive(Debug, PartialEq)]
+enum LockState {
+    /// The process that wrote the lock file is still running
+    Held(u32),
+    /// The process is gone, e.g. because it crashed
+    Stale(u32),
+    /// The lock file doesn't contain a PID (yet)
+    Unknown,
+}
+
+fn lock_state(lock_path: &Path) -> LockState {
+    let pid = fs::read_to_string(lock_path)
+        .ok()
+        .and_then(|content| content.trim().parse::<u32>().ok());
+    match pid {
+        Some(pid) if process_alive(pid) => LockState::Held(pid),
+        Some(pid) => LockState::Stale(pid),
+        None => LockState::Unknown,
+    }
+}
+
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // Signal 0 only checks whether the process exists. EPERM means it exists
+    // but belongs to someone else.
+    let result = unsafe { libc::kill(pid, 0) };
+    result == 0 || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> bool {
+    // Without a way to check, never treat a lock as stale
+    true
+}
+
+/// Takes the lock file for the interactive app. A lock left behind by a
+/// process that is no longer running is removed if `force` is set or
+/// `confirm` agrees.
+fn acquire_lock(
+    config_dir: &Path,
+    force: bool,
+    confirm: impl FnOnce(u32) -> io::Result<bool>,
+) -> Result<PathBuf, Box<dyn Error>> {
+    let lock_path = config_dir.join("lockfile");
+    if let LockState::Stale(pid) = lock_state(&lock_path) {
+        if !force && !confirm(pid)? {
+            return Err(format!(
+                "Stale lock file from PID {} left in place at: {}",
+                pid,
+                lock_path.display()
+            )
+            .into());
+        }
+        fs::remove_file(&lock_path)?;
+    }
+    create_lock_file(config_dir)
+}
+
+/// Asks on the terminal whether to remove a stale lock
+fn confirm_stale_lock_removal(pid: u32) -> io::Result<bool> {
+    eprint!(
+        "Stale lock from PID {} (no longer running), remove and continue? [y/N] ",
+        pid
+    );
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(answer.trim().eq_ignore_ascii_case("y"))
+}
+
+/// `TodoList::reflow_indents` for a run of items, as if it were the whole list
+fn reflow_levels(items: &mut [TodoItem]) -> usize {
+    let len = items.len();
+    reflow_levels_within(items, 0..len)
+}
+
+/// Reflows the items in `range` below the items before it, which keep their
+/// levels, and the items after it until one fits where it is. Returns the
+/// number of items whose level changed.
+fn reflow_levels_within(items: &mut [TodoItem], range: Range<usize>) -> usize {
+    // Original and new levels of the items the current one could be below
+    let mut ancestors: Vec<(usize, usize)> = Vec::new();
+    let mut changed = 0;
+    for (index, item) in items.iter_mut().enumerate() {
+        while ancestors
+            .last()
+            .is_some_and(|&(level, _)| level >= item.indent_level)
+        {
+            ancestors.pop();
+        }
+        if index < range.start {
+            ancestors.push((item.indent_level, item.indent_level));
+            continue;
+        }
+        let level = ancestors.last().map_or(0, |&(_, new_level)| new_level + 1);
+        if index >= range.end
+            && item.indent_level == level
+            && ancestors
+                .iter()
+                .all(|&(old_level, new_level)| old_level == new_level)
+        {
+            break;
+        }
+        ancestors.push((item.indent_level, level));
+        if item.indent_level != level {
+            item.indent_level = level;
+            changed += 1;
+        }
+    }
+    changed
+}
+
+/// A failed save of the list, kept apart from other errors so the terminal
+/// UI can show it until a save succeeds
+#[derive(Debug)]
+pub struct SaveError(Box<dyn Error>);
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Error for SaveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.0.as_ref())
+    }
+}
+
+/// Writes `content` to a temporary file next to `path` and renames it into
+/// place, so readers see either the old or the new file but never a partly
+/// written one
+fn write_atomically(path: &Path, content: &str) -> Result<(), Box<dyn Error>> {
+    // Renaming onto a symlink would replace it, so write to its target
+    let path = &resolve_symlinks(path)?;
+    let file_name = path.file_name().ok_or("Invalid todo file path")?;
+    let mut tmp_name = std::ffi::OsString::from(".");
+    tmp_name.push(file_name);
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(content.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Follows `path` through any symlinks to the file they point to, which
+/// doesn't need to exist yet
+fn resolve_symlinks(path: &Path) -> Result<PathBuf, Box<dyn Error>> {
+    // Same limit as Linux, to stop at symlink loops
+    const MAX_LINKS: usize = 40;
+
+    let mut path = path.to_path_buf();
+    for _ in 0..MAX_LINKS {
+        match fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                // Relative targets are relative to the link's directory
+                let target = fs::read_link(&path)?;
+                path = match path.parent() {
+                    Some(dir) => dir.join(target),
+                    None => target,
+                };
+            }
+            _ => return Ok(path),
+        }
+    }
+    Err(format!("Too many levels of symbolic links at {}", path.display()).into())
+}
+
+/// Reads a consistent snapshot of a todo file. Saves replace the file with a
+/// rename, so this never blocks the writer and never sees a half-written file.
+fn read_todo_list(path: &Path, format: &MarkdownFormat) -> Result<TodoList, Box<dyn Error>> {
+    let (todo_list, warnings) = read_todo_list_quietly(path, format)?;
+    for warning in warnings {
+        warn(warning);
+    }
+    Ok(todo_list)
+}
+
+/// Like `read_todo_list`, but returns the warnings (naming the file) instead
+/// of printing them, for callers that can't write to the terminal
+fn read_todo_list_quietly(
+    path: &Path,
+    format: &MarkdownFormat,
+) -> Result<(TodoList, Vec<String>), Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+    let todo_list = TodoList::from_markdown_with(&content, format.clone())?;
+    let warnings = todo_list
+        .warnings
+        .iter()
+        .map(|warning| format!("{}: {}", path.display(), warning))
+        .collect();
+    Ok((todo_list, warnings))
+}
+
+/// Parses the date out of a `TODO-YYYY-MM-DD.md` file name
+fn parse_todo_file_name(file_name: &str) -> Option<NaiveDate> {
+    let date_part = file_name.strip_prefix("TODO-")?.strip_suffix(".md")?;
+    NaiveDate::parse_from_str(date_part, "%Y-%m-%d").ok()
+}
+
+/// Lists all todo files in `config_dir` with their dates, oldest first
+fn todo_files(config_dir: &Path) -> Vec<(NaiveDate, PathBuf)> {
+    let mut files = Vec::new();
+
+    if let Ok(entries) = fs::read_dir(config_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let file_date = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(parse_todo_file_name);
+            if let Some(file_date) = file_date {
+                files.push((file_date, path));
+            }
+        }
+    }
+
+    files.sort();
+    files
+}
+
+/// Path of the file holding the list of `date`: its own `TODO-YYYY-MM-DD.md`,
+/// or the one `TODO.md` with `single_file`
+fn todo_file_path(config_dir: &Path, config: &Config, date: NaiveDate) -> PathBuf {
+    if config.single_file {
+        config_dir.join(SINGLE_FILE_NAME)
+    } else {
+        config_dir.join(TodoList::new(date).filename())
+    }
+}
+
+/// The todo files the app works with, oldest first: the dated ones, or with
+/// `single_file` just `TODO.md` under the date in its header
+fn todo_files_for(config_dir: &Path, config: &Config) -> Vec<(NaiveDate, PathBuf)> {
+    if !config.single_file {
+        return todo_files(config_dir);
+    }
+    let path = config_dir.join(SINGLE_FILE_NAME);
+    if !path.exists() {
+        return Vec::new();
+    }
+    match read_todo_list_quietly(&path, &config.format) {
+        Ok((todo_list, _)) => vec![(todo_list.date, path)],
+        Err(err) => {
+            warn(format!("Skipping {}: {}", path.display(), err));
+            Vec::new()
+        }
+    }
+}
+
+fn load_or_create_todo_list(
+    config_dir: &Path,
+    target_date: NaiveDate,
+    config: &Config,
+) -> Result<TodoList, Box<dyn Error>> {
+    load_or_start_day(config_dir, target_date, config).map(|(todo_list, _)| todo_list)
+}
+
+/// Like `load_or_create_todo_list`, also telling whether `target_date` had no
+/// file yet, so the list was created or carried over from an older day
+fn load_or_start_day(
+    config_dir: &Path,
+    target_date: NaiveDate,
+    config: &Config,
+) -> Result<(TodoList, bool), Box<dyn Error>> {
+    // Find the newest todo file that's not in the future. The single file
+    // has no date in its name; its header tells the day it was saved on.
+    let mut newest_file: Option<(Option<NaiveDate>, PathBuf)> = None;
+
+    if config.single_file {
+        let path = config_dir.join(SINGLE_FILE_NAME);
+        newest_file = path.exists().then_some((None, path));
+    } else {
+        for (file_date, path) in todo_files(config_dir) {
+            if file_date <= target_date {
+                newest_file = Some((Some(file_date), path));
+            } else {
+                warn(format!(
+                    "Found todo file with future date: {}",
+                    path.display()
+                ));
+            }
+        }
+    }
+
+    if let Some((file_date, path)) = newest_file {
+        let mut todo_list = read_todo_list(&path, &config.format)?;
+        let file_date = file_date.unwrap_or(todo_list.date);
+        if config.reflow_on_load {
+            todo_list.reflow_indents();
+        }
+        // Update the date to current date if it's different
+        if file_date != target_date {
+            todo_list.date = target_date;
+            todo_list.defer_open_items();
+        }
+        todo_list.sort(config.auto_sort, config.completed_secondary);
+        Ok((todo_list, file_date != target_date))
     } else {
         // Create new todo list for today
-        Ok(TodoList::new(target_date))
+        let mut todo_list = TodoList::new(target_date);
+        todo_list.format = config.format.clone();
+        Ok((todo_list, true))
+    }
+}
+
+/// Loads or creates today's list for the interactive UIs and pulls in overdue
+/// items. A new day is saved right away, so only its first start counts as
+/// one. Returns the app and whether the day is new.
+fn open_today(
+    config_dir: PathBuf,
+    config: Config,
+    today: NaiveDate,
+) -> Result<(TodoApp, bool), Box<dyn Error>> {
+    let (mut todo_list, new_day) = load_or_start_day(&config_dir, today, &config)?;
+    if let Err(err) = pull_overdue_items(&config_dir, &config, today, &mut todo_list) {
+        warn(format!("Could not pull in overdue items: {}", err));
+    }
+
+    let mut todo_app = TodoApp::new(config_dir, todo_list).with_config(config);
+    let saved = if new_day {
+        todo_app.save_todo_list_on(today)
+    } else {
+        Ok(())
+    };
+    if let Err(err) = saved {
+        warn(format!("Could not save the new day: {}", err));
+    }
+    Ok((todo_app, new_day))
+}
+
+/// Moves open items that are due by `today` from the files of the previous
+/// `pull_overdue_days` days to the end of `todo_list`, together with their
+/// subitems, and saves it. Pulled items are marked like carried over ones;
+/// items already on the list are left alone. The old files keep their items
+/// unless `pull_overdue_remove` is set. Returns the number of pulled items.
+fn pull_overdue_items(
+    config_dir: &Path,
+    config: &Config,
+    today: NaiveDate,
+    todo_list: &mut TodoList,
+) -> Result<usize, Box<dyn Error>> {
+    if !config.pull_overdue {
+        return Ok(0);
+    }
+
+    // Deferral counts differ from day to day, so they don't tell items apart
+    let key = |item: &TodoItem| {
+        let mut item = item.clone();
+        item.set_deferred_count(0);
+        item.text
+    };
+    // A range reaching back before the earliest date has no limit
+    let since = today
+        .checked_sub_days(Days::new(config.pull_overdue_days.into()))
+        .unwrap_or(NaiveDate::MIN);
+    let mut pulled = 0;
+    let mut changed_files = Vec::new();
+    for (date, path) in todo_files(config_dir) {
+        if date < since || date >= today {
+            continue;
+        }
+        let mut source = read_todo_list(&path, &config.format)?;
+        let mut index = 0;
+        let mut removed = false;
+        while index < source.items.len() {
+            let item = &source.items[index];
+            let overdue =
+                item.is_task && !item.completed && item.due_date().is_some_and(|due| due <= today);
+            let known = todo_list
+                .items
+                .iter()
+                .any(|existing| key(existing) == key(item));
+            if !overdue || known {
+                index += 1;
+                continue;
+            }
+
+            let range = source.subtree_range(index);
+            let level = item.indent_level;
+            let subtree: Vec<TodoItem> = if config.pull_overdue_remove {
+                removed = true;
+                source.items.drain(range).collect()
+            } else {
+                index = range.end;
+                source.items[range].to_vec()
+            };
+            for (i, mut item) in subtree.into_iter().enumerate() {
+                item.indent_level -= level;
+                item.carried = i == 0;
+                todo_list.items.push(item);
+            }
+            pulled += 1;
+        }
+        if removed {
+            changed_files.push((path, source));
+        }
     }
+
+    if pulled > 0 {
+        // Today's list gets them first, so nothing is lost if saving fails
+        write_atomically(
+            &todo_file_path(config_dir, config, todo_list.date),
+            &todo_list.to_markdown(),
+        )?;
+        for (path, source) in changed_files {
+            write_atomically(&path, &source.to_markdown())?;
+        }
+    }
+    Ok(pulled)
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
     let args = Args::parse();
+    QUIET.store(args.quiet, Ordering::Relaxed);
 
     // Common initialization
-    let config_dir = get_config_dir()?;
+    let config_dir = get_config_dir(&args)?;
 
     // Create config directory if it doesn't exist
     if !config_dir.exists() {
         fs::create_dir_all(&config_dir)?;
     }
 
+    let mut config = Config::load(&config_dir)?;
+    if let Some(theme) = args.theme {
+        config.theme = theme;
+    }
+    if let Some(color) = args.color {
+        config.color = color;
+    }
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    config.color = config.color.resolve(no_color, io::stdout().is_terminal());
+    if args.plain_ascii {
+        config.plain_ascii = true;
+    }
+    if args.single_file {
+        config.single_file = true;
+    }
+    if args.reflow {
+        config.reflow_on_load = true;
+    }
+    if args.show_path {
+        config.show_file_path = true;
+    }
+    if args.classic_complete {
+        config.apply_classic_complete();
+    }
+
+    // Subcommands take the lock file themselves if they write to the todo files
+    if let Some(command) = args.command {
+        return commands::run(command, &config_dir, &config, args.output.as_deref());
+    }
+
     // Create and hold lock file
-    let lock_file = create_lock_file(&config_dir)?;
+    let lock_file = acquire_lock(&config_dir, args.force, confirm_stale_lock_removal)?;
 
-    // Load or create today's todo list
     let today = Local::now().date_naive();
-    let todo_list = load_or_create_todo_list(&config_dir, today)?;
+    let pruned = if args.no_prune {
+        Ok(())
+    } else {
+        commands::prune(&config_dir, &config, today)
+    };
+    if let Err(err) = pruned {
+        warn(format!("Could not prune old todo files: {}", err));
+    }
 
-    let todo_app = TodoApp::new(config_dir, todo_list);
+    let greet_on_new_day = config.new_day_greeting;
+    let (todo_app, new_day) = open_today(config_dir, config, today)?;
+    let greet = new_day && greet_on_new_day;
     if args.gui {
         gui::run_gui(todo_app)?
     } else {
-        tui::run_tui(todo_app)?
+        tui::run_tui(todo_app, greet, !args.no_alt_screen)?
     }
 
     fs::remove_file(lock_file)?;
@@ -338,6 +1687,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 mod tests {
     use super::*;
     use chrono::NaiveDate;
+    use config::IndentRounding;
 
     #[test]
     fn test_empty_todo_list_to_markdown() {
@@ -347,6 +1697,40 @@ mod tests {
         assert_eq!(markdown, "# TODO 2025-08-14\n\n");
     }
 
+    #[test]
+    fn test_to_markdown_layout_options() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list.format.final_newline = false;
+        assert_eq!(todo_list.to_markdown(), "# TODO 2025-08-14\n");
+
+        todo_list
+            .items
+            .push(TodoItem::new("a".to_string(), false, 0));
+        todo_list
+            .items
+            .push(TodoItem::new("b".to_string(), true, 1));
+        assert_eq!(
+            todo_list.to_markdown(),
+            "# TODO 2025-08-14\n\n* [ ] a\n  * [x] b"
+        );
+
+        todo_list.format.final_newline = true;
+        todo_list.format.blank_lines_after_header = 0;
+        let markdown = todo_list.to_markdown();
+        assert_eq!(markdown, "# TODO 2025-08-14\n* [ ] a\n  * [x] b\n");
+
+        // Either way the file reads back the same
+        todo_list.format.blank_lines_after_header = 2;
+        todo_list.format.final_newline = false;
+        for markdown in [markdown, todo_list.to_markdown()] {
+            let parsed = TodoList::from_markdown(&markdown).unwrap();
+            assert_eq!(parsed.items.len(), 2);
+            assert_eq!(parsed.items[1].text, "b");
+            assert_eq!(parsed.items[1].indent_level, 1);
+        }
+    }
+
     #[test]
     fn test_todo_list_with_items_to_markdown() {
         let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
@@ -510,6 +1894,116 @@ mod tests {
         let _ = std::fs::remove_dir_all(&config_dir);
     }
 
+    #[test]
+    fn test_move_item_carries_subtree() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let mut todo_list = TodoList::new(date);
+        for (text, level) in [
+            ("A", 0),
+            ("A1", 1),
+            ("P", 1),
+            ("P1", 2),
+            ("P1a", 3),
+            ("B", 0),
+            ("C", 0),
+        ] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), false, level));
+        }
+        let mut todo_app = TodoApp::new(dir.path().to_path_buf(), todo_list);
+        let layout = |todo_app: &TodoApp| -> Vec<(String, usize)> {
+            todo_app
+                .todo_list
+                .items
+                .iter()
+                .map(|i| (i.text.clone(), i.indent_level))
+                .collect()
+        };
+
+        // Moving P down onto B puts the whole subtree below B, as B's child
+        todo_app.move_item(2, 5).unwrap();
+        let expected = [
+            ("A", 0),
+            ("A1", 1),
+            ("B", 0),
+            ("P", 1),
+            ("P1", 2),
+            ("P1a", 3),
+            ("C", 0),
+        ];
+        assert_eq!(layout(&todo_app), expected.map(|(t, l)| (t.to_string(), l)));
+
+        // Moving it to the top flattens it to the top level, children follow
+        todo_app.move_item(3, 0).unwrap();
+        let expected = [
+            ("P", 0),
+            ("P1", 1),
+            ("P1a", 2),
+            ("A", 0),
+            ("A1", 1),
+            ("B", 0),
+            ("C", 0),
+        ];
+        assert_eq!(layout(&todo_app), expected.map(|(t, l)| (t.to_string(), l)));
+
+        // Moving an item into its own subtree does nothing
+        todo_app.move_item(0, 2).unwrap();
+        assert_eq!(todo_app.todo_list.items[0].text, "P");
+
+        // Without carrying the subtree, P's subitems stay behind and move up
+        // to the top level, where they have no parent anymore
+        todo_app.config.move_subtree = false;
+        todo_app.move_item(0, 4).unwrap();
+        let expected = [
+            ("P1", 0),
+            ("P1a", 1),
+            ("A", 0),
+            ("A1", 1),
+            ("P", 0),
+            ("B", 0),
+            ("C", 0),
+        ];
+        assert_eq!(layout(&todo_app), expected.map(|(t, l)| (t.to_string(), l)));
+
+        // A parent can move below its own subitems then, which end up below
+        // the item above them
+        todo_app.move_item(2, 3).unwrap();
+        let expected = [
+            ("P1", 0),
+            ("P1a", 1),
+            ("A1", 1),
+            ("A", 0),
+            ("P", 0),
+            ("B", 0),
+            ("C", 0),
+        ];
+        assert_eq!(layout(&todo_app), expected.map(|(t, l)| (t.to_string(), l)));
+    }
+
+    #[test]
+    fn test_move_item_leaves_other_items_alone() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let mut todo_list = TodoList::new(date);
+        for (text, level) in [("A", 0), ("A1", 1), ("A1a", 3), ("A2", 2), ("B", 0)] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), false, level));
+        }
+        let mut todo_app = TodoApp::new(dir.path().to_path_buf(), todo_list);
+        let levels = |todo_app: &TodoApp| -> Vec<usize> {
+            let items = &todo_app.todo_list.items;
+            items.iter().map(|i| i.indent_level).collect()
+        };
+
+        // A2 fits below A once moved there, and the gap before A1a stays
+        todo_app.move_item(3, 1).unwrap();
+        assert_eq!(todo_app.todo_list.items[1].text, "A2");
+        assert_eq!(levels(&todo_app), [0, 1, 1, 3, 0]);
+    }
+
     #[test]
     fn test_move_item_bounds_checking() {
         let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
@@ -540,4 +2034,1536 @@ mod tests {
         // Cleanup
         let _ = std::fs::remove_dir_all(&config_dir);
     }
+
+    #[test]
+    fn test_quiet_flag_parsing() {
+        let args = Args::try_parse_from(["todui"]).unwrap();
+        assert!(!args.quiet);
+
+        let args = Args::try_parse_from(["todui", "--quiet"]).unwrap();
+        assert!(args.quiet);
+    }
+
+    #[test]
+    fn test_subtree_and_sibling_ranges() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        for (text, level) in [("A", 0), ("A1", 1), ("A1a", 2), ("A2", 1), ("B", 0)] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), false, level));
+        }
+
+        assert_eq!(todo_list.subtree_range(0), 0..4);
+        assert_eq!(todo_list.subtree_range(1), 1..3);
+        assert_eq!(todo_list.subtree_range(3), 3..4);
+        assert_eq!(todo_list.subtree_range(4), 4..5);
+
+        assert_eq!(todo_list.sibling_range(0), 0..5);
+        assert_eq!(todo_list.sibling_range(3), 1..4);
+        assert_eq!(todo_list.sibling_range(2), 2..3);
+    }
+
+    #[test]
+    fn test_move_item_to_top_and_bottom_within_parent() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let config_dir = tempfile::tempdir().unwrap();
+
+        let mut todo_list = TodoList::new(date);
+        for (text, level) in [
+            ("A", 0),
+            ("A1", 1),
+            ("A2", 1),
+            ("A2a", 2),
+            ("A3", 1),
+            ("B", 0),
+        ] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), false, level));
+        }
+        let mut todo_app = TodoApp::new(config_dir.path().to_path_buf(), todo_list);
+        let texts = |app: &TodoApp| -> Vec<String> {
+            app.todo_list.items.iter().map(|i| i.text.clone()).collect()
+        };
+
+        // Nested item moves to the top of its parent's children, carrying its subtree
+        assert_eq!(todo_app.move_item_to_top(2).unwrap(), 1);
+        assert_eq!(texts(&todo_app), ["A", "A2", "A2a", "A1", "A3", "B"]);
+
+        // ... and to the bottom, without escaping the parent
+        assert_eq!(todo_app.move_item_to_bottom(1).unwrap(), 3);
+        assert_eq!(texts(&todo_app), ["A", "A1", "A3", "A2", "A2a", "B"]);
+        assert_eq!(todo_app.todo_list.items[4].indent_level, 2);
+
+        // Top-level items move within the whole list
+        assert_eq!(todo_app.move_item_to_bottom(0).unwrap(), 1);
+        assert_eq!(texts(&todo_app), ["B", "A", "A1", "A3", "A2", "A2a"]);
+
+        // Out-of-bounds indices are ignored
+        assert_eq!(todo_app.move_item_to_top(10).unwrap(), 10);
+    }
+
+    #[test]
+    fn test_notes_and_tasks_round_trip() {
+        let content =
+            "# TODO 2025-08-14\n\n* [ ] task\n  * a note\n* [x] done\n* [ ] \n* [x]\n* [link](x)\n";
+        let todo_list = TodoList::from_markdown(content).unwrap();
+
+        let kinds: Vec<(&str, bool, bool)> = todo_list
+            .items
+            .iter()
+            .map(|item| (item.text.as_str(), item.is_task, item.completed))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("task", true, false),
+                ("a note", false, false),
+                ("done", true, true),
+                ("", true, false),
+                ("", true, true),
+                ("[link](x)", false, false),
+            ]
+        );
+        assert_eq!(todo_list.items[1].indent_level, 1);
+
+        let markdown = todo_list.to_markdown();
+        assert!(markdown.contains("\n  * a note\n"));
+        assert!(markdown.contains("\n* [link](x)\n"));
+        let reparsed = TodoList::from_markdown(&markdown).unwrap();
+        assert!(!reparsed.items[1].is_task);
+        assert_eq!(reparsed.to_markdown(), markdown);
+    }
+
+    #[test]
+    fn test_toggle_note() {
+        let (_dir, mut todo_app) = sink_test_app(&[("task", true, 0)]);
+        todo_app
+            .todo_list
+            .items
+            .push(TodoItem::note("note".to_string(), 0));
+        todo_app.config.sink_completed = false;
+        // Notes don't keep the list from being all done
+        assert!(todo_app.todo_list.all_completed());
+
+        assert!(!todo_app.toggle_item_completed(1).unwrap());
+        assert!(!todo_app.todo_list.items[1].is_task);
+
+        todo_app.config.promote_notes_on_toggle = true;
+        assert!(!todo_app.toggle_item_completed(1).unwrap());
+        assert!(todo_app.todo_list.items[1].is_task);
+        assert!(!todo_app.todo_list.all_completed());
+        assert!(todo_app.toggle_item_completed(1).unwrap());
+    }
+
+    #[test]
+    fn test_parse_markdown_tab_indented() {
+        let content = "# TODO 2025-08-14\n\n* [ ] parent\n\t* [ ] child\n\t\t* [x] grandchild\n";
+        let format = MarkdownFormat {
+            indent_width: 4,
+            tab_width: 4,
+            ..MarkdownFormat::default()
+        };
+        let todo_list = TodoList::from_markdown_with(content, format).unwrap();
+
+        assert_eq!(todo_list.items[0].indent_level, 0);
+        assert_eq!(todo_list.items[1].indent_level, 1);
+        assert_eq!(todo_list.items[2].indent_level, 2);
+        assert_eq!(todo_list.items[2].text, "grandchild");
+        assert!(todo_list.items[2].completed);
+    }
+
+    #[test]
+    fn test_parse_markdown_four_space_indented() {
+        let content =
+            "# TODO 2025-08-14\n\n* [ ] parent\n    * [ ] child\n        * [ ] grandchild\n";
+        let format = MarkdownFormat {
+            indent_width: 4,
+            ..MarkdownFormat::default()
+        };
+        let todo_list = TodoList::from_markdown_with(content, format).unwrap();
+
+        assert_eq!(todo_list.items[1].indent_level, 1);
+        assert_eq!(todo_list.items[2].indent_level, 2);
+
+        // Writing keeps the configured width
+        assert_eq!(todo_list.to_markdown(), content);
+    }
+
+    #[test]
+    fn test_parse_markdown_tabs_with_default_width() {
+        // A tab counts as four spaces, i.e. two levels at the default width of two
+        let content = "# TODO 2025-08-14\n\n* [ ] parent\n\t* [ ] child\n";
+        let todo_list = TodoList::from_markdown(content).unwrap();
+
+        assert_eq!(todo_list.items[1].indent_level, 2);
+        assert_eq!(
+            todo_list.to_markdown(),
+            "# TODO 2025-08-14\n\n* [ ] parent\n    * [ ] child\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_markdown_caps_deep_indentation() {
+        let content = format!(
+            "# TODO 2025-08-14\n\n* [ ] parent\n{}* [ ] deep\n",
+            " ".repeat(40)
+        );
+        let format = MarkdownFormat {
+            max_parsed_indent: 8,
+            ..MarkdownFormat::default()
+        };
+        let todo_list = TodoList::from_markdown_with(&content, format).unwrap();
+
+        assert_eq!(todo_list.items[1].indent_level, 8);
+        assert_eq!(
+            todo_list.warnings,
+            vec!["line 4: indentation level 20 capped at 8"]
+        );
+        // The default cap is 16 levels
+        let todo_list = TodoList::from_markdown(&content).unwrap();
+        assert_eq!(todo_list.items[1].indent_level, 16);
+    }
+
+    #[test]
+    fn test_parse_markdown_done_markers() {
+        let content = "# TODO 2025-08-14\n\n* [X] upper\n* [✓] check\n* [-] open\n* [?] note\n";
+        let todo_list = TodoList::from_markdown(content).unwrap();
+        // `X` is done by default, other marks make notes
+        assert!(todo_list.items[0].completed && todo_list.items[0].is_task);
+        assert_eq!(todo_list.items[0].text, "upper");
+        assert!(!todo_list.items[1].is_task);
+        assert_eq!(todo_list.items[1].text, "[✓] check");
+
+        let format = MarkdownFormat {
+            done_markers: vec!["x".to_string(), "X".to_string(), "✓".to_string()],
+            open_markers: vec![" ".to_string(), "-".to_string()],
+            ..MarkdownFormat::default()
+        };
+        let todo_list = TodoList::from_markdown_with(content, format).unwrap();
+        let states: Vec<_> = todo_list
+            .items
+            .iter()
+            .map(|item| (item.is_task, item.completed, item.text.as_str()))
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                (true, true, "upper"),
+                (true, true, "check"),
+                (true, false, "open"),
+                (false, false, "[?] note"),
+            ]
+        );
+        // Saved with the first marker of each kind
+        assert_eq!(
+            todo_list.to_markdown(),
+            "# TODO 2025-08-14\n\n* [x] upper\n* [x] check\n* [ ] open\n* [?] note\n"
+        );
+    }
+
+    #[test]
+    fn test_canonical_checkbox_markers() {
+        let format = MarkdownFormat {
+            done_markers: vec!["X".to_string(), "x".to_string()],
+            ..MarkdownFormat::default()
+        };
+        let todo_list =
+            TodoList::from_markdown_with("# TODO 2025-08-14\n\n* [x] a\n* [ ]\n", format).unwrap();
+        assert_eq!(
+            todo_list.to_markdown(),
+            "# TODO 2025-08-14\n\n* [X] a\n* [ ] \n"
+        );
+        // Without markers the usual ones are written
+        let format = MarkdownFormat {
+            done_markers: Vec::new(),
+            open_markers: Vec::new(),
+            ..MarkdownFormat::default()
+        };
+        assert_eq!(format.checkbox(true), "[x]");
+        assert_eq!(format.checkbox(false), "[ ]");
+    }
+
+    #[test]
+    fn test_parse_markdown_uneven_indentation() {
+        let content = "# TODO 2025-08-14\n\n* [ ] a\n * [ ] one\n  * [ ] two\n   * [ ] three\n";
+        let levels = |todo_list: &TodoList| -> Vec<usize> {
+            todo_list
+                .items
+                .iter()
+                .map(|item| item.indent_level)
+                .collect()
+        };
+
+        let todo_list = TodoList::from_markdown(content).unwrap();
+        assert_eq!(levels(&todo_list), [0, 0, 1, 1]);
+        assert_eq!(
+            todo_list.warnings,
+            vec![
+                "line 4: indentation of 1 space isn't a multiple of 2, read as level 0",
+                "line 6: indentation of 3 spaces isn't a multiple of 2, read as level 1",
+            ]
+        );
+
+        let format = MarkdownFormat {
+            indent_rounding: IndentRounding::Nearest,
+            ..MarkdownFormat::default()
+        };
+        let todo_list = TodoList::from_markdown_with(content, format).unwrap();
+        assert_eq!(levels(&todo_list), [0, 1, 1, 2]);
+        assert_eq!(todo_list.warnings.len(), 2);
+        assert!(todo_list.warnings[1].ends_with("read as level 2"));
+    }
+
+    #[test]
+    fn test_indent_respects_max_indent() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let config_dir = tempfile::tempdir().unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("Item".to_string(), false, 0));
+
+        let config = Config {
+            max_indent: Some(1),
+            ..Config::default()
+        };
+        let mut todo_app =
+            TodoApp::new(config_dir.path().to_path_buf(), todo_list).with_config(config);
+
+        todo_app.indent_item_right(0).unwrap();
+        assert_eq!(todo_app.todo_list.items[0].indent_level, 1);
+        todo_app.indent_item_right(0).unwrap();
+        assert_eq!(todo_app.todo_list.items[0].indent_level, 1);
+        todo_app.indent_item_left(0).unwrap();
+        assert_eq!(todo_app.todo_list.items[0].indent_level, 0);
+    }
+
+    #[test]
+    fn test_unindent_subitems() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let levels = |todo_app: &TodoApp| -> Vec<usize> {
+            todo_app
+                .todo_list
+                .items
+                .iter()
+                .map(|item| item.indent_level)
+                .collect()
+        };
+        let app_with = |unindent_subitems| {
+            let mut todo_list = TodoList::new(Local::now().date_naive());
+            for (text, level) in [("A", 0), ("B", 1), ("C", 2), ("D", 3), ("E", 1)] {
+                todo_list
+                    .items
+                    .push(TodoItem::new(text.to_string(), false, level));
+            }
+            let config = Config {
+                unindent_subitems,
+                ..Config::default()
+            };
+            TodoApp::new(config_dir.path().to_path_buf(), todo_list).with_config(config)
+        };
+
+        // The subitems of B skip a level below it
+        let mut todo_app = app_with(UnindentSubitems::Keep);
+        todo_app.indent_item_left(1).unwrap();
+        assert_eq!(levels(&todo_app), [0, 0, 2, 3, 1]);
+
+        // They stay nested the same way below B
+        let mut todo_app = app_with(UnindentSubitems::Move);
+        todo_app.indent_item_left(1).unwrap();
+        assert_eq!(levels(&todo_app), [0, 0, 1, 2, 1]);
+        todo_app.indent_item_left(1).unwrap();
+        assert_eq!(levels(&todo_app), [0, 0, 1, 2, 1]);
+
+        let mut todo_app = app_with(UnindentSubitems::Refuse);
+        assert!(todo_app.indent_item_left(1).is_err());
+        assert_eq!(levels(&todo_app), [0, 1, 2, 3, 1]);
+        todo_app.indent_item_left(4).unwrap();
+        todo_app.indent_item_left(3).unwrap();
+        assert_eq!(levels(&todo_app), [0, 1, 2, 2, 0]);
+    }
+
+    #[test]
+    fn test_child_count() {
+        let mut todo_list = TodoList::new(NaiveDate::from_ymd_opt(2025, 8, 14).unwrap());
+        let levels = [
+            ("A", 0),
+            ("B", 1),
+            ("C", 2),
+            ("D", 1),
+            ("E", 3),
+            ("F", 2),
+            ("G", 0),
+        ];
+        for (text, level) in levels {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), false, level));
+        }
+        // B and D; C is below B, E and F below D
+        assert_eq!(todo_list.child_count(0), 2);
+        assert_eq!(todo_list.child_count(1), 1);
+        // E skips a level but is still directly below D, and so is F
+        assert_eq!(todo_list.child_count(3), 2);
+        assert_eq!(todo_list.child_count(4), 0);
+        assert_eq!(todo_list.child_count(6), 0);
+    }
+
+    fn reflowed(levels: &[usize]) -> (Vec<usize>, usize) {
+        let mut todo_list = TodoList::new(NaiveDate::from_ymd_opt(2025, 8, 14).unwrap());
+        for (i, &level) in levels.iter().enumerate() {
+            todo_list
+                .items
+                .push(TodoItem::new(format!("item {}", i), false, level));
+        }
+        let changed = todo_list.reflow_indents();
+        let levels = todo_list
+            .items
+            .iter()
+            .map(|item| item.indent_level)
+            .collect();
+        (levels, changed)
+    }
+
+    #[test]
+    fn test_reflow_indents() {
+        // Well-formed trees stay the same
+        assert_eq!(reflowed(&[]), (vec![], 0));
+        assert_eq!(reflowed(&[0, 1, 2, 1, 0]), (vec![0, 1, 2, 1, 0], 0));
+        // The first item can't be below anything
+        assert_eq!(reflowed(&[2, 3, 2]), (vec![0, 1, 0], 3));
+        // A skipped level is closed and deeper items move up with it
+        assert_eq!(reflowed(&[0, 2, 3, 2]), (vec![0, 1, 2, 1], 3));
+        assert_eq!(reflowed(&[0, 3, 5, 0]), (vec![0, 1, 2, 0], 2));
+        // Siblings stay siblings even if the gap above them closes
+        assert_eq!(reflowed(&[0, 3, 3, 3]), (vec![0, 1, 1, 1], 3));
+        // An item between two deeper ones ends up below the same parent
+        assert_eq!(reflowed(&[0, 3, 2, 3]), (vec![0, 1, 1, 2], 3));
+        assert_eq!(reflowed(&[0, 1, 4, 2]), (vec![0, 1, 2, 2], 1));
+    }
+
+    #[test]
+    fn test_reflow_indents_is_idempotent() {
+        for levels in [
+            vec![3, 0, 4, 4, 1, 7, 2],
+            vec![1, 1, 5, 3, 3, 6],
+            vec![0, 2, 4, 6, 4, 2, 0],
+        ] {
+            let (once, changed) = reflowed(&levels);
+            assert!(changed > 0);
+            for pair in once.windows(2) {
+                assert!(pair[1] <= pair[0] + 1, "{:?}", once);
+            }
+            assert_eq!(once[0], 0);
+            assert_eq!(reflowed(&once), (once.clone(), 0));
+        }
+    }
+
+    #[test]
+    fn test_title_comment_round_trip() {
+        let content = "# TODO 2025-08-14\n<!-- title: Work -->\n\n* [ ] write report\n";
+        let todo_list = TodoList::from_markdown(content).unwrap();
+
+        assert_eq!(todo_list.title.as_deref(), Some("Work"));
+        assert_eq!(todo_list.items.len(), 1);
+        assert_eq!(todo_list.to_markdown(), content);
+    }
+
+    #[test]
+    fn test_day_note_round_trip() {
+        let content = "# TODO 2025-08-14\n<!-- title: Work -->\n> focus: finish report\n\n* [ ] write report\n";
+        let todo_list = TodoList::from_markdown(content).unwrap();
+
+        assert_eq!(todo_list.note.as_deref(), Some("focus: finish report"));
+        assert_eq!(todo_list.items.len(), 1);
+        assert_eq!(todo_list.items[0].text, "write report");
+        assert_eq!(todo_list.to_markdown(), content);
+    }
+
+    #[test]
+    fn test_day_note_parsing() {
+        // Quote lines are joined, and only count before the first item
+        let content = "# TODO 2025-08-14\n> focus:\n>   report\n>\n* [ ] a\n> not a note\n";
+        let todo_list = TodoList::from_markdown(content).unwrap();
+        assert_eq!(todo_list.note.as_deref(), Some("focus: report"));
+        assert_eq!(todo_list.items.len(), 1);
+
+        let todo_list = TodoList::from_markdown("# TODO 2025-08-14\n\n* [ ] a\n").unwrap();
+        assert_eq!(todo_list.note, None);
+        assert!(!todo_list.to_markdown().contains('>'));
+    }
+
+    #[test]
+    fn test_set_note() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let todo_list = TodoList::new(Local::now().date_naive());
+        let mut todo_app = TodoApp::new(config_dir.path().to_path_buf(), todo_list);
+
+        todo_app.set_note("  focus: finish report ").unwrap();
+        let saved = fs::read_to_string(todo_app.file_path()).unwrap();
+        assert!(saved.contains("\n> focus: finish report\n"));
+
+        todo_app.set_note(" ").unwrap();
+        assert_eq!(todo_app.todo_list.note, None);
+        let saved = fs::read_to_string(todo_app.file_path()).unwrap();
+        assert!(!saved.contains('>'));
+    }
+
+    #[test]
+    fn test_display_title_falls_back_to_config_and_date() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let todo_app = TodoApp::new(PathBuf::new(), TodoList::new(date));
+        assert_eq!(todo_app.display_title(), "TODO 2025-08-14");
+
+        let config = Config {
+            title: Some("Home".to_string()),
+            ..Config::default()
+        };
+        let mut todo_app = TodoApp::new(PathBuf::new(), TodoList::new(date)).with_config(config);
+        assert_eq!(todo_app.display_title(), "Home — 2025-08-14");
+
+        // A title stored in the file wins over the configured one
+        todo_app.todo_list.title = Some("Work".to_string());
+        assert_eq!(todo_app.display_title(), "Work — 2025-08-14");
+    }
+
+    #[test]
+    fn test_relative_day_label_boundaries() {
+        let today = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let label = |days: i64| relative_day_label(today + chrono::Duration::days(days), today);
+        assert_eq!(label(0).as_deref(), Some("Today"));
+        assert_eq!(label(-1).as_deref(), Some("Yesterday"));
+        assert_eq!(label(1).as_deref(), Some("Tomorrow"));
+        assert_eq!(label(-2).as_deref(), Some("2 days ago"));
+        assert_eq!(label(-6).as_deref(), Some("6 days ago"));
+        assert_eq!(label(6).as_deref(), Some("in 6 days"));
+        assert_eq!(label(-7), None);
+        assert_eq!(label(7), None);
+        assert_eq!(label(-400), None);
+    }
+
+    #[test]
+    fn test_display_title_with_relative_dates() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let config = Config {
+            relative_dates: true,
+            ..Config::default()
+        };
+        let mut todo_app = TodoApp::new(PathBuf::new(), TodoList::new(date)).with_config(config);
+        assert_eq!(todo_app.display_title_at(date), "TODO Today (2025-08-14)");
+
+        let next_day = date.succ_opt().unwrap();
+        todo_app.todo_list.title = Some("Work".to_string());
+        assert_eq!(
+            todo_app.display_title_at(next_day),
+            "Work — Yesterday (2025-08-14)"
+        );
+
+        let far_away = NaiveDate::from_ymd_opt(2025, 12, 24).unwrap();
+        assert_eq!(todo_app.display_title_at(far_away), "Work — 2025-08-14");
+    }
+
+    #[test]
+    fn test_sort_completed_last_keeps_subtrees() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        for (text, completed, level) in [
+            ("A", true, 0),
+            ("A1", true, 1),
+            ("A2", false, 1),
+            ("B", false, 0),
+            ("C", true, 0),
+            ("D", false, 0),
+        ] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), completed, level));
+        }
+
+        let order = todo_list.sort(SortMode::CompletedLast, CompletedOrder::None);
+
+        let texts: Vec<&str> = todo_list.items.iter().map(|i| i.text.as_str()).collect();
+        assert_eq!(texts, ["B", "D", "A", "A2", "A1", "C"]);
+        assert_eq!(order, [3, 5, 0, 2, 1, 4]);
+        assert_eq!(todo_list.items[3].indent_level, 1);
+    }
+
+    #[test]
+    fn test_load_applies_configured_sort() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("TODO-2025-08-14.md"),
+            "# TODO 2025-08-14\n\n* [x] done\n* [ ] open\n",
+        )
+        .unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+
+        let todo_list = load_or_create_todo_list(dir.path(), date, &Config::default()).unwrap();
+        assert_eq!(todo_list.items[0].text, "done");
+
+        let config = Config {
+            auto_sort: SortMode::CompletedLast,
+            ..Config::default()
+        };
+        let todo_list = load_or_create_todo_list(dir.path(), date, &config).unwrap();
+        assert_eq!(todo_list.items[0].text, "open");
+        assert_eq!(todo_list.items[1].text, "done");
+    }
+
+    #[test]
+    fn test_load_tells_whether_the_day_is_new() {
+        let dir = tempfile::tempdir().unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let config = Config::default();
+
+        let (todo_list, new_day) = load_or_start_day(dir.path(), date, &config).unwrap();
+        assert!(new_day);
+        assert!(todo_list.items.is_empty());
+
+        // Carried over from an older day
+        fs::write(
+            dir.path().join("TODO-2025-08-13.md"),
+            "# TODO 2025-08-13\n\n* [ ] open\n",
+        )
+        .unwrap();
+        let (todo_list, new_day) = load_or_start_day(dir.path(), date, &config).unwrap();
+        assert!(new_day);
+        assert_eq!(todo_list.date, date);
+        // Taking the open item over to the new day counts a deferral
+        assert_eq!(todo_list.items.len(), 1);
+        assert_eq!(todo_list.items[0].text, "open def:1");
+
+        fs::write(
+            dir.path().join("TODO-2025-08-14.md"),
+            "# TODO 2025-08-14\n\n* [ ] open\n",
+        )
+        .unwrap();
+        let (_, new_day) = load_or_start_day(dir.path(), date, &config).unwrap();
+        assert!(!new_day);
+    }
+
+    #[test]
+    fn test_open_today_saves_new_day() {
+        let dir = tempfile::tempdir().unwrap();
+        // The app saves under the wall-clock date when it is dropped
+        let today = Local::now().date_naive();
+        let yesterday = today.pred_opt().unwrap();
+        fs::write(
+            dir.path().join(TodoList::new(yesterday).filename()),
+            format!("# TODO {}\n\n* [ ] open\n", yesterday),
+        )
+        .unwrap();
+        let config = Config::default();
+        let today_path = dir.path().join(TodoList::new(today).filename());
+
+        let (_, new_day) = open_today(dir.path().to_path_buf(), config.clone(), today).unwrap();
+        assert!(new_day);
+        assert_eq!(
+            fs::read_to_string(&today_path).unwrap(),
+            format!("# TODO {}\n\n* [ ] open def:1\n", today)
+        );
+
+        // Starting again the same day neither greets nor defers again
+        let (todo_app, new_day) = open_today(dir.path().to_path_buf(), config, today).unwrap();
+        assert!(!new_day);
+        assert_eq!(todo_app.todo_list.items[0].text, "open def:1");
+    }
+
+    #[test]
+    fn test_single_file_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let config = Config {
+            single_file: true,
+            ..Config::default()
+        };
+        // Dated files are left alone
+        fs::write(
+            dir.path().join("TODO-2025-08-13.md"),
+            "# TODO 2025-08-13\n\n* [ ] dated\n",
+        )
+        .unwrap();
+        let (todo_list, new_day) = load_or_start_day(dir.path(), date, &config).unwrap();
+        assert!(new_day);
+        assert!(todo_list.items.is_empty());
+
+        fs::write(
+            dir.path().join("TODO.md"),
+            "# TODO 2025-08-12\n\n* [ ] single\n",
+        )
+        .unwrap();
+        let (todo_list, new_day) = load_or_start_day(dir.path(), date, &config).unwrap();
+        assert!(new_day);
+        assert_eq!(todo_list.date, date);
+        assert_eq!(todo_list.items[0].text, "single def:1");
+
+        let mut todo_app =
+            TodoApp::new(dir.path().to_path_buf(), todo_list).with_config(config.clone());
+        assert_eq!(todo_app.file_path(), dir.path().join("TODO.md"));
+        todo_app.todo_list.items[0].text = "renamed".to_string();
+        todo_app.save_todo_list_on(date).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.path().join("TODO.md")).unwrap(),
+            "# TODO 2025-08-14\n\n* [ ] renamed\n"
+        );
+        assert_eq!(todo_files(dir.path()).len(), 1);
+        assert_eq!(
+            todo_files_for(dir.path(), &config),
+            [(date, dir.path().join("TODO.md"))]
+        );
+
+        // Saved today, so the day isn't new anymore
+        let (_, new_day) = load_or_start_day(dir.path(), date, &config).unwrap();
+        assert!(!new_day);
+    }
+
+    #[test]
+    fn test_config_dir_precedence() {
+        let xdg = tempfile::tempdir().unwrap();
+        let arg = Some(PathBuf::from("/from/arg"));
+        let env = Some(PathBuf::from("/from/env"));
+        let home = Some(PathBuf::from("/home/user"));
+        let xdg_dir = Some(xdg.path().to_path_buf());
+
+        let resolve = |arg: &Option<PathBuf>, env: &Option<PathBuf>, xdg: &Option<PathBuf>| {
+            resolve_config_dir(arg.clone(), env.clone(), xdg.clone(), home.clone()).unwrap()
+        };
+
+        assert_eq!(resolve(&arg, &env, &xdg_dir), PathBuf::from("/from/arg"));
+        assert_eq!(resolve(&None, &env, &xdg_dir), PathBuf::from("/from/env"));
+        assert_eq!(resolve(&None, &None, &xdg_dir), xdg.path());
+        assert_eq!(resolve(&None, &Some(PathBuf::new()), &xdg_dir), xdg.path());
+        // A missing XDG dir falls back to the home directory
+        let missing = Some(xdg.path().join("missing"));
+        assert_eq!(
+            resolve(&None, &None, &missing),
+            PathBuf::from("/home/user/.todui")
+        );
+    }
+
+    #[test]
+    fn test_config_dir_unresolvable() {
+        let err = resolve_config_dir(None, None, None, None).unwrap_err();
+        assert!(err.to_string().contains("TODUI_HOME"));
+        assert!(err.to_string().contains("--config-dir"));
+    }
+
+    fn indent_test_app(new_item_indent: NewItemIndent) -> (tempfile::TempDir, TodoApp) {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("A".to_string(), false, 0));
+        todo_list
+            .items
+            .push(TodoItem::new("B".to_string(), false, 1));
+        todo_list
+            .items
+            .push(TodoItem::new("C".to_string(), false, 2));
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            new_item_indent,
+            ..Config::default()
+        };
+        let todo_app = TodoApp::new(dir.path().to_path_buf(), todo_list).with_config(config);
+        (dir, todo_app)
+    }
+
+    #[test]
+    fn test_default_indent_inherits_previous() {
+        let (_dir, todo_app) = indent_test_app(NewItemIndent::Previous);
+        assert_eq!(todo_app.default_indent_for(0), 0);
+        assert_eq!(todo_app.default_indent_for(1), 0);
+        assert_eq!(todo_app.default_indent_for(2), 1);
+        assert_eq!(todo_app.default_indent_for(3), 2);
+    }
+
+    #[test]
+    fn test_default_indent_inherits_next() {
+        let (_dir, todo_app) = indent_test_app(NewItemIndent::Next);
+        assert_eq!(todo_app.default_indent_for(0), 0);
+        assert_eq!(todo_app.default_indent_for(1), 1);
+        assert_eq!(todo_app.default_indent_for(2), 2);
+        // Past the end there is no next item, so the previous one counts
+        assert_eq!(todo_app.default_indent_for(3), 2);
+    }
+
+    #[test]
+    fn test_default_indent_fixed() {
+        let (_dir, mut todo_app) = indent_test_app(NewItemIndent::Fixed);
+        assert_eq!(todo_app.default_indent_for(2), 0);
+        assert_eq!(todo_app.default_indent_for(3), 0);
+
+        todo_app.config.new_item_base_indent = 3;
+        todo_app.config.max_indent = Some(2);
+        assert_eq!(todo_app.default_indent_for(0), 2);
+    }
+
+    #[test]
+    fn test_insert_new_item_uses_policy_and_completed_default() {
+        let (_dir, mut todo_app) = indent_test_app(NewItemIndent::Next);
+        todo_app.config.new_item_completed = true;
+        todo_app.insert_new_item(1).unwrap();
+
+        let item = &todo_app.todo_list.items[1];
+        assert_eq!(item.indent_level, 1);
+        assert!(item.completed);
+        assert!(item.text.is_empty());
+
+        // Children get the same defaults
+        todo_app.config.max_indent = Some(1);
+        let at = todo_app.insert_child_item(1).unwrap();
+        let child = &todo_app.todo_list.items[at];
+        assert_eq!((at, child.indent_level), (2, 1));
+        assert!(child.completed);
+    }
+
+    fn sink_test_app(items: &[(&str, bool, usize)]) -> (tempfile::TempDir, TodoApp) {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        for (text, completed, level) in items {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), *completed, *level));
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            sink_completed: true,
+            ..Config::default()
+        };
+        let todo_app = TodoApp::new(dir.path().to_path_buf(), todo_list).with_config(config);
+        (dir, todo_app)
+    }
+
+    fn item_texts(todo_app: &TodoApp) -> Vec<&str> {
+        todo_app
+            .todo_list
+            .items
+            .iter()
+            .map(|i| i.text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_sink_parent_with_children() {
+        let (_dir, mut todo_app) = sink_test_app(&[
+            ("A", false, 0),
+            ("A1", true, 1),
+            ("A2", false, 1),
+            ("B", false, 0),
+            ("C", true, 0),
+        ]);
+
+        todo_app.toggle_item_completed(0).unwrap();
+        assert_eq!(todo_app.sink_item(0).unwrap(), 2);
+        assert_eq!(item_texts(&todo_app), ["B", "C", "A", "A1", "A2"]);
+        assert_eq!(todo_app.todo_list.items[3].indent_level, 1);
+    }
+
+    #[test]
+    fn test_sink_child_stays_within_parent() {
+        let (_dir, mut todo_app) = sink_test_app(&[
+            ("A", false, 0),
+            ("A1", false, 1),
+            ("A1a", false, 2),
+            ("A2", false, 1),
+            ("B", false, 0),
+        ]);
+
+        todo_app.toggle_item_completed(1).unwrap();
+        assert_eq!(todo_app.sink_item(1).unwrap(), 2);
+        assert_eq!(item_texts(&todo_app), ["A", "A2", "A1", "A1a", "B"]);
+    }
+
+    #[test]
+    fn test_uncompleted_item_floats_back() {
+        let (_dir, mut todo_app) = sink_test_app(&[
+            ("A", false, 0),
+            ("B", true, 0),
+            ("C", true, 0),
+            ("C1", false, 1),
+        ]);
+
+        todo_app.toggle_item_completed(2).unwrap();
+        assert_eq!(todo_app.sink_item(2).unwrap(), 1);
+        assert_eq!(item_texts(&todo_app), ["A", "C", "C1", "B"]);
+
+        // Without completed siblings above, the item stays where it is
+        todo_app.toggle_item_completed(0).unwrap();
+        todo_app.toggle_item_completed(0).unwrap();
+        assert_eq!(todo_app.sink_item(0).unwrap(), 0);
+
+        // Disabled by default
+        todo_app.config.sink_completed = false;
+        todo_app.toggle_item_completed(0).unwrap();
+        assert_eq!(todo_app.sink_item(0).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_snapshot_reads_during_saves_are_consistent() {
+        let dir = tempfile::tempdir().unwrap();
+        let today = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let path = dir.path().join(TodoList::new(today).filename());
+
+        let writer_dir = dir.path().to_path_buf();
+        let writer = std::thread::spawn(move || {
+            let mut todo_app = TodoApp::new(writer_dir, TodoList::new(today));
+            for round in 0..200 {
+                // Alternate between a short and a long list
+                let count = if round % 2 == 0 { 10 } else { 500 };
+                todo_app.todo_list.items = (0..count)
+                    .map(|_| TodoItem::new(format!("round {}", round), false, 0))
+                    .collect();
+                todo_app.save_todo_list_on(today).unwrap();
+            }
+        });
+
+        let format = MarkdownFormat::default();
+        while !writer.is_finished() {
+            if let Ok(todo_list) = read_todo_list(&path, &format) {
+                let count = todo_list.items.len();
+                assert!(count == 10 || count == 500, "partial read: {} items", count);
+                assert!(
+                    todo_list
+                        .items
+                        .iter()
+                        .all(|i| i.text == todo_list.items[0].text)
+                );
+            }
+        }
+        writer.join().unwrap();
+
+        // No temporary files are left behind or mistaken for todo files.
+        // Dropping the app saved once more, under the current date.
+        let names: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert!(
+            names.iter().all(|name| !name.ends_with(".tmp")),
+            "{:?}",
+            names
+        );
+        assert_eq!(todo_files(dir.path()).len(), names.len());
+    }
+
+    fn sorted_texts(items: &[(&str, bool)], completed_order: CompletedOrder) -> Vec<String> {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        for (text, completed) in items {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), *completed, 0));
+        }
+        todo_list.sort(SortMode::CompletedLast, completed_order);
+        todo_list.items.into_iter().map(|i| i.text).collect()
+    }
+
+    #[test]
+    fn test_sort_completed_by_text() {
+        let items = [
+            ("zebra", true),
+            ("open b", false),
+            ("Apple", true),
+            ("open a", false),
+            ("mango", true),
+        ];
+        assert_eq!(
+            sorted_texts(&items, CompletedOrder::Text),
+            ["open b", "open a", "Apple", "mango", "zebra"]
+        );
+        assert_eq!(
+            sorted_texts(&items, CompletedOrder::None),
+            ["open b", "open a", "zebra", "Apple", "mango"]
+        );
+    }
+
+    #[test]
+    fn test_sort_completed_by_done_date() {
+        let items = [
+            ("b done:2025-08-12", true),
+            ("no date b", true),
+            ("open", false),
+            ("a done:2025-08-10", true),
+            ("no date a", true),
+            ("bad done:yesterday", true),
+        ];
+        assert_eq!(
+            sorted_texts(&items, CompletedOrder::DoneDate),
+            [
+                "open",
+                "a done:2025-08-10",
+                "b done:2025-08-12",
+                "bad done:yesterday",
+                "no date a",
+                "no date b",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_join_with_next() {
+        let (_dir, mut todo_app) = sink_test_app(&[
+            ("Buy", false, 0),
+            ("milk", true, 1),
+            ("Call", false, 0),
+            ("mom", false, 0),
+            ("mom's number", false, 1),
+        ]);
+
+        assert!(todo_app.join_with_next(0).unwrap());
+        assert_eq!(
+            item_texts(&todo_app),
+            ["Buy milk", "Call", "mom", "mom's number"]
+        );
+        assert!(!todo_app.todo_list.items[0].completed);
+        assert_eq!(todo_app.todo_list.items[0].indent_level, 0);
+
+        // The next item has a subitem, so nothing happens
+        assert!(!todo_app.join_with_next(1).unwrap());
+        assert_eq!(todo_app.todo_list.items.len(), 4);
+
+        // Nothing to join with after the last item
+        assert!(!todo_app.join_with_next(3).unwrap());
+    }
+
+    // PID of a process that has already exited
+    #[cfg(unix)]
+    fn dead_pid() -> u32 {
+        let mut child = process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        pid
+    }
+
+    // Telling live and dead processes apart needs `process_alive` on Unix
+    #[test]
+    #[cfg(unix)]
+    fn test_lock_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock_path = dir.path().join("lockfile");
+
+        fs::write(&lock_path, format!("{}\n", process::id())).unwrap();
+        assert_eq!(lock_state(&lock_path), LockState::Held(process::id()));
+
+        let pid = dead_pid();
+        fs::write(&lock_path, format!("{}\n", pid)).unwrap();
+        assert_eq!(lock_state(&lock_path), LockState::Stale(pid));
+
+        fs::write(&lock_path, "").unwrap();
+        assert_eq!(lock_state(&lock_path), LockState::Unknown);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_acquire_lock_with_stale_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock_path = dir.path().join("lockfile");
+        let pid = dead_pid();
+        fs::write(&lock_path, format!("{}\n", pid)).unwrap();
+
+        // Declining keeps the lock file and fails
+        let mut asked = None;
+        let result = acquire_lock(dir.path(), false, |pid| {
+            asked = Some(pid);
+            Ok(false)
+        });
+        assert!(result.is_err());
+        assert_eq!(asked, Some(pid));
+        assert!(
+            fs::read_to_string(&lock_path)
+                .unwrap()
+                .contains(&pid.to_string())
+        );
+
+        // Agreeing replaces it with our own
+        acquire_lock(dir.path(), false, |_| Ok(true)).unwrap();
+        assert_eq!(lock_state(&lock_path), LockState::Held(process::id()));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_acquire_lock_force_and_live_locks() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock_path = dir.path().join("lockfile");
+
+        // --force doesn't ask
+        fs::write(&lock_path, format!("{}\n", dead_pid())).unwrap();
+        acquire_lock(dir.path(), true, |_| panic!("should not ask")).unwrap();
+
+        // A running instance is never asked about or removed
+        let result = acquire_lock(dir.path(), true, |_| panic!("should not ask"));
+        assert!(result.is_err());
+        assert!(lock_path.exists());
+    }
+
+    #[test]
+    fn test_dedup() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let list = || {
+            let mut todo_list = TodoList::new(date);
+            for (text, completed, level) in [
+                ("call mom", false, 0),
+                ("buy milk", false, 0),
+                ("  call mom ", false, 0),
+                ("call mom", true, 0),
+                ("parent", false, 0),
+                ("call mom", false, 1),
+                ("buy milk", false, 0),
+                ("with subitem", false, 1),
+            ] {
+                todo_list
+                    .items
+                    .push(TodoItem::new(text.to_string(), completed, level));
+            }
+            todo_list
+                .items
+                .push(TodoItem::note("buy milk".to_string(), 0));
+            todo_list
+        };
+
+        let mut todo_list = list();
+        assert_eq!(todo_list.dedup(false), 2);
+        assert!(!todo_list.items[0].completed);
+
+        // Nested copies, copies with subitems and notes stay
+        let mut todo_list = list();
+        assert_eq!(todo_list.dedup(true), 2);
+        let items: Vec<(&str, bool, usize, bool)> = todo_list
+            .items
+            .iter()
+            .map(|item| {
+                (
+                    item.text.as_str(),
+                    item.completed,
+                    item.indent_level,
+                    item.is_task,
+                )
+            })
+            .collect();
+        assert_eq!(
+            items,
+            [
+                ("call mom", true, 0, true),
+                ("buy milk", false, 0, true),
+                ("parent", false, 0, true),
+                ("call mom", false, 1, true),
+                ("buy milk", false, 0, true),
+                ("with subitem", false, 1, true),
+                ("buy milk", false, 0, false),
+            ]
+        );
+        assert_eq!(todo_list.dedup(true), 0);
+    }
+
+    #[test]
+    fn test_dedup_keeps_copies_under_other_parents() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        for (text, level) in [
+            ("groceries", 0),
+            ("milk", 1),
+            ("pharmacy", 0),
+            ("milk", 1),
+            ("milk", 1),
+        ] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), false, level));
+        }
+
+        assert_eq!(todo_list.dedup(true), 1);
+        let texts: Vec<&str> = todo_list.items.iter().map(|i| i.text.as_str()).collect();
+        assert_eq!(texts, ["groceries", "milk", "pharmacy", "milk"]);
+    }
+
+    fn overdue_fixture_dir() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (date, items) in [
+            ("2025-08-01", "* [ ] too old due:2025-08-01\n"),
+            (
+                "2025-08-10",
+                "* [ ] pay rent due:2025-08-12\n  * [x] transfer\n* [ ] later due:2025-08-20\n\
+                 * [x] done due:2025-08-01\n* [ ] no date\n",
+            ),
+            (
+                "2025-08-12",
+                "* [ ] pay rent due:2025-08-12 def:1\n* [ ] call bob due:2025-08-14\n",
+            ),
+            ("2025-08-14", "* [ ] existing\n"),
+        ] {
+            fs::write(
+                dir.path().join(format!("TODO-{}.md", date)),
+                format!("# TODO {}\n\n{}", date, items),
+            )
+            .unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_pull_overdue_items() {
+        let dir = overdue_fixture_dir();
+        let today = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut config = Config::default();
+        let load = |config: &Config| load_or_create_todo_list(dir.path(), today, config).unwrap();
+
+        // Opt-in
+        let mut todo_list = load(&config);
+        assert_eq!(
+            pull_overdue_items(dir.path(), &config, today, &mut todo_list).unwrap(),
+            0
+        );
+        assert_eq!(todo_list.items.len(), 1);
+
+        config.pull_overdue = true;
+        let mut todo_list = load(&config);
+        assert_eq!(
+            pull_overdue_items(dir.path(), &config, today, &mut todo_list).unwrap(),
+            2
+        );
+        let items: Vec<(&str, usize, bool)> = todo_list
+            .items
+            .iter()
+            .map(|item| (item.text.as_str(), item.indent_level, item.carried))
+            .collect();
+        assert_eq!(
+            items,
+            [
+                ("existing", 0, false),
+                ("pay rent due:2025-08-12", 0, true),
+                ("transfer", 1, false),
+                ("call bob due:2025-08-14", 0, true),
+            ]
+        );
+
+        // Saved right away, and the old files stay as they were
+        let saved = read_todo_list(&dir.path().join("TODO-2025-08-14.md"), &config.format);
+        assert_eq!(saved.unwrap().items.len(), 4);
+        let old = read_todo_list(&dir.path().join("TODO-2025-08-10.md"), &config.format);
+        assert_eq!(old.unwrap().items.len(), 5);
+
+        // Pulling again doesn't duplicate anything
+        let mut todo_list = load(&config);
+        assert_eq!(
+            pull_overdue_items(dir.path(), &config, today, &mut todo_list).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_pull_overdue_items_with_huge_range() {
+        let dir = overdue_fixture_dir();
+        let today = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let config = Config {
+            pull_overdue: true,
+            pull_overdue_days: u32::MAX,
+            ..Config::default()
+        };
+        let mut todo_list = load_or_create_todo_list(dir.path(), today, &config).unwrap();
+        // No limit, so even the oldest file is searched
+        assert_eq!(
+            pull_overdue_items(dir.path(), &config, today, &mut todo_list).unwrap(),
+            3
+        );
+        assert_eq!(todo_list.items[1].text, "too old due:2025-08-01");
+    }
+
+    #[test]
+    fn test_pull_overdue_items_can_remove_them() {
+        let dir = overdue_fixture_dir();
+        let today = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let config = Config {
+            pull_overdue: true,
+            pull_overdue_remove: true,
+            ..Config::default()
+        };
+        let mut todo_list = load_or_create_todo_list(dir.path(), today, &config).unwrap();
+        assert_eq!(
+            pull_overdue_items(dir.path(), &config, today, &mut todo_list).unwrap(),
+            2
+        );
+
+        let texts = |date: &str| -> Vec<String> {
+            let path = dir.path().join(format!("TODO-{}.md", date));
+            let todo_list = read_todo_list(&path, &config.format).unwrap();
+            todo_list.items.into_iter().map(|item| item.text).collect()
+        };
+        assert_eq!(
+            texts("2025-08-10"),
+            ["later due:2025-08-20", "done due:2025-08-01", "no date"]
+        );
+        // Already pulled from the older file, so this copy stays
+        assert_eq!(texts("2025-08-12"), ["pay rent due:2025-08-12 def:1"]);
+        assert_eq!(texts("2025-08-01"), ["too old due:2025-08-01"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_save_keeps_symlinks() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempfile::tempdir().unwrap();
+        let synced = dir.path().join("synced");
+        fs::create_dir(&synced).unwrap();
+        let target = synced.join("TODO-2025-08-14.md");
+        fs::write(&target, "# TODO 2025-08-14\n\n* [ ] old\n").unwrap();
+
+        // A link to a relative link to the file
+        let todos = dir.path().join("todos");
+        fs::create_dir(&todos).unwrap();
+        let relative = todos.join("relative.md");
+        symlink("../synced/TODO-2025-08-14.md", &relative).unwrap();
+        let link = todos.join("TODO-2025-08-14.md");
+        symlink(&relative, &link).unwrap();
+
+        write_atomically(&link, "# TODO 2025-08-14\n\n* [ ] new\n").unwrap();
+        assert!(
+            fs::symlink_metadata(&link)
+                .unwrap()
+                .file_type()
+                .is_symlink()
+        );
+        assert!(
+            fs::symlink_metadata(&relative)
+                .unwrap()
+                .file_type()
+                .is_symlink()
+        );
+        assert_eq!(
+            fs::read_to_string(&target).unwrap(),
+            "# TODO 2025-08-14\n\n* [ ] new\n"
+        );
+        // The temporary file went next to the target
+        assert_eq!(fs::read_dir(&todos).unwrap().count(), 2);
+
+        // Dangling links get their target created
+        let dangling = todos.join("TODO-2025-08-15.md");
+        symlink(synced.join("TODO-2025-08-15.md"), &dangling).unwrap();
+        write_atomically(&dangling, "x").unwrap();
+        assert!(
+            fs::symlink_metadata(&dangling)
+                .unwrap()
+                .file_type()
+                .is_symlink()
+        );
+        assert_eq!(
+            fs::read_to_string(synced.join("TODO-2025-08-15.md")).unwrap(),
+            "x"
+        );
+
+        // Loops are an error rather than hanging
+        let looping = todos.join("loop.md");
+        symlink(&looping, &looping).unwrap();
+        assert!(write_atomically(&looping, "x").is_err());
+    }
+
+    #[test]
+    fn test_carry_over_incomplete() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        for (text, completed, level) in [
+            ("done", true, 0),
+            ("done parent", true, 0),
+            ("open child", false, 1),
+            ("done child", true, 1),
+            ("open", false, 0),
+        ] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), completed, level));
+        }
+
+        let carried = todo_list.carry_over_incomplete();
+        let texts: Vec<&str> = carried.iter().map(|i| i.text.as_str()).collect();
+        assert_eq!(texts, ["done parent", "open child def:1", "open def:1"]);
+        // Completed parents only come along for the tree, so they aren't marked
+        let marked: Vec<bool> = carried.iter().map(|i| i.carried).collect();
+        assert_eq!(marked, [false, true, true]);
+    }
+
+    #[test]
+    fn test_carry_over_counts_deferrals() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        for (text, completed, level) in [
+            ("done parent", true, 0),
+            ("open child", false, 1),
+            ("call def:2 mom", false, 0),
+        ] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), completed, level));
+        }
+
+        for _ in 0..3 {
+            todo_list.items = todo_list.carry_over_incomplete();
+            // The counter survives being written and read back
+            todo_list = TodoList::from_markdown(&todo_list.to_markdown()).unwrap();
+        }
+        let texts: Vec<&str> = todo_list.items.iter().map(|i| i.text.as_str()).collect();
+        assert_eq!(texts, ["done parent", "open child def:3", "call def:5 mom"]);
+        let counts: Vec<u32> = todo_list.items.iter().map(|i| i.deferred_count()).collect();
+        assert_eq!(counts, [0, 3, 5]);
+    }
+
+    #[test]
+    fn test_set_deferred_count() {
+        let mut item = TodoItem::new("a def:1 b".to_string(), false, 0);
+        item.set_deferred_count(4);
+        assert_eq!(item.text, "a def:4 b");
+        item.set_deferred_count(0);
+        assert_eq!(item.text, "a b");
+
+        // Other `def:` words aren't counters
+        let mut item = TodoItem::new("def:ault".to_string(), false, 0);
+        assert_eq!(item.deferred_count(), 0);
+        item.set_deferred_count(1);
+        assert_eq!(item.text, "def:ault def:1");
+
+        let mut item = TodoItem::new(String::new(), false, 0);
+        item.set_deferred_count(1);
+        assert_eq!(item.text, "def:1");
+
+        // A hand-edited huge count stays at the maximum
+        let mut item = TodoItem::new(format!("x def:{}", u32::MAX), false, 0);
+        item.defer();
+        assert_eq!(item.deferred_count(), u32::MAX);
+    }
+
+    #[test]
+    fn test_set_due_date() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 20);
+        let mut item = TodoItem::new("report due:2025-08-18 draft".to_string(), false, 0);
+        item.set_due_date(date);
+        assert_eq!(item.text, "report due:2025-08-20 draft");
+        assert_eq!(item.due_date(), date);
+        item.set_due_date(None);
+        assert_eq!(item.text, "report draft");
+
+        // Other `due:` words aren't dates
+        let mut item = TodoItem::new("due:soon".to_string(), false, 0);
+        item.set_due_date(date);
+        assert_eq!(item.text, "due:soon due:2025-08-20");
+    }
+
+    #[test]
+    fn test_todo_item_builder() {
+        let item = TodoItem::builder().text("plain").build();
+        assert_eq!(item.to_markdown_line(), "* [ ] plain");
+        assert!(!item.carried);
+
+        let item = TodoItem::builder()
+            .text("report")
+            .completed(true)
+            .indent(2)
+            .build();
+        assert_eq!(item.to_markdown_line(), "    * [x] report");
+
+        let item = TodoItem::builder()
+            .text("room 4")
+            .task(false)
+            .indent(1)
+            .build();
+        assert_eq!(item.to_markdown_line(), "  * room 4");
+
+        // A due date becomes a token, replacing one in the text
+        let due = NaiveDate::from_ymd_opt(2025, 8, 20);
+        let item = TodoItem::builder()
+            .text("slides due:2025-08-18")
+            .due(due)
+            .build();
+        assert_eq!(item.text, "slides due:2025-08-20");
+        assert_eq!(item.due_date(), due);
+        let item = TodoItem::builder().text("slides").due(None).build();
+        assert_eq!(item.text, "slides");
+    }
+
+    #[test]
+    fn test_escaped_tokens_are_plain_text() {
+        let text = "email \\def:2 draft \\done:2025-08-01 def:1 done:2025-08-12";
+        let mut item = TodoItem::new(text.to_string(), true, 0);
+        assert_eq!(item.deferred_count(), 1);
+        assert_eq!(item.done_date(), NaiveDate::from_ymd_opt(2025, 8, 12));
+
+        item.set_deferred_count(0);
+        assert_eq!(
+            item.text,
+            "email \\def:2 draft \\done:2025-08-01 done:2025-08-12"
+        );
+        item.set_deferred_count(3);
+        assert_eq!(
+            item.text,
+            "email \\def:2 draft \\done:2025-08-01 done:2025-08-12 def:3"
+        );
+
+        // Only escaped tokens: nothing to find, and the escapes round trip
+        let item = TodoItem::new("email \\def:2 draft".to_string(), false, 0);
+        assert_eq!(item.deferred_count(), 0);
+        let mut todo_list = TodoList::new(NaiveDate::from_ymd_opt(2025, 8, 14).unwrap());
+        todo_list.items.push(item);
+        let parsed = TodoList::from_markdown(&todo_list.to_markdown()).unwrap();
+        assert_eq!(parsed.items[0].text, "email \\def:2 draft");
+        assert_eq!(parsed.items[0].deferred_count(), 0);
+    }
+
+    #[test]
+    fn test_reset_deferred_count() {
+        let (_dir, mut todo_app) = sink_test_app(&[("a def:3", false, 0), ("b def:2", false, 0)]);
+        todo_app.config.sink_completed = false;
+
+        // Kept by default
+        todo_app.toggle_item_completed(0).unwrap();
+        todo_app
+            .update_item_text(1, "bb def:2".to_string())
+            .unwrap();
+        assert_eq!(item_texts(&todo_app), ["a def:3", "bb def:2"]);
+
+        todo_app.config.reset_deferred_on_complete = true;
+        todo_app.config.reset_deferred_on_edit = true;
+        todo_app.toggle_item_completed(0).unwrap();
+        assert_eq!(item_texts(&todo_app), ["a def:3", "bb def:2"]);
+        todo_app.toggle_item_completed(0).unwrap();
+        // Confirming an unchanged text isn't an edit
+        todo_app
+            .update_item_text(1, "bb def:2".to_string())
+            .unwrap();
+        assert_eq!(item_texts(&todo_app), ["a", "bb def:2"]);
+        todo_app.update_item_text(1, "b def:2".to_string()).unwrap();
+        assert_eq!(item_texts(&todo_app), ["a", "b"]);
+    }
+
+    #[test]
+    fn test_roll_over() {
+        let (dir, mut todo_app) = sink_test_app(&[("done", true, 0), ("open", false, 0)]);
+        let day = todo_app.todo_list.date;
+        let next_day = day.succ_opt().unwrap();
+
+        // Off by default
+        todo_app.config.rollover = RolloverMode::Off;
+        assert!(!todo_app.roll_over(next_day).unwrap());
+
+        todo_app.config.rollover = RolloverMode::Incomplete;
+        // Nothing to do while it's still the same day
+        assert!(!todo_app.roll_over(day).unwrap());
+
+        assert!(todo_app.roll_over(next_day).unwrap());
+        assert_eq!(todo_app.todo_list.date, next_day);
+        assert_eq!(item_texts(&todo_app), ["open def:1"]);
+
+        // The old day keeps everything, the new day starts with the open items
+        let files = todo_files(dir.path());
+        assert_eq!(files.len(), 2);
+        let old = fs::read_to_string(&files[0].1).unwrap();
+        assert!(old.contains("* [x] done"));
+        let new = fs::read_to_string(&files[1].1).unwrap();
+        assert_eq!(new, format!("# TODO {}\n\n* [ ] open def:1\n", next_day));
+
+        // Touching an item clears its mark
+        assert!(todo_app.todo_list.items[0].carried);
+        todo_app.update_item_text(0, "open!".to_string()).unwrap();
+        assert!(!todo_app.todo_list.items[0].carried);
+    }
 }
@@ -1,18 +1,46 @@
+mod theme;
+
 use std::io;
-use std::{error::Error, fs::File, path::PathBuf};
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, VecDeque},
+    error::Error,
+    fs::File,
+    path::PathBuf,
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
-use crate::{TodoApp, TodoItem, TodoList};
-use chrono::Local;
+use crate::{
+    SaveError, TodoApp, TodoItem, TodoList,
+    config::{
+        ClipboardFormat, ColorMode, Config, EmptyConfirm, MultilinePaste, RolloverMode, SortMode,
+        UnindentSubitems,
+    },
+};
+use chrono::{Local, NaiveDate};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
-    execute,
+    cursor::MoveTo,
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent,
+        MouseEventKind,
+    },
+    execute, queue,
+    style::Print,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
+use theme::Palette;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
 use ratatui::{
     Frame, Terminal,
     backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout},
-    style::{Color, Style},
+    layout::{Alignment, Constraint, Direction, Layout, Position, Rect},
+    style::{Modifier, Style},
+    symbols::border,
+    text::{Line, Span},
     widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
 };
 
@@ -20,7 +48,18 @@ use ratatui::{
 enum AppMode {
     Selection,
     Edit,
+    /// Editing the note of the day
+    EditNote,
+    /// Entering the due date of the selected item in the status bar
+    EditDue,
     Delete,
+    ClearCompleted,
+    ConfirmQuit,
+    ConfirmReload,
+    /// Asking whether to unindent an item away from its subitems
+    ConfirmUnindent,
+    /// Waiting for the letter to jump to after `'`
+    JumpToLetter,
 }
 
 struct App {
@@ -29,56 +68,242 @@ struct App {
     mode: AppMode,
     edit_text: String,
     edit_cursor: usize, // Character position, not byte position
+    clear_confirmations: usize,
+    wrap_text: bool,
+    show_invisible: bool,
+    status_message: Option<StatusMessage>,
+    ring_bell: bool,
     should_quit: bool,
+    last_toggle: Option<LastToggle>,
+    /// Why the last save failed, shown until a save succeeds again
+    last_save_error: Option<String>,
+    /// Shown full-screen instead of the list until the first key press
+    greeting: Option<String>,
+    /// Index of the first item drawn, kept between frames so the list only
+    /// scrolls when the selection leaves the screen
+    scroll_offset: Cell<usize>,
+    /// Wrapped lines of the items drawn before, by item index, so redraws
+    /// only wrap the items that changed
+    wrap_cache: RefCell<HashMap<usize, (WrapKey, Rc<WrappedItem>)>>,
+    /// Where the text of the list rows was drawn last, so mouse clicks can
+    /// be mapped to items
+    list_area: Cell<Rect>,
+    /// Item index of each row drawn in `list_area` and whether it's the
+    /// item's main line
+    drawn_rows: RefCell<Vec<(usize, bool)>>,
+    /// The system clipboard, opened on the first copy and kept open because
+    /// on X11 and Wayland the copied text is gone once it is closed
+    #[cfg(feature = "clipboard")]
+    clipboard: Option<arboard::Clipboard>,
+}
+
+/// A message shown in the status bar instead of the key hints until the
+/// next key press or until it expires
+#[derive(Debug, PartialEq)]
+struct StatusMessage {
+    text: String,
+    is_error: bool,
+    shown_at: Instant,
+}
+
+/// The most recent completion toggle, so `u` can revert it
+#[derive(Debug, Clone, Copy)]
+struct LastToggle {
+    /// Position of the toggled item after it was toggled (and maybe sunk)
+    index: usize,
+    /// Completion state before the first of a run of toggles of the item
+    completed: bool,
+    /// `TodoApp::revision` right after the toggle; any later change to the
+    /// list makes the toggle no longer undoable
+    revision: u64,
 }
 
 const CURSOR: char = '|';
 
+/// List border in plain ASCII mode
+const ASCII_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+/// Marks the selected item in plain ASCII mode if no `selection_marker` is
+/// configured, as the highlight color may be lost on such terminals
+const ASCII_SELECTION_MARKER: &str = ">";
+
+/// How long a status message stays visible without a key press
+const STATUS_MESSAGE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How often to check whether the day has changed when rolling over is enabled
+const ROLLOVER_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
 impl App {
     fn new(todo_list: TodoApp) -> Self {
-        App {
-            selected_index: 0,
+        let items = &todo_list.todo_list.items;
+        let selected_index = if todo_list.config.start_on_first_incomplete {
+            items.iter().position(|item| !item.completed).unwrap_or(0)
+        } else {
+            0
+        };
+
+        let item_count = items.len();
+        let mut app = App {
+            selected_index,
             mode: AppMode::Selection,
             edit_text: String::new(),
             edit_cursor: 0,
+            clear_confirmations: 0,
+            wrap_text: true,
+            show_invisible: false,
+            status_message: None,
+            ring_bell: false,
             should_quit: false,
+            last_toggle: None,
+            last_save_error: None,
+            greeting: None,
+            scroll_offset: Cell::new(0),
+            wrap_cache: RefCell::new(HashMap::new()),
+            list_area: Cell::new(Rect::default()),
+            drawn_rows: RefCell::new(Vec::new()),
+            #[cfg(feature = "clipboard")]
+            clipboard: None,
             todo_list,
+        };
+        if item_count > LARGE_LIST_WARNING {
+            app.notify(format!(
+                "This list has {} items, which may make the display slow",
+                item_count
+            ));
         }
+        app
     }
 
-    fn handle_key_event(&mut self, key: KeyCode) -> Result<(), Box<dyn Error>> {
+    fn handle_key_event(&mut self, key: impl Into<KeyEvent>) -> Result<(), Box<dyn Error>> {
+        let key = key.into();
+        match key.kind {
+            KeyEventKind::Press => {}
+            // Holding a key only repeats harmless actions, never one-shot ones
+            // like quitting, deleting or confirming
+            KeyEventKind::Repeat if self.is_repeatable(&key) => {}
+            _ => return Ok(()),
+        }
+        // Messages are shown until the next key press
+        self.status_message = None;
+        if self.greeting.take().is_some() {
+            // The key only dismisses the greeting
+            return Ok(());
+        }
+        if self.todo_list.config.force_quit && is_force_quit(&key) {
+            // Nothing half-done is left behind, like leaving each mode with Esc
+            if self.mode == AppMode::Edit {
+                self.cancel_edit()?;
+            }
+            self.mode = AppMode::Selection;
+            self.should_quit = true;
+            return Ok(());
+        }
         match self.mode {
             AppMode::Selection => self.handle_selection_mode_key(key)?,
             AppMode::Edit => self.handle_edit_mode_key(key)?,
+            AppMode::EditNote => self.handle_edit_note_mode_key(key)?,
+            AppMode::EditDue => self.handle_edit_due_mode_key(key)?,
             AppMode::Delete => self.handle_delete_mode_key(key)?,
+            AppMode::ClearCompleted => self.handle_clear_completed_mode_key(key)?,
+            AppMode::ConfirmQuit => self.handle_confirm_quit_mode_key(key),
+            AppMode::ConfirmReload => self.handle_confirm_reload_mode_key(key)?,
+            AppMode::ConfirmUnindent => self.handle_confirm_unindent_mode_key(key)?,
+            AppMode::JumpToLetter => self.handle_jump_to_letter_mode_key(key),
         }
         Ok(())
     }
 
-    fn handle_selection_mode_key(&mut self, key: KeyCode) -> Result<(), Box<dyn Error>> {
-        match key {
+    // Whether holding down `key` should repeat its action: navigation, cursor
+    // movement, deleting characters and typing text
+    fn is_repeatable(&self, key: &KeyEvent) -> bool {
+        match self.mode {
+            AppMode::Selection => matches!(
+                key.code,
+                KeyCode::Up | KeyCode::Down | KeyCode::Char('k') | KeyCode::Char('j')
+            ),
+            AppMode::Edit | AppMode::EditNote | AppMode::EditDue => match key.code {
+                KeyCode::Left
+                | KeyCode::Right
+                | KeyCode::Home
+                | KeyCode::End
+                | KeyCode::Backspace
+                | KeyCode::Delete => true,
+                KeyCode::Char(_) => !is_command_chord(key),
+                _ => false,
+            },
+            AppMode::Delete
+            | AppMode::ClearCompleted
+            | AppMode::ConfirmQuit
+            | AppMode::ConfirmReload
+            | AppMode::ConfirmUnindent
+            | AppMode::JumpToLetter => false,
+        }
+    }
+
+    fn handle_selection_mode_key(&mut self, key: KeyEvent) -> Result<(), Box<dyn Error>> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('s') if ctrl => self.save_now(),
+            KeyCode::Char(_) if is_command_chord(&key) => {}
             KeyCode::Char('q') => {
-                self.should_quit = true;
+                if self.todo_list.config.confirm_quit_if_incomplete && self.incomplete_count() > 0 {
+                    self.mode = AppMode::ConfirmQuit;
+                } else {
+                    self.should_quit = true;
+                }
             }
+            // The insert row below the items is the last row
             KeyCode::Up | KeyCode::Char('k') => {
                 if self.selected_index > 0 {
                     self.selected_index -= 1;
+                } else if self.todo_list.config.wrap_navigation {
+                    self.selected_index = self.todo_list.todo_list.items.len();
                 }
             }
             KeyCode::Down | KeyCode::Char('j') => {
                 if self.selected_index < self.todo_list.todo_list.items.len() {
                     self.selected_index += 1;
+                } else if self.todo_list.config.wrap_navigation {
+                    self.selected_index = 0;
                 }
             }
-            KeyCode::Char('x') => {
-                if !self.todo_list.todo_list.items.is_empty() {
-                    self.todo_list.toggle_item_completed(self.selected_index)?;
+            KeyCode::Char('x') => self.toggle_selected()?,
+            KeyCode::Char('u') => match self.undoable_toggle() {
+                Some(toggle) => {
+                    self.selected_index = toggle.index;
+                    let items = &self.todo_list.todo_list.items;
+                    if items[toggle.index].completed != toggle.completed {
+                        self.todo_list.toggle_item_completed(toggle.index)?;
+                        self.selected_index = self.todo_list.sink_item(toggle.index)?;
+                    }
+                    self.last_toggle = None;
+                    self.notify("Undid toggle");
                 }
-            }
+                None => self.notify("Nothing to undo"),
+            },
             KeyCode::Char('i') => {
                 self.todo_list.insert_new_item(self.selected_index)?;
                 self.mode = AppMode::Edit;
             }
+            KeyCode::Char('J') => {
+                let joined = self.todo_list.join_with_next(self.selected_index)?;
+                if !joined {
+                    self.notify("Can't join: there is no next item or it has subitems");
+                }
+            }
+            KeyCode::Char('I') => {
+                self.selected_index = self.todo_list.insert_child_item(self.selected_index)?;
+                self.mode = AppMode::Edit;
+            }
             KeyCode::Enter => {
                 if self.selected_index < self.todo_list.todo_list.items.len() {
                     self.edit_text = self
@@ -96,59 +321,388 @@ impl App {
             KeyCode::Tab => {
                 self.todo_list.indent_item_right(self.selected_index)?;
             }
-            KeyCode::BackTab => {
-                self.todo_list.indent_item_left(self.selected_index)?;
+            KeyCode::BackTab => self.unindent_selected()?,
+            KeyCode::Right if ctrl => {
+                self.todo_list.indent_item_right(self.selected_index)?;
             }
+            KeyCode::Left if ctrl => self.unindent_selected()?,
             KeyCode::Char('d') => {
                 if !self.todo_list.todo_list.items.is_empty() {
                     self.mode = AppMode::Delete;
                 }
             }
+            KeyCode::Char('w') => {
+                self.wrap_text = !self.wrap_text;
+            }
+            KeyCode::Char('n') => {
+                self.edit_text = self.todo_list.todo_list.note.clone().unwrap_or_default();
+                self.edit_cursor = self.edit_text.chars().count();
+                self.mode = AppMode::EditNote;
+            }
+            KeyCode::Char('t') => {
+                if let Some(item) = self.todo_list.todo_list.items.get(self.selected_index) {
+                    self.edit_text = item
+                        .due_date()
+                        .map(|date| date.format("%Y-%m-%d").to_string())
+                        .unwrap_or_default();
+                    self.edit_cursor = self.edit_text.chars().count();
+                    self.mode = AppMode::EditDue;
+                }
+            }
+            KeyCode::Char('r') => {
+                if self.todo_list.has_unsaved_changes() {
+                    self.mode = AppMode::ConfirmReload;
+                } else {
+                    self.reload()?;
+                }
+            }
+            KeyCode::Char('C') if self.completed_count() > 0 => {
+                self.clear_confirmations = 0;
+                self.mode = AppMode::ClearCompleted;
+            }
+            KeyCode::Char('g') => {
+                self.selected_index = self.todo_list.move_item_to_top(self.selected_index)?;
+            }
+            KeyCode::Char('G') => {
+                self.selected_index = self.todo_list.move_item_to_bottom(self.selected_index)?;
+            }
+            KeyCode::Char('\'') if !self.todo_list.todo_list.items.is_empty() => {
+                self.mode = AppMode::JumpToLetter;
+            }
+            KeyCode::Char('Y') => {
+                let format = self.todo_list.config.clipboard_format;
+                let text = list_for_clipboard(&self.todo_list.todo_list, format);
+                match self.copy_to_clipboard(text) {
+                    Ok(()) => self.notify("Copied list to clipboard"),
+                    Err(err) => self.notify_error(err.to_string()),
+                }
+            }
             _ => {}
         }
         Ok(())
     }
 
-    fn handle_edit_mode_key(&mut self, key: KeyCode) -> Result<(), Box<dyn Error>> {
-        match key {
-            KeyCode::Esc => {
-                // Cancel edit mode
-                if self.todo_list.todo_list.items[self.selected_index]
-                    .text
-                    .is_empty()
-                {
-                    // Remove the item if it was newly created and still empty
-                    self.todo_list.delete_item(self.selected_index)?;
-                    self.selected_index = self
-                        .selected_index
-                        .min(self.todo_list.todo_list.items.len());
-                }
+    /// Saves the list (including the text being edited) and reports the
+    /// outcome in the status bar
+    fn save_now(&mut self) {
+        let result = if self.mode == AppMode::Edit {
+            self.todo_list
+                .update_item_text(self.selected_index, self.edit_text.clone())
+        } else {
+            self.todo_list.save_todo_list()
+        };
+        match result {
+            Ok(()) => {
+                self.last_save_error = None;
+                self.notify(format!("Saved {}", self.todo_list.file_path().display()));
+            }
+            Err(err) => {
+                self.last_save_error = Some(err.to_string());
+                self.notify_error(format!("Save failed: {}", err));
+            }
+        }
+    }
+
+    /// Shows an error from handling an event. Failed saves stay on screen
+    /// until a save succeeds.
+    fn report_error(&mut self, err: Box<dyn Error>) {
+        if err.is::<SaveError>() {
+            self.last_save_error = Some(err.to_string());
+        } else {
+            self.notify_error(err.to_string());
+        }
+    }
+
+    /// Puts `text` on the system clipboard, for `Y`
+    #[cfg(feature = "clipboard")]
+    fn copy_to_clipboard(&mut self, text: String) -> Result<(), Box<dyn Error>> {
+        let clipboard = match &mut self.clipboard {
+            Some(clipboard) => clipboard,
+            clipboard @ None => clipboard.insert(arboard::Clipboard::new()?),
+        };
+        clipboard.set_text(text)?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "clipboard"))]
+    fn copy_to_clipboard(&mut self, _text: String) -> Result<(), Box<dyn Error>> {
+        Err("Clipboard support isn't compiled in (build with --features clipboard)".into())
+    }
+
+    /// Drops the save failure once everything has been saved after all
+    fn clear_save_error_if_saved(&mut self) {
+        if !self.todo_list.has_unsaved_changes() {
+            self.last_save_error = None;
+        }
+    }
+
+    /// Suggests splitting the selected item if its line is wider than the
+    /// configured limit. Saving is not affected.
+    fn warn_if_line_too_long(&mut self) {
+        let config = &self.todo_list.config;
+        let Some(item) = self.todo_list.todo_list.items.get(self.selected_index) else {
+            return;
+        };
+        let width = item.to_markdown_line_with(&config.format).width();
+        let limit = config.max_line_length;
+        if limit > 0 && width > limit {
+            self.notify(format!(
+                "Line is {} columns wide (limit {}), consider splitting it with Alt+Enter",
+                width, limit
+            ));
+        }
+    }
+
+    /// Toggles the selected item, if it isn't the insert row
+    fn toggle_selected(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(item) = self.todo_list.todo_list.items.get(self.selected_index) else {
+            return Ok(());
+        };
+        // Toggling the same item again keeps the state from before the first
+        // toggle, so a single undo reverts them all
+        let completed = self
+            .undoable_toggle()
+            .filter(|toggle| toggle.index == self.selected_index)
+            .map_or(item.completed, |toggle| toggle.completed);
+        let was_all_completed = self.todo_list.todo_list.all_completed();
+        self.todo_list.toggle_item_completed(self.selected_index)?;
+        self.selected_index = self.todo_list.sink_item(self.selected_index)?;
+        self.last_toggle = Some(LastToggle {
+            index: self.selected_index,
+            completed,
+            revision: self.todo_list.revision,
+        });
+        if self.todo_list.config.celebrate
+            && !was_all_completed
+            && self.todo_list.todo_list.all_completed()
+        {
+            self.ring_bell = true;
+            let message = if self.todo_list.config.plain_ascii {
+                "All done!"
+            } else {
+                "All done! 🎉"
+            };
+            self.notify(message);
+        }
+        Ok(())
+    }
+
+    /// A left click in selection mode selects the item under the pointer and
+    /// toggles it if the click hit its checkbox
+    fn handle_mouse_event(&mut self, mouse: MouseEvent) -> Result<(), Box<dyn Error>> {
+        if mouse.kind != MouseEventKind::Down(MouseButton::Left)
+            || self.mode != AppMode::Selection
+            || self.greeting.is_some()
+        {
+            return Ok(());
+        }
+        let area = self.list_area.get();
+        if !area.contains(Position::new(mouse.column, mouse.row)) {
+            return Ok(());
+        }
+        let row = (mouse.row - area.y) as usize;
+        let Some(&(index, is_main_line)) = self.drawn_rows.borrow().get(row) else {
+            return Ok(());
+        };
+        self.selected_index = index;
+        let on_checkbox = self
+            .todo_list
+            .todo_list
+            .items
+            .get(index)
+            .is_some_and(|item| is_checkbox_column(item, (mouse.column - area.x) as usize));
+        if is_main_line && on_checkbox {
+            self.toggle_selected()?;
+        }
+        Ok(())
+    }
+
+    /// Flashes a message in the status bar
+    fn notify(&mut self, text: impl Into<String>) {
+        self.set_status_message(text.into(), false);
+    }
+
+    /// Flashes an error message in the status bar
+    fn notify_error(&mut self, text: impl Into<String>) {
+        self.set_status_message(text.into(), true);
+    }
+
+    fn set_status_message(&mut self, text: String, is_error: bool) {
+        self.status_message = Some(StatusMessage {
+            text,
+            is_error,
+            shown_at: Instant::now(),
+        });
+    }
+
+    /// Greets the user before they see the list of a new day
+    fn greet(&mut self) {
+        let open = self.incomplete_count();
+        let tasks = if open == 1 { "task" } else { "tasks" };
+        self.greeting = Some(format!("Good morning! {} {} so far", open, tasks));
+    }
+
+    /// Moves the selection back into the list after it shrank, onto the
+    /// insertion row past the last item at the most
+    fn clamp_selection(&mut self) {
+        self.selected_index = self
+            .selected_index
+            .min(self.todo_list.todo_list.items.len());
+    }
+
+    /// Rolls the list over to `today` if enabled and the day has changed.
+    /// Waits while an item is being edited or a prompt is open.
+    fn check_rollover(&mut self, today: NaiveDate) -> Result<(), Box<dyn Error>> {
+        if self.mode != AppMode::Selection || !self.todo_list.roll_over(today)? {
+            return Ok(());
+        }
+        self.clamp_selection();
+        self.notify(format!("Good morning! Moved the list over to {}", today));
+        Ok(())
+    }
+
+    /// Longest time to wait for input before the next check for a new day
+    fn rollover_check_interval(&self) -> Option<Duration> {
+        (self.todo_list.config.rollover != RolloverMode::Off).then_some(ROLLOVER_CHECK_INTERVAL)
+    }
+
+    /// Time left until the status message expires, if there is one
+    fn status_message_remaining(&self, now: Instant) -> Option<Duration> {
+        self.status_message.as_ref().map(|message| {
+            STATUS_MESSAGE_TIMEOUT.saturating_sub(now.duration_since(message.shown_at))
+        })
+    }
+
+    fn clear_expired_status_message(&mut self, now: Instant) {
+        if self.status_message_remaining(now) == Some(Duration::ZERO) {
+            self.status_message = None;
+        }
+    }
+
+    // The last toggle, unless the list has changed since
+    fn undoable_toggle(&self) -> Option<LastToggle> {
+        self.last_toggle.filter(|toggle| {
+            toggle.revision == self.todo_list.revision
+                && toggle.index < self.todo_list.todo_list.items.len()
+        })
+    }
+
+    fn handle_edit_mode_key(&mut self, key: KeyEvent) -> Result<(), Box<dyn Error>> {
+        match key.code {
+            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => self.save_now(),
+            KeyCode::Esc => self.cancel_edit()?,
+            KeyCode::Enter if key.modifiers.contains(KeyModifiers::ALT) => {
+                // Split the item at the cursor and continue editing the new item
+                self.split_edit_item()?;
+            }
+            KeyCode::Enter
+                if self.todo_list.config.empty_confirm == EmptyConfirm::Delete
+                    && self.edit_text.trim().is_empty() =>
+            {
+                // Blank items are dropped, whether new or emptied
+                self.todo_list.delete_item(self.selected_index)?;
+                self.clamp_selection();
                 self.mode = AppMode::Selection;
+                self.edit_cursor = 0;
+                self.edit_text.clear();
             }
             KeyCode::Enter => {
                 // Confirm changes
                 self.todo_list
                     .update_item_text(self.selected_index, self.edit_text.clone())?;
+                self.warn_if_line_too_long();
+                if self.selected_index < self.todo_list.todo_list.items.len() {
+                    self.selected_index = self.todo_list.apply_auto_sort(self.selected_index)?;
+                }
                 self.mode = AppMode::Selection;
                 self.edit_cursor = 0;
                 self.edit_text.clear();
             }
-            KeyCode::Left => {
-                if self.edit_cursor > 0 {
-                    self.edit_cursor -= 1;
+            KeyCode::Tab => {
+                // Nest the item being edited without leaving edit mode
+                self.todo_list.indent_item_right(self.selected_index)?;
+            }
+            KeyCode::BackTab => {
+                self.todo_list.indent_item_left(self.selected_index)?;
+            }
+            _ => self.handle_text_key(key),
+        }
+        Ok(())
+    }
+
+    fn handle_edit_note_mode_key(&mut self, key: KeyEvent) -> Result<(), Box<dyn Error>> {
+        match key.code {
+            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => self.save_now(),
+            KeyCode::Esc => {
+                self.mode = AppMode::Selection;
+                self.edit_text.clear();
+            }
+            KeyCode::Enter => {
+                self.mode = AppMode::Selection;
+                let note = std::mem::take(&mut self.edit_text);
+                self.edit_cursor = 0;
+                self.todo_list.set_note(&note)?;
+            }
+            _ => self.handle_text_key(key),
+        }
+        Ok(())
+    }
+
+    fn handle_edit_due_mode_key(&mut self, key: KeyEvent) -> Result<(), Box<dyn Error>> {
+        match key.code {
+            KeyCode::Esc => {
+                self.mode = AppMode::Selection;
+                self.edit_text.clear();
+            }
+            KeyCode::Enter => match parse_due_input(&self.edit_text, Local::now().date_naive()) {
+                Ok(date) => {
+                    self.mode = AppMode::Selection;
+                    self.edit_text.clear();
+                    self.edit_cursor = 0;
+                    self.todo_list.set_due_date(self.selected_index, date)?;
                 }
+                // Stay in the prompt to fix the input
+                Err(err) => self.notify_error(err),
+            },
+            _ => self.handle_text_key(key),
+        }
+        Ok(())
+    }
+
+    // Cursor movement, typing and deleting in the edit text, shared by
+    // editing items, the note of the day and due dates
+    fn handle_text_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.show_invisible = !self.show_invisible;
+            }
+            KeyCode::Char('x') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                let cursor_byte = byte_index(&self.edit_text, self.edit_cursor);
+                self.edit_cursor = strip_invisible(&self.edit_text[..cursor_byte])
+                    .chars()
+                    .count();
+                self.edit_text = strip_invisible(&self.edit_text);
+            }
+            // The cursor moves over and deletes whole graphemes, so it never
+            // ends up inside an emoji sequence or before a combining mark
+            KeyCode::Left => {
+                self.edit_cursor = prev_grapheme_boundary(&self.edit_text, self.edit_cursor);
             }
             KeyCode::Right => {
-                if self.edit_cursor < self.edit_text.chars().count() {
-                    self.edit_cursor += 1;
-                }
+                self.edit_cursor = next_grapheme_boundary(&self.edit_text, self.edit_cursor);
             }
             KeyCode::Backspace => {
-                self.edit_cursor = self.edit_cursor.saturating_sub(1);
-                Self::remove_edit_text_char_at(&mut self.edit_text, self.edit_cursor);
+                let start = prev_grapheme_boundary(&self.edit_text, self.edit_cursor);
+                for _ in start..self.edit_cursor {
+                    Self::remove_edit_text_char_at(&mut self.edit_text, start);
+                }
+                self.edit_cursor = start;
             }
             KeyCode::Delete => {
-                Self::remove_edit_text_char_at(&mut self.edit_text, self.edit_cursor);
+                let end = next_grapheme_boundary(&self.edit_text, self.edit_cursor);
+                for _ in self.edit_cursor..end {
+                    Self::remove_edit_text_char_at(&mut self.edit_text, self.edit_cursor);
+                }
             }
             KeyCode::Home => {
                 self.edit_cursor = 0;
@@ -156,20 +710,197 @@ impl App {
             KeyCode::End => {
                 self.edit_cursor = self.edit_text.chars().count();
             }
-            KeyCode::Char(c) => {
-                // Convert character position to byte position for insertion
-                let byte_pos = self
-                    .edit_text
-                    .char_indices()
-                    .nth(self.edit_cursor)
-                    .map(|(pos, _)| pos)
-                    .unwrap_or(self.edit_text.len());
-
-                self.edit_text.insert(byte_pos, c);
-                self.edit_cursor += 1;
+            KeyCode::Char(c) if !is_command_chord(&key) => {
+                self.insert_edit_text(c.encode_utf8(&mut [0; 4]));
             }
             _ => {}
         }
+    }
+
+    /// Leaves edit mode without keeping the edited text
+    fn cancel_edit(&mut self) -> Result<(), Box<dyn Error>> {
+        let items = &self.todo_list.todo_list.items;
+        if items
+            .get(self.selected_index)
+            .is_some_and(|item| item.text.is_empty())
+        {
+            // Remove the item if it was newly created and still empty
+            self.todo_list.delete_item(self.selected_index)?;
+        }
+        self.clamp_selection();
+        self.mode = AppMode::Selection;
+        Ok(())
+    }
+
+    /// Inserts pasted text at the cursor in one go. Pastes outside of edit
+    /// mode are ignored.
+    fn handle_paste(&mut self, text: &str) -> Result<(), Box<dyn Error>> {
+        if !matches!(
+            self.mode,
+            AppMode::Edit | AppMode::EditNote | AppMode::EditDue
+        ) {
+            return Ok(());
+        }
+
+        // Blank lines would only produce empty items or double spaces
+        let lines: Vec<&str> = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .collect();
+        if self.mode != AppMode::Edit {
+            // Notes and due dates are a single line
+            self.insert_edit_text(&lines.join(" "));
+            return Ok(());
+        }
+        match self.todo_list.config.multiline_paste {
+            MultilinePaste::Join => self.insert_edit_text(&lines.join(" ")),
+            MultilinePaste::Outline => {
+                let Some((first, rest)) = lines.split_first() else {
+                    return Ok(());
+                };
+                self.insert_edit_text(first.trim());
+                let items = self.outline_items(first, rest);
+                if !items.is_empty() {
+                    let list = &self.todo_list.todo_list;
+                    let at = list.subtree_range(self.selected_index).end;
+                    self.todo_list.insert_items(at, items)?;
+                }
+            }
+            MultilinePaste::Split => {
+                for (i, line) in lines.into_iter().enumerate() {
+                    if i > 0 {
+                        self.split_edit_item()?;
+                    }
+                    self.insert_edit_text(line);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Starts editing a new item at the end of the list, for `todui capture`.
+    /// The item isn't saved until it is confirmed with some text.
+    fn start_capture(&mut self) {
+        let at = self.todo_list.todo_list.items.len();
+        let item = self
+            .todo_list
+            .new_empty_item(self.todo_list.default_indent_for(at));
+        self.todo_list.todo_list.items.push(item);
+        self.selected_index = at;
+        self.mode = AppMode::Edit;
+        self.edit_text.clear();
+        self.edit_cursor = 0;
+    }
+
+    // Ends the capture, dropping the unsaved item that is still being written
+    fn quit_capture(&mut self) {
+        if self.mode == AppMode::Edit {
+            self.todo_list.todo_list.items.truncate(self.selected_index);
+            self.mode = AppMode::Selection;
+            self.edit_text.clear();
+            self.edit_cursor = 0;
+        }
+        self.should_quit = true;
+    }
+
+    /// Handles a key while capturing: Enter saves the item and quits, or with
+    /// `stay` starts the next one. Esc and confirming an empty item quit
+    /// without adding anything.
+    fn handle_capture_key(&mut self, key: KeyEvent, stay: bool) -> Result<(), Box<dyn Error>> {
+        let confirms = key.kind == KeyEventKind::Press
+            && key.code == KeyCode::Enter
+            && !key.modifiers.contains(KeyModifiers::ALT);
+        let cancels = key.kind == KeyEventKind::Press && key.code == KeyCode::Esc;
+        if cancels || (confirms && self.edit_text.trim().is_empty()) {
+            self.quit_capture();
+            return Ok(());
+        }
+
+        let text = self.edit_text.clone();
+        self.handle_key_event(key)?;
+        if self.should_quit {
+            // Force quit while still writing
+            self.quit_capture();
+        } else if self.mode == AppMode::Edit {
+            return Ok(());
+        } else if confirms && stay {
+            self.start_capture();
+            self.notify(format!("Added \"{}\"", text));
+        } else {
+            self.should_quit = true;
+        }
+        Ok(())
+    }
+
+    /// Items for the pasted `lines` that follow the `first` one, which goes
+    /// into the edited item. Lines indented deeper than the first become its
+    /// subitems, the others its siblings. List markers like `* [x] ` are
+    /// taken off, keeping the completion state.
+    fn outline_items(&self, first: &str, lines: &[&str]) -> Vec<TodoItem> {
+        let format = &self.todo_list.todo_list.format;
+        let level = |line: &str| format.indent_level(format.leading_width(line));
+        let base_level = level(first);
+        let parent_level = self.todo_list.todo_list.items[self.selected_index].indent_level;
+        lines
+            .iter()
+            .map(|line| {
+                let mut indent_level = parent_level + level(line).saturating_sub(base_level);
+                if let Some(max) = self.todo_list.config.max_indent {
+                    indent_level = indent_level.min(max);
+                }
+                let text = line.trim();
+                let text = ["* ", "- ", "+ "]
+                    .iter()
+                    .find_map(|bullet| text.strip_prefix(bullet))
+                    .unwrap_or(text);
+                let checkbox = text
+                    .strip_prefix('[')
+                    .and_then(|rest| rest.split_once(']'))
+                    .and_then(|(marker, rest)| {
+                        Some((rest.strip_prefix(' ')?, format.marker_completed(marker)?))
+                    });
+                let (text, completed) = checkbox.unwrap_or((text, false));
+                TodoItem::new(text.to_string(), completed, indent_level)
+            })
+            .collect()
+    }
+
+    /// Inserts `text` at the cursor, cutting it off at `max_edit_length`
+    fn insert_edit_text(&mut self, text: &str) {
+        let mut text = text;
+        if let Some(max) = self.todo_list.config.max_edit_length {
+            let room = max.saturating_sub(self.edit_text.chars().count());
+            if text.chars().count() > room {
+                // Cut between graphemes, so no accent or emoji is split up
+                let end = grapheme_boundaries(text)
+                    .take_while(|&boundary| boundary <= room)
+                    .last()
+                    .unwrap_or(0);
+                text = &text[..byte_index(text, end)];
+                self.notify(format!("Items are limited to {} characters", max));
+            }
+        }
+        let byte_pos = byte_index(&self.edit_text, self.edit_cursor);
+        self.edit_text.insert_str(byte_pos, text);
+        self.edit_cursor += text.chars().count();
+    }
+
+    fn split_edit_item(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.selected_index >= self.todo_list.todo_list.items.len() {
+            return Ok(());
+        }
+
+        let byte_pos = byte_index(&self.edit_text, self.edit_cursor);
+        let tail = self.edit_text.split_off(byte_pos);
+        self.todo_list.split_item(
+            self.selected_index,
+            std::mem::take(&mut self.edit_text),
+            tail.clone(),
+        )?;
+
+        self.selected_index += 1;
+        self.edit_text = tail;
+        self.edit_cursor = 0;
         Ok(())
     }
 
@@ -189,18 +920,42 @@ impl App {
             false
         }
     }
-    fn handle_delete_mode_key(&mut self, key: KeyCode) -> Result<(), Box<dyn Error>> {
-        match key {
-            KeyCode::Char('y') => {
-                // Confirm delete
-                self.todo_list.delete_item(self.selected_index)?;
+    fn handle_delete_mode_key(&mut self, key: KeyEvent) -> Result<(), Box<dyn Error>> {
+        let config = &self.todo_list.config;
+        let bound = |keys: &[String]| keys.iter().any(|name| key_matches(name, &key));
+        if bound(&config.delete_confirm_keys) {
+            self.todo_list.delete_item(self.selected_index)?;
+            self.mode = AppMode::Selection;
+            // Deleting the last item selects the one above, not the insertion row
+            self.clamp_selection();
+            if self.selected_index >= self.todo_list.todo_list.items.len() {
+                self.selected_index = self.selected_index.saturating_sub(1);
+            }
+        } else if bound(&config.delete_cancel_keys) {
+            self.mode = AppMode::Selection;
+        }
+        Ok(())
+    }
+
+    fn handle_confirm_quit_mode_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('y') if !is_command_chord(&key) => {
+                self.should_quit = true;
+            }
+            KeyCode::Char('n') | KeyCode::Esc => {
                 self.mode = AppMode::Selection;
-                if self.selected_index >= self.todo_list.todo_list.items.len() {
-                    self.selected_index = self.selected_index.saturating_sub(1);
-                }
             }
-            KeyCode::Esc => {
-                // Cancel delete
+            _ => {}
+        }
+    }
+
+    fn handle_confirm_reload_mode_key(&mut self, key: KeyEvent) -> Result<(), Box<dyn Error>> {
+        match key.code {
+            KeyCode::Char('y') if !is_command_chord(&key) => {
+                self.mode = AppMode::Selection;
+                self.reload()?;
+            }
+            KeyCode::Char('n') | KeyCode::Esc => {
                 self.mode = AppMode::Selection;
             }
             _ => {}
@@ -208,55 +963,347 @@ impl App {
         Ok(())
     }
 
-    fn should_quit(&self) -> bool {
-        self.should_quit
+    /// Unindents the selected item, first asking if that would leave its
+    /// subitems behind and `unindent_subitems` is `confirm`
+    fn unindent_selected(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.orphaned_by_unindent() > 0 {
+            self.mode = AppMode::ConfirmUnindent;
+            return Ok(());
+        }
+        self.todo_list.indent_item_left(self.selected_index)
     }
-}
-
-// Helper function to wrap text based on available width
-fn wrap_todo_item_text(
-    item: &TodoItem,
-    available_width: usize,
-    is_selected: bool,
-    edit_text: &str,
-    edit_cursor: usize,
-    is_editing: bool,
-) -> Vec<(String, bool)> {
-    let indent = "  ".repeat(item.indent_level);
-    let checkbox = if item.completed { "[x]" } else { "[ ]" };
-    let prefix = format!("{}* {} ", indent, checkbox);
-    let prefix_len = prefix.len();
 
-    let text = if is_editing && is_selected {
-        let mut display_text = edit_text.to_string();
-        // Insert cursor at character position, not byte position
-        let byte_pos = edit_text
-            .char_indices()
-            .nth(edit_cursor)
-            .map(|(pos, _)| pos)
-            .unwrap_or(edit_text.len());
-        display_text.insert(byte_pos, CURSOR);
-        display_text
-    } else {
-        item.text.clone()
-    };
+    // Subitems that unindenting the selected item would leave behind, counted
+    // only if the user wants to be asked about them
+    fn orphaned_by_unindent(&self) -> usize {
+        let todo_list = &self.todo_list.todo_list;
+        let asks = self.todo_list.config.unindent_subitems == UnindentSubitems::Confirm;
+        match todo_list.items.get(self.selected_index) {
+            Some(item) if asks && item.indent_level > 0 => {
+                todo_list.child_count(self.selected_index)
+            }
+            _ => 0,
+        }
+    }
 
-    if available_width <= prefix_len {
-        return vec![(format!("{}{}", prefix, text), true)];
+    fn handle_confirm_unindent_mode_key(&mut self, key: KeyEvent) -> Result<(), Box<dyn Error>> {
+        match key.code {
+            KeyCode::Char('y') if !is_command_chord(&key) => {
+                self.mode = AppMode::Selection;
+                self.todo_list.indent_item_left(self.selected_index)?;
+            }
+            KeyCode::Char('n') | KeyCode::Esc => {
+                self.mode = AppMode::Selection;
+            }
+            _ => {}
+        }
+        Ok(())
     }
 
-    let text_width = available_width - prefix_len;
-    let words: Vec<&str> = text.split_whitespace().collect();
+    // Any key ends the jump; a letter also moves the selection to the next
+    // item starting with it, so repeating `'` and the letter cycles through them
+    fn handle_jump_to_letter_mode_key(&mut self, key: KeyEvent) {
+        self.mode = AppMode::Selection;
+        let KeyCode::Char(letter) = key.code else {
+            return;
+        };
+        if is_command_chord(&key) {
+            return;
+        }
+        match next_item_starting_with(&self.todo_list.todo_list.items, self.selected_index, letter)
+        {
+            Some(index) => self.selected_index = index,
+            None => self.notify(format!("No item starts with '{}'", letter)),
+        }
+    }
 
-    if words.is_empty() {
-        return vec![(prefix, true)];
+    /// Re-reads the list from its file, keeping the selection in range.
+    /// Warnings about the file are shown instead of the usual message.
+    fn reload(&mut self) -> Result<(), Box<dyn Error>> {
+        let warnings = self.todo_list.reload()?;
+        self.clamp_selection();
+        let message = match warnings.as_slice() {
+            [] => format!("Reloaded {}", self.todo_list.file_path().display()),
+            [warning] => warning.clone(),
+            [warning, rest @ ..] => format!("{} (+{} more)", warning, rest.len()),
+        };
+        self.notify(message);
+        Ok(())
     }
 
-    let mut lines = Vec::new();
+    fn handle_clear_completed_mode_key(&mut self, key: KeyEvent) -> Result<(), Box<dyn Error>> {
+        match key.code {
+            KeyCode::Char('y') if !is_command_chord(&key) => {
+                // Removing more items than the threshold takes a second confirmation
+                self.clear_confirmations += 1;
+                if self.clear_confirmations >= self.required_clear_confirmations() {
+                    self.todo_list.clear_completed()?;
+                    self.mode = AppMode::Selection;
+                    self.clamp_selection();
+                }
+            }
+            KeyCode::Esc => {
+                self.mode = AppMode::Selection;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn completed_count(&self) -> usize {
+        self.todo_list
+            .todo_list
+            .items
+            .iter()
+            .filter(|item| item.completed)
+            .count()
+    }
+
+    // Open tasks, leaving out notes
+    fn incomplete_count(&self) -> usize {
+        self.todo_list
+            .todo_list
+            .items
+            .iter()
+            .filter(|item| item.is_task && !item.completed)
+            .count()
+    }
+
+    fn required_clear_confirmations(&self) -> usize {
+        if self.completed_count() > self.todo_list.config.bulk_delete_threshold {
+            2
+        } else {
+            1
+        }
+    }
+
+    fn should_quit(&self) -> bool {
+        self.should_quit
+    }
+}
+
+// Ctrl+q or Ctrl+c, which quit from any mode
+fn is_force_quit(key: &KeyEvent) -> bool {
+    key.modifiers.contains(KeyModifiers::CONTROL)
+        && matches!(key.code, KeyCode::Char('q') | KeyCode::Char('c'))
+}
+
+// Whether a character key was pressed together with either Ctrl or Alt. Plain
+// letter bindings and text input ignore these; Ctrl+Alt is let through since
+// that's how AltGr characters arrive on some platforms.
+fn is_command_chord(key: &KeyEvent) -> bool {
+    key.modifiers.contains(KeyModifiers::CONTROL) != key.modifiers.contains(KeyModifiers::ALT)
+}
+
+// Whether `key` is the key configured as `name`: a single character or a
+// named key like "Enter" (ignoring case)
+fn key_matches(name: &str, key: &KeyEvent) -> bool {
+    if is_command_chord(key) {
+        return false;
+    }
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return key.code == KeyCode::Char(c);
+    }
+    match name.to_ascii_lowercase().as_str() {
+        "enter" => key.code == KeyCode::Enter,
+        "esc" => key.code == KeyCode::Esc,
+        "space" => key.code == KeyCode::Char(' '),
+        "tab" => key.code == KeyCode::Tab,
+        "backspace" => key.code == KeyCode::Backspace,
+        _ => false,
+    }
+}
+
+// Converts a character position into the matching byte position within `text`
+fn byte_index(text: &str, char_index: usize) -> usize {
+    text.char_indices()
+        .nth(char_index)
+        .map(|(pos, _)| pos)
+        .unwrap_or(text.len())
+}
+
+// Visible stand-in for characters that don't show up (or look like a plain
+// space) when rendered
+fn invisible_placeholder(c: char) -> Option<char> {
+    match c {
+        // No-break spaces
+        '\u{00A0}' | '\u{2007}' | '\u{202F}' => Some('·'),
+        // Zero-width characters, soft hyphen and byte order mark
+        '\u{200B}' | '\u{200C}' | '\u{200D}' | '\u{2060}' | '\u{00AD}' | '\u{FEFF}' => Some('␣'),
+        _ => None,
+    }
+}
+
+// Replaces invisible characters with placeholders, one char for one char so
+// that cursor positions stay valid
+fn show_invisible(text: &str) -> String {
+    text.chars()
+        .map(|c| invisible_placeholder(c).unwrap_or(c))
+        .collect()
+}
+
+// Turns no-break spaces into plain spaces and drops zero-width characters.
+// Note that this also breaks up emoji sequences joined with U+200D.
+fn strip_invisible(text: &str) -> String {
+    text.chars()
+        .filter_map(|c| match invisible_placeholder(c) {
+            Some('·') => Some(' '),
+            Some(_) => None,
+            None => Some(c),
+        })
+        .collect()
+}
+
+/// Part of an item's text, either plain or an inline `[label](url)` link
+#[derive(Debug, PartialEq)]
+enum InlineSegment<'a> {
+    Text(&'a str),
+    Link { label: &'a str, url: &'a str },
+}
+
+// Splits `text` into plain text and `[label](url)` links. Anything that
+// doesn't form a complete link (e.g. a URL with spaces) stays plain text.
+fn parse_inline_links(text: &str) -> Vec<InlineSegment<'_>> {
+    let mut segments = Vec::new();
+    let mut rest = text;
+    while let Some((start, label, url, end)) = find_inline_link(rest) {
+        if start > 0 {
+            segments.push(InlineSegment::Text(&rest[..start]));
+        }
+        segments.push(InlineSegment::Link { label, url });
+        rest = &rest[end..];
+    }
+    if !rest.is_empty() {
+        segments.push(InlineSegment::Text(rest));
+    }
+    segments
+}
+
+// Finds the first link, returning its start, label, URL and end
+fn find_inline_link(text: &str) -> Option<(usize, &str, &str, usize)> {
+    let mut search_from = 0;
+    while let Some(offset) = text[search_from..].find('[') {
+        let start = search_from + offset;
+        let after_open = &text[start + 1..];
+        if let Some(close) = after_open.find(']') {
+            let label = &after_open[..close];
+            let url = after_open[close + 1..]
+                .strip_prefix('(')
+                .and_then(|tail| tail.split_once(')'))
+                .map(|(url, _)| url)
+                .filter(|url| !url.is_empty() && !url.contains(char::is_whitespace));
+            if let Some(url) = url.filter(|_| !label.contains('[')) {
+                // `[` + label + `](` + url + `)`
+                let end = start + label.len() + url.len() + 4;
+                return Some((start, label, url, end));
+            }
+        }
+        search_from = start + 1;
+    }
+    None
+}
+
+// Replaces links with their labels, returning the text to display and for
+// each of its chars whether it belongs to a link
+fn render_links(text: &str) -> (String, Vec<bool>) {
+    let mut display = String::new();
+    let mut flags = Vec::new();
+    for segment in parse_inline_links(text) {
+        let (part, is_link) = match segment {
+            InlineSegment::Text(text) => (text, false),
+            InlineSegment::Link { label, .. } => (label, true),
+        };
+        display.push_str(part);
+        flags.extend(part.chars().map(|_| is_link));
+    }
+    (display, flags)
+}
+
+// Styles the link labels in a rendered line. Wrapping only drops and
+// re-inserts whitespace, so the line's other chars match up one by one with
+// the non-whitespace `link_chars` of the displayed text.
+fn style_links(
+    line: &str,
+    prefix_chars: usize,
+    link_chars: &mut impl Iterator<Item = (char, bool)>,
+    link_style: Style,
+) -> Line<'static> {
+    let split = byte_index(line, prefix_chars);
+    let mut spans = vec![Span::raw(line[..split].to_string())];
+    let mut current = String::new();
+    let mut current_is_link = false;
+    for c in line[split..].chars() {
+        let is_link = !c.is_whitespace()
+            && link_chars
+                .next()
+                .is_some_and(|(source, is_link)| source == c && is_link);
+        if is_link != current_is_link && !current.is_empty() {
+            let text = std::mem::take(&mut current);
+            spans.push(if current_is_link {
+                Span::styled(text, link_style)
+            } else {
+                Span::raw(text)
+            });
+        }
+        current_is_link = is_link;
+        current.push(c);
+    }
+    if !current.is_empty() {
+        spans.push(if current_is_link {
+            Span::styled(current, link_style)
+        } else {
+            Span::raw(current)
+        });
+    }
+    Line::from(spans)
+}
+
+// Helper function to wrap text based on available width
+fn wrap_todo_item_text(
+    item: &TodoItem,
+    available_width: usize,
+    is_selected: bool,
+    edit_text: &str,
+    edit_cursor: usize,
+    is_editing: bool,
+) -> Vec<(String, bool)> {
+    let indent = "  ".repeat(item.indent_level);
+    let prefix = if item.is_task {
+        let checkbox = if item.completed { "[x]" } else { "[ ]" };
+        format!("{}* {} ", indent, checkbox)
+    } else {
+        format!("{}* ", indent)
+    };
+    let prefix_len = prefix.width();
+
+    let text = if is_editing && is_selected {
+        let mut display_text = edit_text.to_string();
+        // Insert cursor at character position, not byte position
+        display_text.insert(byte_index(edit_text, edit_cursor), CURSOR);
+        display_text
+    } else {
+        item.text.clone()
+    };
+
+    if available_width <= prefix_len {
+        return vec![(format!("{}{}", prefix, text), true)];
+    }
+
+    let text_width = available_width - prefix_len;
+    let words: Vec<&str> = text.split_whitespace().collect();
+
+    if words.is_empty() {
+        return vec![(prefix, true)];
+    }
+
+    let mut lines = Vec::new();
     let mut current_line = String::new();
 
+    // Widths are measured in terminal columns so that wide characters (and
+    // the cursor placed between them) line up with what is drawn
     for word in words {
-        if word.len() > text_width {
+        if word.width() > text_width {
             // Handle very long words by breaking them
             if !current_line.is_empty() {
                 lines.push(current_line);
@@ -264,15 +1311,17 @@ fn wrap_todo_item_text(
             }
 
             let mut remaining = word;
-            while remaining.len() > text_width {
-                let chunk = &remaining[..text_width];
+            while remaining.width() > text_width {
+                let (chunk, rest) = split_at_width(remaining, text_width);
                 lines.push(chunk.to_string());
-                remaining = &remaining[text_width..];
+                remaining = rest;
             }
             if !remaining.is_empty() {
                 current_line = remaining.to_string();
             }
-        } else if current_line.len() + word.len() + (if current_line.is_empty() { 0 } else { 1 })
+        } else if current_line.width()
+            + word.width()
+            + (if current_line.is_empty() { 0 } else { 1 })
             > text_width
         {
             // Word doesn't fit on current line
@@ -303,7 +1352,7 @@ fn wrap_todo_item_text(
 
     // Build the result with proper formatting
     let mut result = Vec::new();
-    let continuation_prefix = format!("{}   ", indent);
+    let continuation_prefix = " ".repeat(line_prefix_chars(item, false));
 
     for (i, line) in lines.iter().enumerate() {
         if i == 0 {
@@ -316,103 +1365,746 @@ fn wrap_todo_item_text(
     result
 }
 
+// Index of the first item after `from` whose text starts with `letter`,
+// ignoring case, continuing from the top after the last item. The item at
+// `from` is only found if it's the only match.
+fn next_item_starting_with(items: &[TodoItem], from: usize, letter: char) -> Option<usize> {
+    let letter: String = letter.to_lowercase().collect();
+    let starts_with = |item: &TodoItem| {
+        item.text
+            .trim_start()
+            .chars()
+            .next()
+            .is_some_and(|first| first.to_lowercase().eq(letter.chars()))
+    };
+    (1..=items.len())
+        .map(|offset| (from + offset) % items.len())
+        .find(|&index| starts_with(&items[index]))
+}
+
+// Whether `column`, counted from the start of the main line drawn for
+// `item`, is on the `[ ]` or `[x]` after the indentation and bullet
+fn is_checkbox_column(item: &TodoItem, column: usize) -> bool {
+    let start = 2 * item.indent_level + 2;
+    item.is_task && (start..start + 3).contains(&column)
+}
+
+// Number of chars before the text on a line drawn for `item`: the indentation
+// and bullet, plus the checkbox for tasks
+fn line_prefix_chars(item: &TodoItem, is_main_line: bool) -> usize {
+    let marker = match (item.is_task, is_main_line) {
+        (true, true) => 6,
+        (true, false) => 3,
+        (false, _) => 2,
+    };
+    2 * item.indent_level + marker
+}
+
+// Splits off the longest prefix of `text` that fits into `width` columns, but
+// at least one grapheme so that callers always make progress. Graphemes are
+// measured as a whole, like the terminal draws them: an emoji sequence joined
+// with zero width joiners is two columns wide, not the sum of its parts.
+fn split_at_width(text: &str, width: usize) -> (&str, &str) {
+    let mut used = 0;
+    for (index, grapheme) in text.grapheme_indices(true) {
+        used += grapheme.width();
+        if used > width && index > 0 {
+            return text.split_at(index);
+        }
+    }
+    (text, "")
+}
+
+// Char indices where the graphemes of `text` start, followed by its length
+fn grapheme_boundaries(text: &str) -> impl Iterator<Item = usize> + '_ {
+    let mut chars = 0;
+    std::iter::once(0).chain(text.graphemes(true).map(move |grapheme| {
+        chars += grapheme.chars().count();
+        chars
+    }))
+}
+
+// Char index of the grapheme boundary before the char index `cursor`
+fn prev_grapheme_boundary(text: &str, cursor: usize) -> usize {
+    grapheme_boundaries(text)
+        .take_while(|&boundary| boundary < cursor)
+        .last()
+        .unwrap_or(0)
+}
+
+// Char index of the grapheme boundary after the char index `cursor`, or
+// `cursor` itself at the end of `text`
+fn next_grapheme_boundary(text: &str, cursor: usize) -> usize {
+    grapheme_boundaries(text)
+        .find(|&boundary| boundary > cursor)
+        .unwrap_or(cursor)
+}
+
+// Renders an item on a single line, cutting it off with `ellipsis` if it
+// doesn't fit into `available_width`
+fn truncate_todo_item_text(
+    item: &TodoItem,
+    available_width: usize,
+    ellipsis: char,
+) -> Vec<(String, bool)> {
+    let (line, _) = wrap_todo_item_text(item, usize::MAX, false, "", 0, false).remove(0);
+    if line.width() <= available_width {
+        return vec![(line, true)];
+    }
+
+    let (head, _) = split_at_width(&line, available_width.saturating_sub(1));
+    let mut truncated = head.to_string();
+    truncated.push(ellipsis);
+    vec![(truncated, true)]
+}
+
+// Cuts `text` down to `width` columns by replacing its start with `ellipsis`,
+// keeping the end of a path, which tells files apart
+fn truncate_left(text: &str, width: usize, ellipsis: char) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let mut used = 1;
+    let start = text
+        .grapheme_indices(true)
+        .rev()
+        .take_while(|(_, grapheme)| {
+            used += grapheme.width();
+            used <= width
+        })
+        .last()
+        .map_or(text.len(), |(index, _)| index);
+    format!("{}{}", ellipsis, &text[start..])
+}
+
+// Status bar label and the key bindings available in the current mode, most
+// important first. Bindings of features that are switched off are left out.
+fn key_hints(app: &App) -> (String, Vec<(&str, &'static str)>) {
+    let config = &app.todo_list.config;
+    let can_indent = config.max_indent != Some(0);
+    let ascii = config.plain_ascii;
+    match app.mode {
+        AppMode::Selection => {
+            // Auto-sort changes where inserted items end up, so make it visible
+            let label = match config.auto_sort {
+                SortMode::None => "Sel",
+                SortMode::CompletedLast => "Sel (sorted: completed last)",
+            };
+            if app.todo_list.todo_list.items.is_empty() {
+                return (label.to_string(), vec![("i", "Insert"), ("q", "Quit")]);
+            }
+
+            let mut hints = vec![
+                (if ascii { "k" } else { "↑k" }, "Up"),
+                (if ascii { "j" } else { "↓j" }, "Down"),
+                ("x", "Toggle"),
+                ("i", "Insert"),
+            ];
+            if can_indent {
+                hints.push(("I", "Insert child"));
+            }
+            if app.undoable_toggle().is_some() {
+                hints.push(("u", "Undo toggle"));
+            }
+            hints.extend([("Enter", "Edit"), ("J", "Join with next")]);
+            if can_indent {
+                if ascii {
+                    hints.push(("Tab/Ctrl+Right", "Indent"));
+                    hints.push(("Shift+Tab/Ctrl+Left", "Unindent"));
+                } else {
+                    hints.push(("Tab/Ctrl+→", "Indent"));
+                    hints.push(("Shift+Tab/Ctrl+←", "Unindent"));
+                }
+            }
+            hints.extend([
+                ("g/G", "Move top/bottom"),
+                ("'+letter", "Jump to letter"),
+                ("d", "Delete"),
+            ]);
+            if app.completed_count() > 0 {
+                hints.push(("C", "Clear completed"));
+            }
+            hints.extend([
+                ("w", "Wrap on/off"),
+                ("n", "Note"),
+                ("t", "Due date"),
+                ("Y", "Copy list"),
+                ("r", "Reload"),
+                ("Ctrl+s", "Save"),
+                ("q", "Quit"),
+            ]);
+            (label.to_string(), hints)
+        }
+        AppMode::Edit => {
+            let mut hints = vec![("Enter", "Confirm"), ("Alt+Enter", "Split")];
+            if can_indent {
+                hints.push(("Tab/Shift+Tab", "Indent"));
+            }
+            hints.extend([
+                ("Ctrl+s", "Save"),
+                ("Ctrl+t", "Show invisible"),
+                ("Ctrl+x", "Strip invisible"),
+                ("Esc", "Cancel"),
+                (if ascii { "Left/Right" } else { "←→" }, "Move cursor"),
+            ]);
+            ("Edit".to_string(), hints)
+        }
+        AppMode::EditDue => {
+            let mut input = app.edit_text.clone();
+            input.insert(byte_index(&input, app.edit_cursor), CURSOR);
+            (
+                format!("Due: {}", input),
+                vec![("Enter", "Set (empty clears)"), ("Esc", "Cancel")],
+            )
+        }
+        AppMode::EditNote => (
+            "Note".to_string(),
+            vec![
+                ("Enter", "Confirm"),
+                ("Esc", "Cancel"),
+                (if ascii { "Left/Right" } else { "←→" }, "Move cursor"),
+            ],
+        ),
+        AppMode::ConfirmQuit => (
+            format!("{} tasks remaining, quit anyway?", app.incomplete_count()),
+            vec![("y", "Quit"), ("n/Esc", "Back")],
+        ),
+        AppMode::ConfirmReload => (
+            "Changes have not been saved, reload anyway?".to_string(),
+            vec![("y", "Reload"), ("n/Esc", "Back")],
+        ),
+        AppMode::ConfirmUnindent => (
+            {
+                let orphaned = app.orphaned_by_unindent();
+                let subtasks = if orphaned == 1 { "subtask" } else { "subtasks" };
+                format!("This will orphan {} {}, continue?", orphaned, subtasks)
+            },
+            vec![("y", "Unindent"), ("n/Esc", "Back")],
+        ),
+        AppMode::JumpToLetter => (
+            "Jump to".to_string(),
+            vec![("letter", "Next item starting with it"), ("Esc", "Cancel")],
+        ),
+        AppMode::Delete => {
+            let confirm = config
+                .delete_confirm_keys
+                .iter()
+                .map(|key| (key.as_str(), "Confirm Delete"));
+            let cancel = config
+                .delete_cancel_keys
+                .iter()
+                .map(|key| (key.as_str(), "Cancel"));
+            ("Delete".to_string(), confirm.chain(cancel).collect())
+        }
+        AppMode::ClearCompleted => {
+            let remaining = app.required_clear_confirmations() - app.clear_confirmations;
+            let confirm = if remaining > 1 {
+                "Confirm (press twice)"
+            } else {
+                "Confirm"
+            };
+            (
+                format!("Delete {} completed items?", app.completed_count()),
+                vec![("y", confirm), ("Esc", "Cancel")],
+            )
+        }
+    }
+}
+
+// Builds the key hint lines for the status bar, continuing on the next of
+// up to `lines` lines when a hint doesn't fit into `width` columns and
+// leaving out the hints that fit nowhere
+fn status_hints(app: &App, width: usize, lines: usize) -> String {
+    let (label, hints) = key_hints(app);
+    let mut rows = vec![label];
+    for (key, label) in hints {
+        let hint = format!("{}:{}", key, label);
+        let last = rows.len() - 1;
+        if rows[last].width() + " | ".len() + hint.width() <= width {
+            rows[last].push_str(" | ");
+            rows[last].push_str(&hint);
+        } else if rows.len() < lines && hint.width() <= width {
+            rows.push(hint);
+        } else {
+            break;
+        }
+    }
+    rows.join("\n")
+}
+
+/// An item's text as drawn in the list
+#[derive(Debug, Clone, PartialEq)]
+struct WrappedItem {
+    /// The text with links replaced by their labels
+    display_text: String,
+    /// Whether each char of `display_text` belongs to a link label
+    link_flags: Vec<bool>,
+    /// Screen lines, each with whether it is the item's first line
+    lines: Vec<(String, bool)>,
+}
+
+/// Everything `wrap_item` depends on, to tell whether a cached result for
+/// an item is still valid
+#[derive(Debug, Clone, PartialEq)]
+struct WrapKey {
+    text: String,
+    is_task: bool,
+    completed: bool,
+    indent_level: usize,
+    available_width: usize,
+    wrap_text: bool,
+    is_selected: bool,
+    /// Edit text, cursor and invisible characters setting while editing
+    editing: Option<(String, usize, bool)>,
+}
+
+impl WrapKey {
+    fn new(app: &App, index: usize, available_width: usize) -> Self {
+        let item = &app.todo_list.todo_list.items[index];
+        let is_selected = index == app.selected_index;
+        let is_editing = app.mode == AppMode::Edit && is_selected;
+        WrapKey {
+            text: item.text.clone(),
+            is_task: item.is_task,
+            completed: item.completed,
+            indent_level: item.indent_level,
+            available_width,
+            wrap_text: app.wrap_text,
+            is_selected,
+            editing: is_editing
+                .then(|| (app.edit_text.clone(), app.edit_cursor, app.show_invisible)),
+        }
+    }
+}
+
+// Wrapped lines of the item at `index`, taken from the cache unless the item
+// or anything else its wrapping depends on has changed since
+fn cached_wrap_item(app: &App, index: usize, available_width: usize) -> Rc<WrappedItem> {
+    let key = WrapKey::new(app, index, available_width);
+    let mut cache = app.wrap_cache.borrow_mut();
+    match cache.get(&index) {
+        Some((cached_key, wrapped)) if *cached_key == key => Rc::clone(wrapped),
+        _ => {
+            let wrapped = Rc::new(wrap_item(app, index, available_width));
+            cache.insert(index, (key, Rc::clone(&wrapped)));
+            wrapped
+        }
+    }
+}
+
+// Wraps (or truncates) the item at `index` for the list
+fn wrap_item(app: &App, index: usize, available_width: usize) -> WrappedItem {
+    let item = &app.todo_list.todo_list.items[index];
+    let is_selected = index == app.selected_index;
+    let is_editing = app.mode == AppMode::Edit && is_selected;
+
+    // Links show just their label, except while the raw text is edited
+    let (display_text, link_flags) = if is_editing {
+        (item.text.clone(), Vec::new())
+    } else {
+        render_links(&item.text)
+    };
+    let display_item = TodoItem {
+        text: display_text,
+        ..item.clone()
+    };
+
+    // The item being edited always wraps so the cursor stays visible
+    let lines = if app.wrap_text || is_editing {
+        let edit_text = if app.show_invisible {
+            show_invisible(&app.edit_text)
+        } else {
+            app.edit_text.clone()
+        };
+        wrap_todo_item_text(
+            &display_item,
+            available_width,
+            is_selected,
+            &edit_text,
+            app.edit_cursor,
+            is_editing,
+        )
+    } else {
+        let ellipsis = if app.todo_list.config.plain_ascii {
+            '~'
+        } else {
+            '…'
+        };
+        truncate_todo_item_text(&display_item, available_width, ellipsis)
+    };
+
+    WrappedItem {
+        display_text: display_item.text,
+        link_flags,
+        lines,
+    }
+}
+
+// Picks the items shown in a list `height` rows high: starting at the previous
+// scroll offset, moved just far enough to keep the selected item in view.
+// Only these items get wrapped, so drawing doesn't slow down with the length
+// of the list. Returns the new scroll offset and the wrapped visible items.
+fn visible_items(
+    app: &App,
+    height: usize,
+    mut wrap: impl FnMut(usize) -> Rc<WrappedItem>,
+) -> (usize, Vec<(usize, Rc<WrappedItem>)>) {
+    let len = app.todo_list.todo_list.items.len();
+    let selected = app.selected_index.min(len);
+    let offset = app.scroll_offset.get().min(selected);
+
+    // Walk up from the selected item (or the insertion marker past the end)
+    // to the old offset, unless the screen is full before
+    let mut visible = VecDeque::new();
+    let mut rows = 1;
+    if selected < len {
+        let wrapped = wrap(selected);
+        rows = wrapped.lines.len();
+        visible.push_back((selected, wrapped));
+    }
+    let mut start = selected;
+    while start > offset {
+        let wrapped = wrap(start - 1);
+        if rows + wrapped.lines.len() > height {
+            break;
+        }
+        rows += wrapped.lines.len();
+        start -= 1;
+        visible.push_front((start, wrapped));
+    }
+
+    // Fill the rest of the screen with the items below
+    let mut next = selected + 1;
+    while rows < height && next < len {
+        let wrapped = wrap(next);
+        rows += wrapped.lines.len();
+        visible.push_back((next, wrapped));
+        next += 1;
+    }
+
+    (start, visible.into())
+}
+
+// Title suffix and border style of the list while editing or deleting, so
+// the mode shows at a glance; `None` in the other modes or when disabled
+fn mode_border(app: &App, palette: &Palette) -> Option<(&'static str, Style)> {
+    if !app.todo_list.config.mode_border {
+        return None;
+    }
+    match app.mode {
+        AppMode::Edit | AppMode::EditNote => Some(("EDIT", palette.edit_border)),
+        AppMode::Delete | AppMode::ClearCompleted => Some(("DELETE", palette.delete_border)),
+        AppMode::Selection
+        | AppMode::EditDue
+        | AppMode::ConfirmQuit
+        | AppMode::ConfirmReload
+        | AppMode::ConfirmUnindent
+        | AppMode::JumpToLetter => None,
+    }
+}
+
+// The note being edited, with the cursor drawn in
+fn note_with_cursor(app: &App) -> String {
+    let mut text = if app.show_invisible {
+        show_invisible(&app.edit_text)
+    } else {
+        app.edit_text.clone()
+    };
+    text.insert(byte_index(&text, app.edit_cursor), CURSOR);
+    text
+}
+
+/// Lists with more items than this get a warning on startup, as they are
+/// unwieldy to work with
+const LARGE_LIST_WARNING: usize = 1000;
+
+/// Smallest terminal the list can be drawn in: the bordered list needs room
+/// for at least one row and a checkbox with some text, plus the status bar
+const MIN_TERMINAL_WIDTH: u16 = 16;
+const MIN_TERMINAL_HEIGHT: u16 = 4;
+
+fn fits_terminal(area: Rect) -> bool {
+    area.width >= MIN_TERMINAL_WIDTH && area.height >= MIN_TERMINAL_HEIGHT
+}
+
+// Text `Y` puts on the clipboard: the file contents, or only the item texts
+// for pasting into chats that don't render markdown
+fn list_for_clipboard(todo_list: &TodoList, format: ClipboardFormat) -> String {
+    match format {
+        ClipboardFormat::Markdown => todo_list.to_markdown(),
+        ClipboardFormat::Plain => todo_list
+            .items
+            .iter()
+            .map(|item| format!("{}{}\n", "  ".repeat(item.indent_level), item.text))
+            .collect(),
+    }
+}
+
+// Due date typed into the prompt of `t`: YYYY-MM-DD, `today`, `tomorrow` or
+// `+N` days from `today`. Nothing means no due date.
+fn parse_due_input(input: &str, today: NaiveDate) -> Result<Option<NaiveDate>, String> {
+    let input = input.trim();
+    let date = match input.to_lowercase().as_str() {
+        "" => return Ok(None),
+        "today" => Some(today),
+        "tomorrow" => today.succ_opt(),
+        _ => match input.strip_prefix('+') {
+            Some(days) => days
+                .parse()
+                .ok()
+                .and_then(|days| today.checked_add_days(chrono::Days::new(days))),
+            None => NaiveDate::parse_from_str(input, "%Y-%m-%d").ok(),
+        },
+    };
+    date.map(Some).ok_or_else(|| {
+        format!(
+            "Invalid due date '{}', expected YYYY-MM-DD, +N, today or tomorrow",
+            input
+        )
+    })
+}
+
+// Marker drawn in a gutter left of the selected item's main line, if any.
+// Without colors it helps reverse video point out the selection.
+fn selection_marker(config: &Config) -> Option<&str> {
+    let fallback = config.plain_ascii || config.color == ColorMode::Never;
+    match config.selection_marker.as_deref() {
+        Some("") => None,
+        Some(marker) => Some(marker),
+        None => fallback.then_some(ASCII_SELECTION_MARKER),
+    }
+}
+
+// Bordered block around the list, drawn with ASCII characters in plain
+// ASCII mode
+fn bordered_block(config: &Config) -> Block<'static> {
+    let block = Block::default().borders(Borders::ALL);
+    if config.plain_ascii {
+        block.border_set(ASCII_BORDER)
+    } else {
+        block
+    }
+}
+
+// Draws `text` alone on a line in the middle of the screen
+fn centered_message(f: &mut Frame, text: &str) {
+    let area = f.area();
+    let middle = Rect {
+        y: area.y + area.height / 2,
+        height: area.height.min(1),
+        ..area
+    };
+    let message = Paragraph::new(text).alignment(Alignment::Center);
+    f.render_widget(message, middle);
+}
+
 fn ui(f: &mut Frame, app: &App) {
+    app.drawn_rows.borrow_mut().clear();
+    if let Some(greeting) = &app.greeting {
+        centered_message(f, greeting);
+        return;
+    }
+    if !fits_terminal(f.area()) {
+        // Drawn again normally as soon as the terminal gets resized
+        centered_message(f, "Terminal too small");
+        return;
+    }
+
+    let palette = Palette::for_config(&app.todo_list.config);
+    let banner_height = u16::from(app.last_save_error.is_some());
+    let status_lines = app.todo_list.config.status_lines;
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+        .constraints(
+            [
+                Constraint::Min(0),
+                Constraint::Length(banner_height),
+                Constraint::Length(status_lines),
+            ]
+            .as_ref(),
+        )
         .split(f.area());
 
     // Main todo list area
     let todo_area = chunks[0];
-    let status_area = chunks[1];
+    let status_area = chunks[2];
+
+    if let Some(err) = &app.last_save_error {
+        let banner = Paragraph::new(format!("SAVE FAILED: {}", err)).style(palette.status_error);
+        f.render_widget(banner, chunks[1]);
+    }
 
     // Render todo list
-    let title = format!("TODO {}", app.todo_list.todo_list.date.format("%Y-%m-%d"));
+    let title = app.todo_list.display_title();
 
     // Calculate available width for text (accounting for borders and padding)
-    let available_width = todo_area.width.saturating_sub(4) as usize; // 2 for borders, 2 for padding
+    // The selection marker gutter shifts all lines right
+    let marker = selection_marker(&app.todo_list.config);
+    let marker_width = marker.map_or(0, |marker| marker.width() as u16);
+    let available_width = todo_area.width.saturating_sub(4 + marker_width) as usize; // 2 for borders, 2 for padding
+
+    // The note of the day takes the first row inside the border
+    let note = if app.mode == AppMode::EditNote {
+        Some(note_with_cursor(app))
+    } else {
+        app.todo_list.todo_list.note.clone()
+    };
+    let note_height = u16::from(note.is_some());
 
     let mut display_items = Vec::new();
-    let mut logical_to_display_map = Vec::new(); // Maps logical item index to display item indices
+    let mut selected_display_index = None;
+    let items = &app.todo_list.todo_list.items;
 
-    if app.todo_list.todo_list.items.is_empty() {
+    let mut drawn_rows = app.drawn_rows.borrow_mut();
+    if items.is_empty() {
         display_items.push(ListItem::new("No items"));
-        logical_to_display_map.push(vec![0]);
+        drawn_rows.push((0, false));
+        selected_display_index = Some(0);
     } else {
-        for (logical_index, item) in app.todo_list.todo_list.items.iter().enumerate() {
+        let list_height = todo_area.height.saturating_sub(2 + note_height) as usize; // 2 for borders
+        // Entries of items that no longer exist would never be used again
+        app.wrap_cache
+            .borrow_mut()
+            .retain(|&index, _| index < items.len());
+        let (offset, visible) = visible_items(app, list_height, |index| {
+            cached_wrap_item(app, index, available_width)
+        });
+        app.scroll_offset.set(offset);
+
+        for (logical_index, wrapped) in visible {
+            let item = &items[logical_index];
             let is_selected = logical_index == app.selected_index;
-            let is_editing = app.mode == AppMode::Edit && is_selected;
-            let is_delete_mode = app.mode == AppMode::Delete && is_selected;
-
-            let wrapped_lines = wrap_todo_item_text(
-                item,
-                available_width,
-                is_selected,
-                &app.edit_text,
-                app.edit_cursor,
-                is_editing,
-            );
-
-            let start_display_index = display_items.len();
-            let mut display_indices = Vec::new();
-
-            for (line_index, (line_text, is_main_line)) in wrapped_lines.iter().enumerate() {
+            let is_delete_mode = (app.mode == AppMode::Delete && is_selected)
+                || (app.mode == AppMode::ClearCompleted && item.completed);
+
+            if is_selected {
+                selected_display_index = Some(display_items.len());
+            }
+            let mut link_chars = wrapped
+                .display_text
+                .chars()
+                .zip(wrapped.link_flags.iter().copied())
+                .filter(|(c, _)| !c.is_whitespace());
+
+            for (line_text, is_main_line) in &wrapped.lines {
                 let style = if is_delete_mode && *is_main_line {
-                    Style::default().bg(Color::Red).fg(Color::White)
+                    palette.deleting
                 } else if is_selected && *is_main_line {
-                    Style::default().bg(Color::DarkGray).fg(Color::White)
+                    palette.selected
                 } else if item.completed {
-                    Style::default().fg(Color::DarkGray)
+                    palette.completed
+                } else {
+                    let mut style = Style::default();
+                    if app.todo_list.config.indent_colors {
+                        style = style.fg(palette.indent_color(item.indent_level));
+                    }
+                    // Deferral warnings win over the indentation colors
+                    if let Some(color) = palette.deferral_color(item.deferred_count()) {
+                        style = style.fg(color);
+                    }
+                    if item.carried {
+                        // Still open from the day before
+                        style = style.add_modifier(Modifier::ITALIC | Modifier::DIM);
+                    }
+                    style
+                };
+                let style = if item.completed && app.todo_list.config.strikethrough_completed {
+                    style.add_modifier(Modifier::CROSSED_OUT)
                 } else {
-                    Style::default()
+                    style
                 };
 
-                display_items.push(ListItem::new(line_text.clone()).style(style));
-                display_indices.push(start_display_index + line_index);
+                let prefix_chars = line_prefix_chars(item, *is_main_line);
+                let line = style_links(line_text, prefix_chars, &mut link_chars, palette.link);
+                display_items.push(ListItem::new(line).style(style));
+                drawn_rows.push((logical_index, *is_main_line));
             }
-
-            logical_to_display_map.push(display_indices);
         }
 
         // Add a virtual item for insertion past the last item
-        if app.selected_index == app.todo_list.todo_list.items.len() {
-            let style = Style::default().bg(Color::DarkGray).fg(Color::Yellow);
-            display_items.push(ListItem::new("--- Insert new item here ---").style(style));
-            logical_to_display_map.push(vec![display_items.len() - 1]);
+        if app.selected_index == items.len() {
+            selected_display_index = Some(display_items.len());
+            display_items
+                .push(ListItem::new("--- Insert new item here ---").style(palette.insert_marker));
+            drawn_rows.push((items.len(), false));
         }
     }
-
-    // Calculate which display item should be selected
-    let selected_display_index = if app.selected_index < logical_to_display_map.len() {
-        logical_to_display_map[app.selected_index].first().copied()
-    } else {
-        None
+    drop(drawn_rows);
+
+    let config = &app.todo_list.config;
+    let mut block = bordered_block(config);
+    let title = match mode_border(app, &palette) {
+        Some((label, style)) => {
+            block = block.border_style(style);
+            format!("{}{}{}", title, config.title_separator(), label)
+        }
+        None => title,
     };
+    let title_width = title.width();
+    block = block.title(title);
+    if config.show_file_path {
+        // Room left of the corner after the title and a gap
+        let width = (todo_area.width as usize).saturating_sub(title_width + 4);
+        let ellipsis = if config.plain_ascii { '~' } else { '…' };
+        let path = std::path::absolute(app.todo_list.file_path())
+            .unwrap_or_else(|_| app.todo_list.file_path());
+        let path = truncate_left(&path.display().to_string(), width, ellipsis);
+        block = block.title(Line::from(path).right_aligned());
+    }
+    let inner = block.inner(todo_area);
+    f.render_widget(block, todo_area);
+    let inner_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(note_height), Constraint::Min(0)].as_ref())
+        .split(inner);
+
+    if let Some(note) = note {
+        let line = format!("> {}", note);
+        // Keep the cursor in view while editing a note wider than the list
+        let scroll = if app.mode == AppMode::EditNote {
+            let cursor_column = 2 + note[..byte_index(&note, app.edit_cursor)].width();
+            (cursor_column + 1).saturating_sub(inner.width as usize) as u16
+        } else {
+            0
+        };
+        let paragraph = Paragraph::new(line)
+            .style(Style::default().add_modifier(Modifier::ITALIC))
+            .scroll((0, scroll));
+        f.render_widget(paragraph, inner_chunks[0]);
+    }
 
-    let todo_list = List::new(display_items)
-        .block(Block::default().borders(Borders::ALL).title(title))
-        .highlight_style(Style::default().bg(Color::DarkGray));
+    let mut todo_list = List::new(display_items).highlight_style(palette.selected);
+    if let Some(marker) = marker {
+        todo_list = todo_list.highlight_symbol(marker);
+    }
 
     let mut list_state = ListState::default();
     list_state.select(selected_display_index);
 
-    f.render_stateful_widget(todo_list, todo_area, &mut list_state);
+    f.render_stateful_widget(todo_list, inner_chunks[1], &mut list_state);
+    // Rows are drawn right of the selection marker gutter
+    let list_area = inner_chunks[1];
+    app.list_area.set(Rect {
+        x: list_area.x + marker_width,
+        width: list_area.width.saturating_sub(marker_width),
+        ..list_area
+    });
 
     // Status bar
-    let status_text = match app.mode {
-        AppMode::Selection => {
-            if app.todo_list.todo_list.items.is_empty() {
-                "Sel | i:Insert | q:Quit"
-            } else {
-                "Sel | ↑k:Up | ↓j:Down | x:Toggle | i:Insert | Enter:Edit | Tab:Indent | Shift+Tab:Unindent | d:Delete | q:Quit"
-            }
-        }
-        AppMode::Edit => "Edit | Enter:Confirm | Esc:Cancel | ←→:Move cursor",
-        AppMode::Delete => "Delete | y:Confirm Delete | Esc:Cancel",
+    let (status_text, status_style) = match &app.status_message {
+        Some(message) if message.is_error => (message.text.clone(), palette.status_error),
+        Some(message) => (message.text.clone(), palette.status),
+        None => (
+            status_hints(app, status_area.width as usize, status_lines.into()),
+            palette.status,
+        ),
     };
 
     let status_paragraph = Paragraph::new(status_text)
-        .style(Style::default().bg(Color::Blue).fg(Color::White))
+        .style(status_style)
         .wrap(ratatui::widgets::Wrap { trim: true });
 
     f.render_widget(status_paragraph, status_area);
@@ -422,13 +2114,41 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, mut app: App)
     loop {
         terminal.draw(|f| ui(f, &app))?;
 
-        if let Event::Key(key) = event::read()? {
-            if key.kind == KeyEventKind::Press {
-                if let Err(err) = app.handle_key_event(key.code) {
-                    eprintln!("Error handling key event: {}", err);
-                }
+        if std::mem::take(&mut app.ring_bell) {
+            execute!(terminal.backend_mut(), Print('\x07'))?;
+        }
+
+        // Wake up when the status message expires so it gets cleared, and
+        // regularly to notice a new day
+        let timeout = [
+            app.status_message_remaining(Instant::now()),
+            app.rollover_check_interval(),
+        ]
+        .into_iter()
+        .flatten()
+        .min();
+        let has_event = match timeout {
+            Some(timeout) => event::poll(timeout)?,
+            None => true,
+        };
+        if has_event {
+            let result = match event::read()? {
+                // `handle_key_event` decides which key event kinds to act on
+                Event::Key(key) => app.handle_key_event(key),
+                Event::Paste(text) => app.handle_paste(&text),
+                Event::Mouse(mouse) => app.handle_mouse_event(mouse),
+                _ => Ok(()),
+            };
+            match result {
+                // stderr isn't visible behind the alternate screen
+                Err(err) => app.report_error(err),
+                Ok(()) => app.clear_save_error_if_saved(),
             }
         }
+        app.clear_expired_status_message(Instant::now());
+        if let Err(err) = app.check_rollover(Local::now().date_naive()) {
+            app.notify_error(err.to_string());
+        }
 
         if app.should_quit() {
             break;
@@ -438,27 +2158,115 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, mut app: App)
     Ok(())
 }
 
-pub fn run_tui(todo_list: TodoApp) -> Result<(), Box<dyn Error>> {
-    // Setup terminal
-    enable_raw_mode()?;
-    let mut stdout = std::io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-
-    // Create app
-    let app = App::new(todo_list);
+// Minimal screen for `todui capture`: just the item being written and the
+// status bar
+fn capture_ui(f: &mut Frame, app: &App, stay: bool) {
+    let palette = Palette::for_config(&app.todo_list.config);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+        .split(f.area());
+
+    let config = &app.todo_list.config;
+    let title = format!(
+        "Capture{}{}",
+        config.title_separator(),
+        app.todo_list.display_title()
+    );
+    let available_width = chunks[0].width.saturating_sub(4) as usize;
+    let lines: Vec<Line> = match app.todo_list.todo_list.items.get(app.selected_index) {
+        Some(_) => wrap_item(app, app.selected_index, available_width)
+            .lines
+            .into_iter()
+            .map(|(text, _)| Line::from(text))
+            .collect(),
+        None => Vec::new(),
+    };
+    let prompt = Paragraph::new(lines).block(bordered_block(config).title(title));
+    f.render_widget(prompt, chunks[0]);
+
+    let (status_text, status_style) = match &app.status_message {
+        Some(message) if message.is_error => (message.text.clone(), palette.status_error),
+        Some(message) => (message.text.clone(), palette.status),
+        None if stay => (
+            "Capture | Enter:Save, next | Enter on empty item:Quit | Esc:Quit".to_string(),
+            palette.status,
+        ),
+        None => (
+            "Capture | Enter:Save and quit | Esc:Quit".to_string(),
+            palette.status,
+        ),
+    };
+    f.render_widget(Paragraph::new(status_text).style(status_style), chunks[1]);
+}
+
+// Stripped-down version of `run_app` for `todui capture`, running until the
+// capture is confirmed or cancelled
+fn run_capture_loop(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    mut app: App,
+    stay: bool,
+) -> io::Result<()> {
+    while !app.should_quit {
+        terminal.draw(|f| capture_ui(f, &app, stay))?;
+        let result = match event::read()? {
+            Event::Key(key) => app.handle_capture_key(key, stay),
+            Event::Paste(text) => app.handle_paste(&text),
+            _ => Ok(()),
+        };
+        if let Err(err) = result {
+            app.notify_error(err.to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Runs the terminal UI, starting with a greeting if `greet` is set. Without
+/// `alt_screen` it draws in the normal screen buffer and leaves the final
+/// state in the scrollback on exit.
+pub fn run_tui(todo_list: TodoApp, greet: bool, alt_screen: bool) -> Result<(), Box<dyn Error>> {
+    let mut app = App::new(todo_list);
+    if greet {
+        app.greet();
+    }
+    in_terminal(alt_screen, |terminal| run_app(terminal, app))
+}
+
+/// Edits a new item at the end of the list, saves it on Enter and quits, or
+/// with `stay` keeps capturing items until one is confirmed empty
+pub fn run_capture(todo_list: TodoApp, stay: bool) -> Result<(), Box<dyn Error>> {
+    let mut app = App::new(todo_list);
+    // The list isn't shown, so warnings about it would only get in the way
+    app.status_message = None;
+    app.start_capture();
+    in_terminal(true, |terminal| run_capture_loop(terminal, app, stay))
+}
+
+// Runs `run` on the alternate screen, or in the normal buffer without
+// `alt_screen`, and restores the terminal afterwards
+fn in_terminal(
+    alt_screen: bool,
+    run: impl FnOnce(&mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<()>,
+) -> Result<(), Box<dyn Error>> {
+    // Setup terminal
+    enable_raw_mode()?;
+    let mut stdout = std::io::stdout();
+    enter_screen(&mut stdout, alt_screen)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+    if !alt_screen {
+        // The first frame only draws over cells that aren't blank, so the
+        // shell output above would show through
+        terminal.clear()?;
+    }
 
     // Run the app
-    let res = run_app(&mut terminal, app);
+    let res = run(&mut terminal);
 
     // Restore terminal
     disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
+    let rows = terminal.size()?.height;
+    leave_screen(terminal.backend_mut(), alt_screen, rows)?;
 
     if let Err(err) = res {
         println!("Error: {:?}", err);
@@ -467,16 +2275,74 @@ pub fn run_tui(todo_list: TodoApp) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+// Switches to the alternate screen if asked and turns on mouse capture and
+// bracketed paste
+fn enter_screen(out: &mut impl io::Write, alt_screen: bool) -> io::Result<()> {
+    if alt_screen {
+        queue!(out, EnterAlternateScreen)?;
+    }
+    execute!(out, EnableMouseCapture, EnableBracketedPaste)
+}
+
+// Undoes `enter_screen`. In the normal buffer the last frame stays visible,
+// and the cursor moves below it so the shell prompt doesn't overwrite it.
+fn leave_screen(out: &mut impl io::Write, alt_screen: bool, rows: u16) -> io::Result<()> {
+    queue!(out, DisableMouseCapture, DisableBracketedPaste)?;
+    if alt_screen {
+        queue!(out, LeaveAlternateScreen)?;
+    } else {
+        queue!(out, MoveTo(0, rows.saturating_sub(1)), Print("\r\n"))?;
+    }
+    out.flush()
+}
+
 #[cfg(test)]
 mod test {
-    use std::path::PathBuf;
+    use std::{
+        path::PathBuf,
+        rc::Rc,
+        time::{Duration, Instant},
+    };
+
+    use chrono::{Local, NaiveDate};
+    use crossterm::event::{
+        KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers, MouseButton, MouseEvent,
+        MouseEventKind,
+    };
+
+    use crate::{
+        TodoApp, TodoList,
+        config::{
+            ClipboardFormat, ColorMode, EmptyConfirm, MultilinePaste, RolloverMode, SortMode,
+            UnindentSubitems,
+        },
+        tui::CURSOR,
+    };
 
-    use chrono::NaiveDate;
-    use crossterm::event::KeyCode;
+    use ratatui::{
+        Terminal,
+        backend::TestBackend,
+        layout::Rect,
+        style::{Color, Modifier},
+    };
 
-    use crate::{TodoApp, TodoList, tui::CURSOR};
+    use super::{
+        App, AppMode, InlineSegment, LARGE_LIST_WARNING, STATUS_MESSAGE_TIMEOUT, TodoItem,
+        capture_ui, enter_screen, fits_terminal, is_checkbox_column, leave_screen,
+        list_for_clipboard, next_item_starting_with, parse_due_input, parse_inline_links,
+        show_invisible, status_hints, strip_invisible, truncate_left, truncate_todo_item_text, ui,
+        visible_items, wrap_item, wrap_todo_item_text,
+    };
 
-    use super::{App, AppMode, TodoItem, wrap_todo_item_text};
+    // Renders the app into a test terminal and returns the screen rows
+    fn render(app: &App, width: u16, height: u16) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|f| ui(f, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..height)
+            .map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect())
+            .collect()
+    }
 
     #[test]
     fn test_wrap_todo_item_text_editing() {
@@ -574,6 +2440,50 @@ mod test {
         assert_eq!(app.todo_list.todo_list.items[0].text, "Item 2"); // Remaining item should be "Item 2"
     }
 
+    #[test]
+    fn test_configured_delete_keys() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        for text in ["Item 1", "Item 2", "Item 3"] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), false, 0));
+        }
+        let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+
+        // Enter confirms by default
+        app.handle_key_event(KeyCode::Char('d')).unwrap();
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        assert_eq!(app.mode, AppMode::Selection);
+        assert_eq!(app.todo_list.todo_list.items.len(), 2);
+
+        app.todo_list.config.delete_confirm_keys = vec!["j".to_string()];
+        app.todo_list.config.delete_cancel_keys = vec!["n".to_string()];
+        app.handle_key_event(KeyCode::Char('d')).unwrap();
+
+        // Unrelated keys, including the defaults that were replaced, do nothing
+        for code in [
+            KeyCode::Char('y'),
+            KeyCode::Enter,
+            KeyCode::Esc,
+            KeyCode::Char('x'),
+        ] {
+            app.handle_key_event(code).unwrap();
+            assert_eq!(app.mode, AppMode::Delete);
+        }
+        assert_eq!(app.todo_list.todo_list.items.len(), 2);
+
+        app.handle_key_event(KeyCode::Char('n')).unwrap();
+        assert_eq!(app.mode, AppMode::Selection);
+        assert_eq!(app.todo_list.todo_list.items.len(), 2);
+
+        app.handle_key_event(KeyCode::Char('d')).unwrap();
+        app.handle_key_event(KeyCode::Char('j')).unwrap();
+        assert_eq!(app.mode, AppMode::Selection);
+        assert_eq!(app.todo_list.todo_list.items.len(), 1);
+        assert_eq!(app.todo_list.todo_list.items[0].text, "Item 3");
+    }
+
     #[test]
     fn test_delete_last_item_adjusts_selection() {
         let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
@@ -825,6 +2735,47 @@ mod test {
         assert!(wrapped[0].0.contains(&term)); // Cursor should be positioned correctly
     }
 
+    #[test]
+    fn test_selection_is_clamped_after_list_shrinks() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let new_app = || {
+            let mut todo_list = TodoList::new(date);
+            for (text, completed) in [("A", false), ("B", true), ("C", true)] {
+                todo_list
+                    .items
+                    .push(TodoItem::new(text.to_string(), completed, 0));
+            }
+            App::new(TodoApp::new(PathBuf::new(), todo_list))
+        };
+
+        // Clearing completed items leaves the insertion row selected
+        let mut app = new_app();
+        app.selected_index = 3;
+        app.handle_key_event(KeyCode::Char('C')).unwrap();
+        app.handle_key_event(KeyCode::Char('y')).unwrap();
+        assert_eq!(app.todo_list.todo_list.items.len(), 1);
+        assert_eq!(app.selected_index, 1);
+
+        // Deleting with a stale selection past the end selects the last item
+        let mut app = new_app();
+        app.handle_key_event(KeyCode::Char('d')).unwrap();
+        app.todo_list.todo_list.items.truncate(1);
+        app.selected_index = 5;
+        app.handle_key_event(KeyCode::Char('y')).unwrap();
+        assert_eq!(app.selected_index, 0);
+
+        // Cancelling an edit whose item went away doesn't panic
+        let mut app = new_app();
+        app.selected_index = 2;
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        app.todo_list.todo_list.items.truncate(1);
+        app.handle_key_event(KeyCode::Esc).unwrap();
+        assert_eq!(app.mode, AppMode::Selection);
+        assert_eq!(app.selected_index, 1);
+        app.handle_key_event(KeyCode::Char('x')).unwrap();
+        assert!(!app.todo_list.todo_list.items[0].completed);
+    }
+
     #[test]
     fn test_tab_change_indent_level() {
         let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
@@ -937,7 +2888,7 @@ mod test {
     }
 
     #[test]
-    fn test_tab_indentation_only_works_in_selection_mode() {
+    fn test_tab_indentation_in_edit_and_delete_modes() {
         let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
         let mut todo_list = TodoList::new(date);
         todo_list
@@ -947,15 +2898,6 @@ mod test {
         let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
         app.selected_index = 0;
 
-        // Test that Tab doesn't work in Edit mode
-        app.mode = AppMode::Edit;
-        app.edit_text = "editing".to_string();
-        app.edit_cursor = 7;
-
-        assert_eq!(app.todo_list.todo_list.items[0].indent_level, 0);
-        app.handle_key_event(KeyCode::Tab).unwrap();
-        assert_eq!(app.todo_list.todo_list.items[0].indent_level, 0); // Should remain unchanged
-
         // Test that Tab doesn't work in Delete mode
         app.mode = AppMode::Delete;
         app.handle_key_event(KeyCode::Tab).unwrap();
@@ -965,6 +2907,45 @@ mod test {
         app.mode = AppMode::Selection;
         app.handle_key_event(KeyCode::Tab).unwrap();
         assert_eq!(app.todo_list.todo_list.items[0].indent_level, 1); // Should increase
+
+        // Test that Tab and Shift+Tab work in Edit mode without touching the buffer
+        app.mode = AppMode::Edit;
+        app.edit_text = "editing".to_string();
+        app.edit_cursor = 7;
+        app.handle_key_event(KeyCode::Tab).unwrap();
+        assert_eq!(app.todo_list.todo_list.items[0].indent_level, 2);
+        app.handle_key_event(KeyCode::BackTab).unwrap();
+        assert_eq!(app.todo_list.todo_list.items[0].indent_level, 1);
+        assert_eq!(app.mode, AppMode::Edit);
+        assert_eq!(app.edit_text, "editing");
+        assert_eq!(app.edit_cursor, 7);
+    }
+
+    #[test]
+    fn test_insert_then_nest_in_edit_mode() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("Parent".to_string(), false, 0));
+
+        let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+        app.selected_index = 1;
+
+        app.handle_key_event(KeyCode::Char('i')).unwrap();
+        app.handle_key_event(KeyCode::Tab).unwrap();
+        app.handle_key_event(KeyCode::Char('C')).unwrap();
+        app.handle_key_event(KeyCode::Enter).unwrap();
+
+        assert_eq!(app.mode, AppMode::Selection);
+        assert_eq!(app.todo_list.todo_list.items[1].text, "C");
+        assert_eq!(app.todo_list.todo_list.items[1].indent_level, 1);
+
+        // Unindenting stops at level 0
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        app.handle_key_event(KeyCode::BackTab).unwrap();
+        app.handle_key_event(KeyCode::BackTab).unwrap();
+        assert_eq!(app.todo_list.todo_list.items[1].indent_level, 0);
     }
 
     #[test]
@@ -1009,4 +2990,2188 @@ mod test {
         let _result = app.handle_key_event(KeyCode::BackTab);
         assert_eq!(app.todo_list.todo_list.items[0].indent_level, 0);
     }
+
+    #[test]
+    fn test_alt_enter_splits_item_at_cursor() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("Parent".to_string(), false, 0));
+        todo_list.items.push(TodoItem::new(
+            "Käse kaufen Brot backen".to_string(),
+            false,
+            1,
+        ));
+        todo_list
+            .items
+            .push(TodoItem::new("Last".to_string(), false, 0));
+
+        let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+        app.selected_index = 1;
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        app.edit_cursor = 12; // Before "Brot", after the umlaut-containing first part
+
+        app.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::ALT))
+            .unwrap();
+
+        // Still editing, now on the newly created item
+        assert_eq!(app.mode, AppMode::Edit);
+        assert_eq!(app.selected_index, 2);
+        assert_eq!(app.edit_text, "Brot backen");
+        assert_eq!(app.edit_cursor, 0);
+
+        let items = &app.todo_list.todo_list.items;
+        assert_eq!(items.len(), 4);
+        assert_eq!(items[1].text, "Käse kaufen ");
+        assert_eq!(items[2].text, "Brot backen");
+        assert_eq!(items[2].indent_level, 1);
+        assert_eq!(items[3].text, "Last");
+    }
+
+    #[test]
+    fn test_alt_enter_at_end_creates_empty_item() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("Only item".to_string(), false, 0));
+
+        let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        app.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::ALT))
+            .unwrap();
+
+        assert_eq!(app.selected_index, 1);
+        assert_eq!(app.todo_list.todo_list.items[0].text, "Only item");
+        assert_eq!(app.todo_list.todo_list.items[1].text, "");
+
+        // Cancelling the empty split-off item removes it again
+        app.handle_key_event(KeyCode::Esc).unwrap();
+        assert_eq!(app.mode, AppMode::Selection);
+        assert_eq!(app.todo_list.todo_list.items.len(), 1);
+    }
+
+    #[test]
+    fn test_empty_confirm() {
+        for (empty_confirm, texts) in [
+            (EmptyConfirm::Keep, vec!["", "Keep me", ""]),
+            (EmptyConfirm::Delete, vec!["Keep me"]),
+        ] {
+            let config_dir = tempfile::tempdir().unwrap();
+            let mut todo_list = TodoList::new(Local::now().date_naive());
+            for text in ["Empty me", "Keep me"] {
+                todo_list
+                    .items
+                    .push(TodoItem::new(text.to_string(), false, 0));
+            }
+            let mut todo_app = TodoApp::new(config_dir.path().to_path_buf(), todo_list);
+            todo_app.config.empty_confirm = empty_confirm;
+            let mut app = App::new(todo_app);
+
+            // A new item confirmed without typing anything
+            app.selected_index = 2;
+            app.handle_key_event(KeyCode::Char('i')).unwrap();
+            app.handle_key_event(KeyCode::Enter).unwrap();
+            assert_eq!(app.mode, AppMode::Selection);
+
+            // An existing item whose text is deleted
+            app.selected_index = 0;
+            app.handle_key_event(KeyCode::Enter).unwrap();
+            for _ in 0.."Empty me".len() {
+                app.handle_key_event(KeyCode::Backspace).unwrap();
+            }
+            app.handle_key_event(KeyCode::Enter).unwrap();
+            assert_eq!(app.mode, AppMode::Selection);
+
+            let items = &app.todo_list.todo_list.items;
+            let actual: Vec<&str> = items.iter().map(|item| item.text.as_str()).collect();
+            assert_eq!(actual, texts, "{:?}", empty_confirm);
+            assert!(app.selected_index <= items.len());
+        }
+    }
+
+    #[test]
+    fn test_move_to_top_and_bottom_keys_follow_item() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        for text in ["Item 1", "Item 2", "Item 3"] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), false, 0));
+        }
+
+        let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+        app.selected_index = 1;
+
+        app.handle_key_event(KeyCode::Char('G')).unwrap();
+        assert_eq!(app.selected_index, 2);
+        assert_eq!(app.todo_list.todo_list.items[2].text, "Item 2");
+
+        app.handle_key_event(KeyCode::Char('g')).unwrap();
+        assert_eq!(app.selected_index, 0);
+        assert_eq!(app.todo_list.todo_list.items[0].text, "Item 2");
+
+        // The virtual insertion row is left alone
+        app.selected_index = 3;
+        app.handle_key_event(KeyCode::Char('g')).unwrap();
+        assert_eq!(app.selected_index, 3);
+    }
+
+    #[test]
+    fn test_clear_completed_below_threshold() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("Done".to_string(), true, 0));
+        todo_list
+            .items
+            .push(TodoItem::new("Open".to_string(), false, 0));
+
+        let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+        app.selected_index = 2;
+
+        app.handle_key_event(KeyCode::Char('C')).unwrap();
+        assert_eq!(app.mode, AppMode::ClearCompleted);
+        app.handle_key_event(KeyCode::Char('y')).unwrap();
+
+        assert_eq!(app.mode, AppMode::Selection);
+        assert_eq!(app.todo_list.todo_list.items.len(), 1);
+        assert_eq!(app.todo_list.todo_list.items[0].text, "Open");
+        assert_eq!(app.selected_index, 1);
+    }
+
+    #[test]
+    fn test_clear_completed_above_threshold_needs_second_confirmation() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        for i in 0..3 {
+            todo_list
+                .items
+                .push(TodoItem::new(format!("Done {}", i), true, 0));
+        }
+
+        let mut todo_app = TodoApp::new(PathBuf::new(), todo_list);
+        todo_app.config.bulk_delete_threshold = 2;
+        let mut app = App::new(todo_app);
+
+        app.handle_key_event(KeyCode::Char('C')).unwrap();
+        app.handle_key_event(KeyCode::Char('y')).unwrap();
+        assert_eq!(app.mode, AppMode::ClearCompleted);
+        assert_eq!(app.todo_list.todo_list.items.len(), 3);
+
+        // Cancelling resets the confirmation count
+        app.handle_key_event(KeyCode::Esc).unwrap();
+        app.handle_key_event(KeyCode::Char('C')).unwrap();
+        app.handle_key_event(KeyCode::Char('y')).unwrap();
+        assert_eq!(app.todo_list.todo_list.items.len(), 3);
+
+        app.handle_key_event(KeyCode::Char('y')).unwrap();
+        assert_eq!(app.mode, AppMode::Selection);
+        assert!(app.todo_list.todo_list.items.is_empty());
+        assert_eq!(app.selected_index, 0);
+    }
+
+    #[test]
+    fn test_clear_completed_without_completed_items() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("Open".to_string(), false, 0));
+
+        let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+        app.handle_key_event(KeyCode::Char('C')).unwrap();
+        assert_eq!(app.mode, AppMode::Selection);
+    }
+
+    #[test]
+    fn test_ctrl_arrows_change_indent_level() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("Item 1".to_string(), false, 0));
+
+        let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+
+        app.handle_key_event(KeyEvent::new(KeyCode::Right, KeyModifiers::CONTROL))
+            .unwrap();
+        assert_eq!(app.todo_list.todo_list.items[0].indent_level, 1);
+
+        app.handle_key_event(KeyEvent::new(KeyCode::Left, KeyModifiers::CONTROL))
+            .unwrap();
+        assert_eq!(app.todo_list.todo_list.items[0].indent_level, 0);
+
+        // Plain arrows don't change the indentation
+        app.handle_key_event(KeyCode::Right).unwrap();
+        assert_eq!(app.todo_list.todo_list.items[0].indent_level, 0);
+    }
+
+    #[test]
+    fn test_modifier_chords_do_not_trigger_plain_bindings() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("Item 1".to_string(), false, 0));
+
+        let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+
+        // Ctrl+d and Alt+x are not `d` and `x`
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL))
+            .unwrap();
+        assert_eq!(app.mode, AppMode::Selection);
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::ALT))
+            .unwrap();
+        assert!(!app.todo_list.todo_list.items[0].completed);
+
+        // Shifted letters still count as plain keys
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::SHIFT))
+            .unwrap();
+        assert!(app.todo_list.todo_list.items[0].completed);
+
+        // Ctrl+s doesn't insert text while editing, but AltGr characters do
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        app.edit_text.clear();
+        app.edit_cursor = 0;
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL))
+            .unwrap();
+        app.handle_key_event(KeyEvent::new(
+            KeyCode::Char('@'),
+            KeyModifiers::CONTROL | KeyModifiers::ALT,
+        ))
+        .unwrap();
+        assert_eq!(app.edit_text, "@");
+    }
+
+    #[test]
+    fn test_ctrl_s_saves_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("Item 1".to_string(), false, 0));
+
+        let mut app = App::new(TodoApp::new(dir.path().to_path_buf(), todo_list));
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL))
+            .unwrap();
+
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+        let message = app.status_message.as_ref().unwrap();
+        assert!(message.text.starts_with("Saved "));
+        assert!(!message.is_error);
+
+        // Saving while editing keeps the edit going
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        app.handle_key_event(KeyCode::Char('!')).unwrap();
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL))
+            .unwrap();
+        assert_eq!(app.mode, AppMode::Edit);
+        let (_, path) = crate::todo_files(dir.path()).pop().unwrap();
+        let content = std::fs::read_to_string(path).unwrap();
+        assert!(content.contains("* [ ] Item 1!"));
+    }
+
+    #[test]
+    fn test_save_error_is_shown_in_status_bar() {
+        let dir = tempfile::tempdir().unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let todo_list = TodoList::new(date);
+
+        let missing = dir.path().join("missing");
+        let mut app = App::new(TodoApp::new(missing, todo_list));
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL))
+            .unwrap();
+
+        let message = app.status_message.as_ref().unwrap();
+        assert!(message.is_error);
+        assert!(message.text.starts_with("Save failed"));
+    }
+
+    #[test]
+    fn test_truncate_todo_item_text() {
+        let item = TodoItem::new("This is a long item".to_string(), false, 1);
+        assert_eq!(
+            truncate_todo_item_text(&item, 15, '…'),
+            vec![("  * [ ] This i…".to_string(), true)]
+        );
+        assert_eq!(
+            truncate_todo_item_text(&item, 50, '…'),
+            vec![("  * [ ] This is a long item".to_string(), true)]
+        );
+    }
+
+    #[test]
+    fn test_wrap_toggle_keeps_selected_item() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list.items.push(TodoItem::new(
+            "A long first item that wraps over several rows".to_string(),
+            false,
+            0,
+        ));
+        todo_list
+            .items
+            .push(TodoItem::new("Second".to_string(), false, 0));
+
+        let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+        app.selected_index = 1;
+
+        let rows = render(&app, 24, 10);
+        let second_row = rows.iter().position(|r| r.contains("Second")).unwrap();
+        assert!(second_row > 2);
+
+        app.handle_key_event(KeyCode::Char('w')).unwrap();
+        assert!(!app.wrap_text);
+        let rows = render(&app, 24, 10);
+        assert!(rows[1].contains("A long first"));
+        assert!(rows[1].contains('…'));
+        assert!(rows[2].contains("Second"));
+        assert_eq!(app.selected_index, 1);
+
+        app.handle_key_event(KeyCode::Char('w')).unwrap();
+        assert!(app.wrap_text);
+    }
+
+    #[test]
+    fn test_celebrate_when_last_item_completed() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("Done".to_string(), true, 0));
+        todo_list
+            .items
+            .push(TodoItem::new("Open".to_string(), false, 0));
+
+        let mut todo_app = TodoApp::new(PathBuf::new(), todo_list);
+        todo_app.config.celebrate = true;
+        let mut app = App::new(todo_app);
+        app.selected_index = 1;
+
+        app.handle_key_event(KeyCode::Char('x')).unwrap();
+        assert!(app.ring_bell);
+        assert_eq!(
+            app.status_message.as_ref().map(|m| m.text.as_str()),
+            Some("All done! 🎉")
+        );
+
+        // The message goes away with the next key, and un-completing doesn't celebrate
+        app.ring_bell = false;
+        app.handle_key_event(KeyCode::Char('x')).unwrap();
+        assert_eq!(app.status_message, None);
+        assert!(!app.ring_bell);
+    }
+
+    #[test]
+    fn test_no_celebration_by_default() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("Open".to_string(), false, 0));
+
+        let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+        app.handle_key_event(KeyCode::Char('x')).unwrap();
+        assert!(!app.ring_bell);
+        assert_eq!(app.status_message, None);
+    }
+
+    #[test]
+    fn test_inserted_item_is_sorted_after_confirm() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("Open".to_string(), false, 0));
+        todo_list
+            .items
+            .push(TodoItem::new("Done".to_string(), true, 0));
+
+        let mut todo_app = TodoApp::new(PathBuf::new(), todo_list);
+        todo_app.config.auto_sort = SortMode::CompletedLast;
+        let mut app = App::new(todo_app);
+        app.selected_index = 2;
+
+        app.handle_key_event(KeyCode::Char('i')).unwrap();
+        app.handle_key_event(KeyCode::Char('N')).unwrap();
+        app.handle_key_event(KeyCode::Enter).unwrap();
+
+        let texts: Vec<&str> = app
+            .todo_list
+            .todo_list
+            .items
+            .iter()
+            .map(|i| i.text.as_str())
+            .collect();
+        assert_eq!(texts, ["Open", "N", "Done"]);
+        // The selection follows the new item
+        assert_eq!(app.selected_index, 1);
+    }
+
+    #[test]
+    fn test_insert_child_item() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("Parent".to_string(), false, 0));
+        todo_list
+            .items
+            .push(TodoItem::new("Last".to_string(), false, 1));
+
+        let mut todo_app = TodoApp::new(PathBuf::new(), todo_list);
+        todo_app.config.max_indent = Some(1);
+        let mut app = App::new(todo_app);
+
+        app.handle_key_event(KeyCode::Char('I')).unwrap();
+        assert_eq!(app.mode, AppMode::Edit);
+        assert_eq!(app.selected_index, 1);
+        assert_eq!(app.todo_list.todo_list.items[1].indent_level, 1);
+        app.handle_key_event(KeyCode::Char('a')).unwrap();
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        assert_eq!(app.todo_list.todo_list.items[1].text, "a");
+
+        // A child of the last item goes to the end, clamped to max_indent
+        app.selected_index = 2;
+        app.handle_key_event(KeyCode::Char('I')).unwrap();
+        assert_eq!(app.selected_index, 3);
+        assert_eq!(app.todo_list.todo_list.items.len(), 4);
+        assert_eq!(app.todo_list.todo_list.items[3].indent_level, 1);
+    }
+
+    #[test]
+    fn test_status_message_expires() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut app = App::new(TodoApp::new(PathBuf::new(), TodoList::new(date)));
+        assert_eq!(app.status_message_remaining(Instant::now()), None);
+
+        app.notify("Hello");
+        let shown_at = app.status_message.as_ref().unwrap().shown_at;
+        assert_eq!(
+            app.status_message_remaining(shown_at + Duration::from_secs(1)),
+            Some(STATUS_MESSAGE_TIMEOUT - Duration::from_secs(1))
+        );
+
+        // The message takes priority over the key hints while it is shown
+        let lines = render(&app, 40, 6);
+        assert!(lines[5].contains("Hello"));
+
+        app.clear_expired_status_message(shown_at + Duration::from_secs(1));
+        assert!(app.status_message.is_some());
+        app.clear_expired_status_message(shown_at + STATUS_MESSAGE_TIMEOUT);
+        assert_eq!(app.status_message, None);
+        assert!(render(&app, 40, 6)[5].contains("Sel"));
+    }
+
+    #[test]
+    fn test_edit_cursor_column_with_wide_chars() {
+        use unicode_width::UnicodeWidthStr;
+
+        let item = TodoItem::new(String::new(), false, 0);
+        let edit_text = "日本語 abc 🎉x";
+
+        // The cursor sits between the glyphs at its char index, so its display
+        // column is the width of everything before it
+        for (cursor, expected_column) in [(0, 6), (2, 10), (3, 12), (5, 14), (9, 19), (10, 20)] {
+            let wrapped = wrap_todo_item_text(&item, 40, true, edit_text, cursor, true);
+            let line = &wrapped[0].0;
+            let (before, _) = line.split_once('|').unwrap();
+            assert_eq!(before.width(), expected_column, "cursor {}", cursor);
+        }
+
+        // Wrapping measures columns, so no line gets wider than the space
+        let long = "日本語日本語日本語日本語";
+        let wrapped = wrap_todo_item_text(&item, 12, true, long, 5, true);
+        assert!(wrapped.iter().all(|(line, _)| line.width() <= 12));
+        let joined: String = wrapped
+            .iter()
+            .map(|(line, _)| line.trim_start_matches(['*', '[', ']', ' ']))
+            .collect();
+        assert_eq!(joined, "日本語日本|語日本語日本語");
+    }
+
+    #[test]
+    fn test_emoji_sequences_wrap_and_edit_as_one() {
+        use unicode_width::UnicodeWidthStr;
+
+        // A woman technologist: three chars joined into one two-column glyph
+        let emoji = "👩\u{200D}💻";
+        let item = TodoItem::new(format!("{} fix the build", emoji), false, 0);
+        let wrapped = wrap_todo_item_text(&item, 16, false, "", 0, false);
+        assert_eq!(
+            wrapped,
+            vec![
+                (format!("* [ ] {} fix the", emoji), true),
+                ("   build".to_string(), false),
+            ]
+        );
+
+        // Long words are broken between the sequences, never inside them
+        let item = TodoItem::new(emoji.repeat(6), false, 0);
+        let wrapped = wrap_todo_item_text(&item, 16, false, "", 0, false);
+        assert_eq!(wrapped[0].0, format!("* [ ] {}", emoji.repeat(5)));
+        assert_eq!(wrapped[1].0, format!("   {}", emoji));
+        assert!(wrapped.iter().all(|(line, _)| line.width() <= 16));
+
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new(format!("{}x", emoji), false, 0));
+        let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        assert_eq!(app.edit_cursor, 4);
+        app.handle_key_event(KeyCode::Left).unwrap();
+        assert_eq!(app.edit_cursor, 3);
+        let item = &app.todo_list.todo_list.items[0];
+        let wrapped = wrap_todo_item_text(item, 20, true, &app.edit_text, app.edit_cursor, true);
+        assert_eq!(wrapped[0].0, format!("* [ ] {}|x", emoji));
+        app.handle_key_event(KeyCode::Left).unwrap();
+        assert_eq!(app.edit_cursor, 0);
+        app.handle_key_event(KeyCode::Right).unwrap();
+        assert_eq!(app.edit_cursor, 3);
+
+        app.handle_key_event(KeyCode::Backspace).unwrap();
+        assert_eq!((app.edit_text.as_str(), app.edit_cursor), ("x", 0));
+        app.edit_text = format!("{}x", emoji);
+        app.handle_key_event(KeyCode::Delete).unwrap();
+        assert_eq!((app.edit_text.as_str(), app.edit_cursor), ("x", 0));
+    }
+
+    #[test]
+    fn test_long_line_warning() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("Item".to_string(), false, 1));
+
+        let mut todo_app = TodoApp::new(PathBuf::new(), todo_list);
+        todo_app.config.max_line_length = 12;
+        let mut app = App::new(todo_app);
+
+        // "  * [ ] 日本" is exactly 12 columns wide
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        app.edit_text = "日本".to_string();
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        assert_eq!(app.status_message, None);
+
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        app.handle_key_event(KeyCode::Char('x')).unwrap();
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        let message = app.status_message.as_ref().unwrap();
+        assert!(message.text.contains("13 columns"));
+        assert!(!message.is_error);
+        // The text is saved anyway
+        assert_eq!(app.todo_list.todo_list.items[0].text, "日本x");
+
+        // 0 disables the check
+        app.todo_list.config.max_line_length = 0;
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        assert_eq!(app.status_message, None);
+    }
+
+    #[test]
+    fn test_start_on_first_incomplete() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        for (text, completed) in [("A", true), ("B", true), ("C", false), ("D", false)] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), completed, 0));
+        }
+
+        let mut todo_app = TodoApp::new(PathBuf::new(), todo_list);
+        todo_app.config.start_on_first_incomplete = true;
+        let mut app = App::new(todo_app);
+        assert_eq!(app.selected_index, 2);
+
+        // All done or empty falls back to the top
+        for item in &mut app.todo_list.todo_list.items {
+            item.completed = true;
+        }
+        let app = App::new(app.todo_list);
+        assert_eq!(app.selected_index, 0);
+    }
+
+    #[test]
+    fn test_show_and_strip_invisible() {
+        let text = "a\u{00A0}b\u{200B}c\u{FEFF}d\u{202F}e\u{00AD}f\u{2060}g";
+        assert_eq!(show_invisible(text), "a·b␣c␣d·e␣f␣g");
+        assert_eq!(strip_invisible(text), "a bcd efg");
+        assert_eq!(show_invisible("plain text"), "plain text");
+        assert_eq!(strip_invisible("plain text"), "plain text");
+    }
+
+    #[test]
+    fn test_invisible_characters_in_edit_mode() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("a\u{200B}b\u{00A0}c".to_string(), false, 0));
+        let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL))
+            .unwrap();
+        assert!(render(&app, 30, 4)[1].contains("a␣b·c|"));
+
+        // Stripping keeps the cursor behind the same character
+        app.edit_cursor = 3;
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL))
+            .unwrap();
+        assert_eq!(app.edit_text, "ab c");
+        assert_eq!(app.edit_cursor, 2);
+    }
+
+    #[test]
+    fn test_status_hints_follow_enabled_features() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("Item".to_string(), false, 0));
+        let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+
+        let hints = status_hints(&app, usize::MAX, 1);
+        assert!(hints.starts_with("Sel | ↑k:Up"));
+        assert!(hints.contains("Tab/Ctrl+→:Indent"));
+        // Nothing to clear yet
+        assert!(!hints.contains("C:Clear completed"));
+
+        app.todo_list.todo_list.items[0].completed = true;
+        app.todo_list.config.max_indent = Some(0);
+        let hints = status_hints(&app, usize::MAX, 1);
+        assert!(hints.contains("C:Clear completed"));
+        assert!(!hints.contains("Indent"));
+        assert!(!hints.contains("I:Insert child"));
+
+        // Hints that don't fit are dropped as a whole
+        assert_eq!(status_hints(&app, 25, 1), "Sel | ↑k:Up | ↓j:Down");
+        assert_eq!(status_hints(&app, 2, 1), "Sel");
+
+        // A second line takes the hints that didn't fit on the first
+        assert_eq!(
+            status_hints(&app, 25, 2),
+            "Sel | ↑k:Up | ↓j:Down\nx:Toggle | i:Insert"
+        );
+    }
+
+    #[test]
+    fn test_status_lines_layout() {
+        let mut app = numbered_app(10);
+        let bottom_border = |app: &App| {
+            render(app, 30, 10)
+                .iter()
+                .rposition(|row| row.starts_with('└'))
+                .unwrap()
+        };
+        assert_eq!(bottom_border(&app), 8);
+
+        app.todo_list.config.status_lines = 2;
+        assert_eq!(bottom_border(&app), 7);
+        let screen = render(&app, 30, 10);
+        assert!(screen[8].starts_with("Sel | ↑k:Up | ↓j:Down"));
+        assert!(screen[9].starts_with("x:Toggle | i:Insert"));
+    }
+
+    #[test]
+    fn test_confirm_quit_if_incomplete() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("Open".to_string(), false, 0));
+        let mut todo_app = TodoApp::new(PathBuf::new(), todo_list);
+        todo_app.config.confirm_quit_if_incomplete = true;
+        let mut app = App::new(todo_app);
+
+        app.handle_key_event(KeyCode::Char('q')).unwrap();
+        assert_eq!(app.mode, AppMode::ConfirmQuit);
+        assert!(!app.should_quit());
+        assert!(render(&app, 60, 4)[3].starts_with("1 tasks remaining, quit anyway?"));
+
+        app.handle_key_event(KeyCode::Char('n')).unwrap();
+        assert_eq!(app.mode, AppMode::Selection);
+        app.handle_key_event(KeyCode::Char('q')).unwrap();
+        app.handle_key_event(KeyCode::Esc).unwrap();
+        assert_eq!(app.mode, AppMode::Selection);
+
+        app.handle_key_event(KeyCode::Char('q')).unwrap();
+        app.handle_key_event(KeyCode::Char('y')).unwrap();
+        assert!(app.should_quit());
+
+        // Without open items there is nothing to confirm
+        let mut app = App::new(app.todo_list);
+        app.todo_list.todo_list.items[0].completed = true;
+        app.handle_key_event(KeyCode::Char('q')).unwrap();
+        assert_eq!(app.mode, AppMode::Selection);
+        assert!(app.should_quit());
+    }
+
+    #[test]
+    fn test_parse_inline_links() {
+        assert_eq!(
+            parse_inline_links("read [the docs](https://example.com) today"),
+            vec![
+                InlineSegment::Text("read "),
+                InlineSegment::Link {
+                    label: "the docs",
+                    url: "https://example.com"
+                },
+                InlineSegment::Text(" today"),
+            ]
+        );
+        assert_eq!(
+            parse_inline_links("[a](x)[b](y)"),
+            vec![
+                InlineSegment::Link {
+                    label: "a",
+                    url: "x"
+                },
+                InlineSegment::Link {
+                    label: "b",
+                    url: "y"
+                },
+            ]
+        );
+        // Incomplete links stay plain text
+        for text in ["[no url]", "[a] (x)", "[a](has space)", "[a]()", "plain"] {
+            assert_eq!(parse_inline_links(text), vec![InlineSegment::Text(text)]);
+        }
+        assert_eq!(
+            parse_inline_links("[a [b](x)"),
+            vec![
+                InlineSegment::Text("[a "),
+                InlineSegment::Link {
+                    label: "b",
+                    url: "x"
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_links_render_as_labels() {
+        use ratatui::style::{Color, Modifier};
+
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list.items.push(TodoItem::new(
+            "see [the docs](https://example.com) now".to_string(),
+            false,
+            0,
+        ));
+        let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+        app.selected_index = 1;
+
+        let mut terminal = Terminal::new(TestBackend::new(40, 6)).unwrap();
+        terminal.draw(|f| ui(f, &app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let line: String = (0..40).map(|x| buffer[(x, 1)].symbol()).collect();
+        assert!(line.contains("* [ ] see the docs now"));
+
+        // Only the label is styled as a link
+        let column = line[..line.find("the docs").unwrap()].chars().count() as u16;
+        let link_cell = &buffer[(column, 1)];
+        assert_eq!(link_cell.fg, Color::Cyan);
+        assert!(link_cell.modifier.contains(Modifier::UNDERLINED));
+        assert_ne!(buffer[(column - 2, 1)].fg, Color::Cyan);
+        assert_ne!(buffer[(column + 9, 1)].fg, Color::Cyan);
+
+        // Storage keeps the raw link
+        assert_eq!(
+            app.todo_list.todo_list.items[0].to_markdown_line(),
+            "* [ ] see [the docs](https://example.com) now"
+        );
+
+        // Editing shows the raw text
+        app.selected_index = 0;
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        assert!(render(&app, 60, 4)[1].contains("[the docs](https://example.com)"));
+    }
+
+    #[test]
+    fn test_paste_inserts_at_cursor() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("Grüße aus".to_string(), false, 0));
+        let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+
+        // Ignored outside of edit mode
+        app.handle_paste("ignored").unwrap();
+        assert_eq!(app.edit_text, "");
+
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        app.handle_key_event(KeyCode::Home).unwrap();
+        app.handle_key_event(KeyCode::Right).unwrap();
+        app.handle_key_event(KeyCode::Right).unwrap();
+        app.handle_key_event(KeyCode::Right).unwrap();
+        app.handle_paste("日本ü").unwrap();
+        assert_eq!(app.edit_text, "Grü日本üße aus");
+        assert_eq!(app.edit_cursor, 6);
+
+        // Typing continues behind the pasted text
+        app.handle_key_event(KeyCode::Char('!')).unwrap();
+        assert_eq!(app.edit_text, "Grü日本ü!ße aus");
+    }
+
+    #[test]
+    fn test_multiline_paste_joins_lines() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("A".to_string(), false, 0));
+        let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        app.handle_paste("ä one\r\n\ntwo ö\n").unwrap();
+        assert_eq!(app.edit_text, "Aä one two ö");
+        assert_eq!(app.edit_cursor, 12);
+        assert_eq!(app.todo_list.todo_list.items.len(), 1);
+    }
+
+    #[test]
+    fn test_multiline_paste_splits_items() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("head tail".to_string(), false, 1));
+        let mut todo_app = TodoApp::new(PathBuf::new(), todo_list);
+        todo_app.config.multiline_paste = MultilinePaste::Split;
+        let mut app = App::new(todo_app);
+
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        app.edit_cursor = 5;
+        app.handle_paste("één\nzwei\n\ndrei ").unwrap();
+
+        let items = &app.todo_list.todo_list.items;
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].text, "head één");
+        assert_eq!(items[1].text, "zwei");
+        assert!(items.iter().all(|item| item.indent_level == 1));
+        assert_eq!(app.selected_index, 2);
+        assert_eq!(app.edit_text, "drei tail");
+        assert_eq!(app.edit_cursor, 5);
+    }
+
+    #[test]
+    fn test_undo_toggle() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        for text in ["A", "B"] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), false, 0));
+        }
+        let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+
+        app.handle_key_event(KeyCode::Char('u')).unwrap();
+        assert_eq!(app.status_message.as_ref().unwrap().text, "Nothing to undo");
+
+        app.handle_key_event(KeyCode::Char('x')).unwrap();
+        app.handle_key_event(KeyCode::Char('j')).unwrap();
+        app.handle_key_event(KeyCode::Char('u')).unwrap();
+        assert!(!app.todo_list.todo_list.items[0].completed);
+        assert_eq!(app.selected_index, 0);
+
+        // Only the last toggle can be undone
+        app.handle_key_event(KeyCode::Char('u')).unwrap();
+        assert_eq!(app.status_message.as_ref().unwrap().text, "Nothing to undo");
+
+        // Any other change forgets the toggle
+        app.handle_key_event(KeyCode::Char('x')).unwrap();
+        app.handle_key_event(KeyCode::Tab).unwrap();
+        app.handle_key_event(KeyCode::Char('u')).unwrap();
+        assert!(app.todo_list.todo_list.items[0].completed);
+    }
+
+    #[test]
+    fn test_undo_coalesces_toggles_of_same_item() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        for text in ["A", "B", "C"] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), false, 0));
+        }
+        let mut todo_app = TodoApp::new(PathBuf::new(), todo_list);
+        todo_app.config.sink_completed = true;
+        let mut app = App::new(todo_app);
+
+        // Three toggles of A, which sinks below the open items each time
+        for _ in 0..3 {
+            app.handle_key_event(KeyCode::Char('x')).unwrap();
+        }
+        let texts: Vec<_> = app
+            .todo_list
+            .todo_list
+            .items
+            .iter()
+            .map(|i| &i.text)
+            .collect();
+        assert_eq!(texts, ["B", "C", "A"]);
+        assert!(app.todo_list.todo_list.items[2].completed);
+
+        // One undo reopens it and brings it back up
+        app.handle_key_event(KeyCode::Char('u')).unwrap();
+        assert!(
+            app.todo_list
+                .todo_list
+                .items
+                .iter()
+                .all(|item| !item.completed)
+        );
+        assert_eq!(app.todo_list.todo_list.items[app.selected_index].text, "A");
+
+        // Toggling another item starts over
+        app.handle_key_event(KeyCode::Char('x')).unwrap();
+        app.handle_key_event(KeyCode::Char('k')).unwrap();
+        app.handle_key_event(KeyCode::Char('x')).unwrap();
+        app.handle_key_event(KeyCode::Char('u')).unwrap();
+        assert_eq!(
+            app.todo_list
+                .todo_list
+                .items
+                .iter()
+                .filter(|item| item.completed)
+                .count(),
+            1
+        );
+    }
+
+    fn repeat(code: KeyCode) -> KeyEvent {
+        KeyEvent {
+            code,
+            modifiers: KeyModifiers::NONE,
+            kind: KeyEventKind::Repeat,
+            state: KeyEventState::NONE,
+        }
+    }
+
+    #[test]
+    fn test_repeat_only_triggers_repeatable_keys() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        for text in ["A", "B", "C"] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), false, 0));
+        }
+        let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+
+        // Navigation repeats
+        app.handle_key_event(repeat(KeyCode::Char('j'))).unwrap();
+        app.handle_key_event(repeat(KeyCode::Down)).unwrap();
+        assert_eq!(app.selected_index, 2);
+        app.handle_key_event(repeat(KeyCode::Char('k'))).unwrap();
+        assert_eq!(app.selected_index, 1);
+
+        // One-shot actions don't
+        for code in [KeyCode::Char('x'), KeyCode::Char('d'), KeyCode::Char('q')] {
+            app.handle_key_event(repeat(code)).unwrap();
+        }
+        assert!(!app.todo_list.todo_list.items[1].completed);
+        assert_eq!(app.mode, AppMode::Selection);
+        assert!(!app.should_quit());
+
+        // In edit mode typing and deleting repeat, confirming doesn't
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        app.handle_key_event(repeat(KeyCode::Char('b'))).unwrap();
+        app.handle_key_event(repeat(KeyCode::Backspace)).unwrap();
+        app.handle_key_event(repeat(KeyCode::Char('!'))).unwrap();
+        app.handle_key_event(repeat(KeyCode::Enter)).unwrap();
+        assert_eq!(app.mode, AppMode::Edit);
+        assert_eq!(app.edit_text, "B!");
+
+        // Delete confirmations never repeat
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        app.handle_key_event(KeyCode::Char('d')).unwrap();
+        app.handle_key_event(repeat(KeyCode::Char('y'))).unwrap();
+        assert_eq!(app.mode, AppMode::Delete);
+        assert_eq!(app.todo_list.todo_list.items.len(), 3);
+    }
+
+    #[test]
+    fn test_join_key() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        for (text, level) in [("A", 0), ("B", 0), ("C", 0), ("C1", 1)] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), false, level));
+        }
+        let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+
+        app.handle_key_event(KeyCode::Char('J')).unwrap();
+        assert_eq!(app.todo_list.todo_list.items[0].text, "A B");
+        assert_eq!(app.selected_index, 0);
+        assert_eq!(app.status_message, None);
+
+        app.handle_key_event(KeyCode::Char('J')).unwrap();
+        assert_eq!(app.todo_list.todo_list.items.len(), 3);
+        assert!(app.status_message.is_some());
+    }
+
+    #[test]
+    fn test_rollover_waits_for_selection_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let next_day = NaiveDate::from_ymd_opt(2025, 8, 15).unwrap();
+        let mut todo_list = TodoList::new(date);
+        for (text, completed) in [("A", true), ("B", true), ("C", false)] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), completed, 0));
+        }
+        let mut todo_app = TodoApp::new(dir.path().to_path_buf(), todo_list);
+        todo_app.config.rollover = RolloverMode::Incomplete;
+        let mut app = App::new(todo_app);
+        app.selected_index = 2;
+
+        // Not while editing
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        app.check_rollover(next_day).unwrap();
+        assert_eq!(app.todo_list.todo_list.date, date);
+
+        app.handle_key_event(KeyCode::Esc).unwrap();
+        app.check_rollover(next_day).unwrap();
+        assert_eq!(app.todo_list.todo_list.date, next_day);
+        assert_eq!(app.todo_list.todo_list.items.len(), 1);
+        assert_eq!(app.selected_index, 1);
+        assert!(app.status_message.is_some());
+    }
+
+    fn numbered_app(count: usize) -> App {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        for i in 0..count {
+            todo_list
+                .items
+                .push(TodoItem::new(format!("Item {}", i), false, 0));
+        }
+        App::new(TodoApp::new(PathBuf::new(), todo_list))
+    }
+
+    #[test]
+    fn test_only_visible_items_are_wrapped() {
+        let mut app = numbered_app(5000);
+        app.selected_index = 2500;
+
+        let mut wrapped = Vec::new();
+        let (offset, visible) = visible_items(&app, 10, |index| {
+            wrapped.push(index);
+            Rc::new(wrap_item(&app, index, 40))
+        });
+        // The selected item ends up at the bottom, with the screen filled above
+        assert_eq!(offset, 2491);
+        let indices: Vec<usize> = visible.iter().map(|(index, _)| *index).collect();
+        assert_eq!(indices, (2491..=2500).collect::<Vec<_>>());
+        // Plus the one above that didn't fit anymore
+        assert_eq!(wrapped.len(), 11);
+
+        // Moving within the screen keeps the offset, moving past it scrolls
+        app.scroll_offset.set(offset);
+        app.selected_index = 2495;
+        let (offset, visible) =
+            visible_items(&app, 10, |index| Rc::new(wrap_item(&app, index, 40)));
+        assert_eq!(offset, 2491);
+        assert_eq!(visible.last().unwrap().0, 2500);
+        app.selected_index = 2400;
+        let (offset, _) = visible_items(&app, 10, |index| Rc::new(wrap_item(&app, index, 40)));
+        assert_eq!(offset, 2400);
+    }
+
+    #[test]
+    fn test_wrap_cache_reuses_unchanged_items() {
+        let mut app = numbered_app(3);
+        let cached = |app: &App, index| Rc::clone(&app.wrap_cache.borrow()[&index].1);
+
+        render(&app, 30, 8);
+        let first = [cached(&app, 0), cached(&app, 1), cached(&app, 2)];
+
+        // A redraw after a key that changes nothing wraps nothing again
+        app.handle_key_event(KeyCode::Char('u')).unwrap();
+        render(&app, 30, 8);
+        for (index, wrapped) in first.iter().enumerate() {
+            assert!(Rc::ptr_eq(wrapped, &cached(&app, index)));
+        }
+
+        // Changing an item only wraps that one again
+        app.todo_list
+            .update_item_text(2, "Changed".to_string())
+            .unwrap();
+        render(&app, 30, 8);
+        assert!(Rc::ptr_eq(&first[0], &cached(&app, 0)));
+        assert!(Rc::ptr_eq(&first[1], &cached(&app, 1)));
+        assert!(!Rc::ptr_eq(&first[2], &cached(&app, 2)));
+        let changed = cached(&app, 2);
+
+        // Moving the selection wraps the items it moved between
+        app.handle_key_event(KeyCode::Char('j')).unwrap();
+        render(&app, 30, 8);
+        assert!(!Rc::ptr_eq(&first[0], &cached(&app, 0)));
+        assert!(!Rc::ptr_eq(&first[1], &cached(&app, 1)));
+        assert!(Rc::ptr_eq(&changed, &cached(&app, 2)));
+
+        // A different width wraps everything again
+        let before = cached(&app, 0);
+        render(&app, 40, 8);
+        assert!(!Rc::ptr_eq(&before, &cached(&app, 0)));
+
+        // Entries of deleted items are dropped
+        app.todo_list.delete_item(2).unwrap();
+        render(&app, 40, 8);
+        assert!(!app.wrap_cache.borrow().contains_key(&2));
+    }
+
+    #[test]
+    fn test_wrap_cache_redraws_promoted_notes() {
+        let mut app = numbered_app(1);
+        app.todo_list.config.promote_notes_on_toggle = true;
+        app.todo_list
+            .todo_list
+            .items
+            .push(TodoItem::note("A note".to_string(), 0));
+
+        assert!(render(&app, 30, 8)[2].starts_with("│* A note"));
+        // Same text and completion, but it has a checkbox now
+        app.todo_list.toggle_item_completed(1).unwrap();
+        assert!(render(&app, 30, 8)[2].starts_with("│* [ ] A note"));
+    }
+
+    #[test]
+    fn test_scrolled_list_renders_selection() {
+        let mut app = numbered_app(50);
+        assert!(app.status_message.is_none());
+        app.selected_index = 50;
+
+        // 6 list rows: the marker and the five items above it
+        let screen = render(&app, 30, 9);
+        assert!(screen[1].contains("Item 45"));
+        assert!(screen[5].contains("Item 49"));
+        assert!(screen[6].contains("Insert new item here"));
+
+        app.selected_index = 47;
+        let screen = render(&app, 30, 9);
+        assert!(screen[1].contains("Item 45"));
+
+        let app = numbered_app(LARGE_LIST_WARNING + 1);
+        assert!(app.status_message.is_some());
+    }
+
+    #[test]
+    fn test_notes_render_without_checkbox() {
+        let note = TodoItem::note("a note that wraps".to_string(), 1);
+        let wrapped = wrap_todo_item_text(&note, 12, false, "", 0, false);
+        assert_eq!(
+            wrapped,
+            [
+                ("  * a note".to_string(), true),
+                ("    that".to_string(), false),
+                ("    wraps".to_string(), false),
+            ]
+        );
+
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::note("see [docs](x)".to_string(), 0));
+        todo_list
+            .items
+            .push(TodoItem::new("task".to_string(), false, 0));
+        let app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+        let screen = render(&app, 30, 5);
+        assert!(screen[1].starts_with("│* see docs "));
+        assert!(screen[2].starts_with("│* [ ] task "));
+    }
+
+    #[test]
+    fn test_list_for_clipboard() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list.items = vec![
+            TodoItem::new("Plan".to_string(), true, 0),
+            TodoItem::new("Slides".to_string(), false, 1),
+            TodoItem::note("Room 4".to_string(), 1),
+        ];
+
+        assert_eq!(
+            list_for_clipboard(&todo_list, ClipboardFormat::Markdown),
+            "# TODO 2025-08-14\n\n* [x] Plan\n  * [ ] Slides\n  * Room 4\n"
+        );
+        assert_eq!(
+            list_for_clipboard(&todo_list, ClipboardFormat::Plain),
+            "Plan\n  Slides\n  Room 4\n"
+        );
+    }
+
+    #[test]
+    fn test_alt_screen_is_optional() {
+        let written = |f: &dyn Fn(&mut Vec<u8>) -> std::io::Result<()>| {
+            let mut out = Vec::new();
+            f(&mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let enter_alt = "\x1b[?1049h";
+        let leave_alt = "\x1b[?1049l";
+        let bracketed_paste = "\x1b[?2004h";
+
+        let output = written(&|out| enter_screen(out, true));
+        assert!(output.starts_with(enter_alt));
+        assert!(output.contains(bracketed_paste));
+        assert!(written(&|out| leave_screen(out, true, 24)).ends_with(leave_alt));
+
+        let output = written(&|out| enter_screen(out, false));
+        assert!(!output.contains(enter_alt));
+        assert!(output.contains(bracketed_paste));
+        let output = written(&|out| leave_screen(out, false, 24));
+        assert!(!output.contains(leave_alt));
+        // Below the last frame
+        assert!(output.ends_with("\x1b[24;1H\r\n"));
+    }
+
+    #[test]
+    fn test_greeting_is_dismissed_by_any_key() {
+        let mut app = numbered_app(3);
+        app.todo_list.todo_list.items[0].completed = true;
+        app.greet();
+        let screen = render(&app, 40, 6);
+        assert_eq!(screen[3].trim(), "Good morning! 2 tasks so far");
+        assert!(!screen.iter().any(|row| row.contains("Item")));
+
+        // The key only dismisses the greeting
+        app.handle_key_event(KeyCode::Char('q')).unwrap();
+        assert!(!app.should_quit());
+        assert!(render(&app, 40, 6)[1].contains("Item 0"));
+        app.handle_key_event(KeyCode::Char('q')).unwrap();
+        assert!(app.should_quit());
+    }
+
+    #[test]
+    fn test_fits_terminal() {
+        assert!(fits_terminal(Rect::new(0, 0, 80, 24)));
+        assert!(fits_terminal(Rect::new(0, 0, 16, 4)));
+        assert!(!fits_terminal(Rect::new(0, 0, 15, 24)));
+        assert!(!fits_terminal(Rect::new(0, 0, 80, 3)));
+        assert!(!fits_terminal(Rect::new(0, 0, 80, 1)));
+    }
+
+    #[test]
+    fn test_tiny_terminal_shows_message() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("Item".to_string(), false, 0));
+        let app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+
+        let screen = render(&app, 30, 2);
+        assert_eq!(screen[0].trim(), "");
+        assert_eq!(screen[1].trim(), "Terminal too small");
+
+        // Back to normal once there is enough room
+        let screen = render(&app, 30, 4);
+        assert!(screen[1].contains("* [ ] Item"));
+    }
+
+    #[test]
+    fn test_carried_items_are_styled() {
+        let dir = tempfile::tempdir().unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        for text in ["A", "B"] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), false, 0));
+        }
+        let mut todo_app = TodoApp::new(dir.path().to_path_buf(), todo_list);
+        todo_app.config.rollover = RolloverMode::Incomplete;
+        let mut app = App::new(todo_app);
+        app.check_rollover(date.succ_opt().unwrap()).unwrap();
+        app.todo_list.insert_new_item(2).unwrap();
+        app.todo_list.update_item_text(2, "C".to_string()).unwrap();
+
+        let mut terminal = Terminal::new(TestBackend::new(20, 6)).unwrap();
+        terminal.draw(|f| ui(f, &app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        // Rows 1-3 hold A (selected), B and the new C; column 7 is the text
+        assert!(!buffer[(7, 1)].modifier.contains(Modifier::ITALIC));
+        assert_eq!(buffer[(7, 2)].symbol(), "B");
+        assert!(buffer[(7, 2)].modifier.contains(Modifier::ITALIC));
+        assert_eq!(buffer[(7, 3)].symbol(), "C");
+        assert!(!buffer[(7, 3)].modifier.contains(Modifier::ITALIC));
+    }
+
+    #[test]
+    fn test_strikethrough_completed() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        for (text, completed) in [("A", false), ("B", true)] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), completed, 0));
+        }
+        let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+
+        let mut terminal = Terminal::new(TestBackend::new(20, 5)).unwrap();
+        let mut struck = |app: &App| {
+            terminal.draw(|f| ui(f, app)).unwrap();
+            let buffer = terminal.backend().buffer();
+            [1, 2].map(|y| buffer[(7, y)].modifier.contains(Modifier::CROSSED_OUT))
+        };
+        assert_eq!(struck(&app), [false, false]);
+        app.todo_list.config.strikethrough_completed = true;
+        assert_eq!(struck(&app), [false, true]);
+    }
+
+    #[test]
+    fn test_capture_saves_item_and_quits() {
+        let dir = tempfile::tempdir().unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("Existing".to_string(), false, 0));
+        let mut app = App::new(TodoApp::new(dir.path().to_path_buf(), todo_list));
+        app.start_capture();
+        assert_eq!(app.mode, AppMode::Edit);
+        assert_eq!(app.selected_index, 1);
+
+        for c in "New".chars() {
+            app.handle_capture_key(KeyCode::Char(c).into(), false)
+                .unwrap();
+        }
+        let mut terminal = Terminal::new(TestBackend::new(40, 4)).unwrap();
+        terminal.draw(|f| capture_ui(f, &app, false)).unwrap();
+        let row: String = (0..40)
+            .map(|x| terminal.backend().buffer()[(x, 1)].symbol().to_string())
+            .collect();
+        assert!(row.contains(&format!("* [ ] New{}", CURSOR)));
+        assert!(!row.contains("Existing"));
+        // Nothing is saved while the item is being written
+        assert!(!app.todo_list.file_path().exists());
+
+        app.handle_capture_key(KeyCode::Enter.into(), false)
+            .unwrap();
+        assert!(app.should_quit);
+        let saved = std::fs::read_to_string(app.todo_list.file_path()).unwrap();
+        assert!(saved.ends_with("* [ ] Existing\n* [ ] New\n"));
+    }
+
+    #[test]
+    fn test_capture_stay_until_empty_confirm() {
+        let dir = tempfile::tempdir().unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let todo_app = TodoApp::new(dir.path().to_path_buf(), TodoList::new(date));
+        let mut app = App::new(todo_app);
+        app.start_capture();
+        let texts = |app: &App| -> Vec<String> {
+            let items = &app.todo_list.todo_list.items;
+            items.iter().map(|item| item.text.clone()).collect()
+        };
+
+        for text in ["A", "B"] {
+            app.insert_edit_text(text);
+            app.handle_capture_key(KeyCode::Enter.into(), true).unwrap();
+            assert!(!app.should_quit);
+            assert_eq!(app.mode, AppMode::Edit);
+        }
+        assert_eq!(texts(&app), ["A", "B", ""]);
+        let saved = std::fs::read_to_string(app.todo_list.file_path()).unwrap();
+        assert!(saved.ends_with("* [ ] A\n* [ ] B\n"));
+
+        // Confirming the empty item ends the capture without keeping it
+        app.handle_capture_key(KeyCode::Enter.into(), true).unwrap();
+        assert!(app.should_quit);
+        assert_eq!(texts(&app), ["A", "B"]);
+
+        // Esc quits right away, dropping what was typed
+        app.should_quit = false;
+        app.start_capture();
+        app.insert_edit_text("C");
+        app.handle_capture_key(KeyCode::Esc.into(), true).unwrap();
+        assert!(app.should_quit);
+        assert_eq!(texts(&app), ["A", "B"]);
+    }
+
+    #[test]
+    fn test_confirm_unindent_with_subitems() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let mut todo_list = TodoList::new(Local::now().date_naive());
+        for (text, level) in [("A", 0), ("B", 1), ("C", 2), ("D", 2)] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), false, level));
+        }
+        let mut todo_app = TodoApp::new(config_dir.path().to_path_buf(), todo_list);
+        todo_app.config.unindent_subitems = UnindentSubitems::Confirm;
+        let mut app = App::new(todo_app);
+        let levels = |app: &App| -> Vec<usize> {
+            let items = &app.todo_list.todo_list.items;
+            items.iter().map(|item| item.indent_level).collect()
+        };
+
+        app.selected_index = 1;
+        app.handle_key_event(KeyCode::BackTab).unwrap();
+        assert_eq!(app.mode, AppMode::ConfirmUnindent);
+        let screen = render(&app, 80, 6);
+        assert!(screen[5].starts_with("This will orphan 2 subtasks, continue?"));
+        app.handle_key_event(KeyCode::Char('n')).unwrap();
+        assert_eq!(app.mode, AppMode::Selection);
+        assert_eq!(levels(&app), [0, 1, 2, 2]);
+
+        app.handle_key_event(KeyCode::BackTab).unwrap();
+        app.handle_key_event(KeyCode::Char('y')).unwrap();
+        assert_eq!(app.mode, AppMode::Selection);
+        assert_eq!(levels(&app), [0, 0, 2, 2]);
+
+        // Items without subitems are unindented right away
+        app.selected_index = 3;
+        app.handle_key_event(KeyCode::BackTab).unwrap();
+        assert_eq!(app.mode, AppMode::Selection);
+        assert_eq!(levels(&app), [0, 0, 2, 1]);
+
+        // A single subtask is counted in the singular
+        app.todo_list.todo_list.items[2].indent_level = 1;
+        app.todo_list.todo_list.items[3].indent_level = 2;
+        app.selected_index = 2;
+        app.handle_key_event(KeyCode::BackTab).unwrap();
+        assert_eq!(app.mode, AppMode::ConfirmUnindent);
+        let screen = render(&app, 80, 6);
+        assert!(screen[5].starts_with("This will orphan 1 subtask, continue?"));
+    }
+
+    #[test]
+    fn test_next_item_starting_with() {
+        let items: Vec<TodoItem> = ["apples", "Bread", "avocado", "beans", "almonds"]
+            .iter()
+            .map(|text| TodoItem::new(text.to_string(), false, 0))
+            .collect();
+
+        assert_eq!(next_item_starting_with(&items, 0, 'a'), Some(2));
+        assert_eq!(next_item_starting_with(&items, 2, 'a'), Some(4));
+        // Continues from the top after the last item
+        assert_eq!(next_item_starting_with(&items, 4, 'a'), Some(0));
+        assert_eq!(next_item_starting_with(&items, 3, 'b'), Some(1));
+        // Ignores case, also from the insert row
+        assert_eq!(next_item_starting_with(&items, 5, 'B'), Some(1));
+        assert_eq!(next_item_starting_with(&items, 0, 'z'), None);
+        assert_eq!(next_item_starting_with(&items[..1], 0, 'a'), Some(0));
+        assert_eq!(next_item_starting_with(&[], 0, 'a'), None);
+    }
+
+    #[test]
+    fn test_jump_to_letter() {
+        let mut todo_list = TodoList::new(Local::now().date_naive());
+        for text in ["call mom", "buy milk", "clean up", "cook"] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), false, 0));
+        }
+        let config_dir = tempfile::tempdir().unwrap();
+        let mut app = App::new(TodoApp::new(config_dir.path().to_path_buf(), todo_list));
+
+        let jump = |app: &mut App, letter| {
+            app.handle_key_event(KeyCode::Char('\'')).unwrap();
+            assert_eq!(app.mode, AppMode::JumpToLetter);
+            app.handle_key_event(KeyCode::Char(letter)).unwrap();
+            assert_eq!(app.mode, AppMode::Selection);
+            app.selected_index
+        };
+        assert_eq!(jump(&mut app, 'c'), 2);
+        assert_eq!(jump(&mut app, 'c'), 3);
+        assert_eq!(jump(&mut app, 'c'), 0);
+        // Letters that are bound in selection mode only jump after `'`
+        assert_eq!(jump(&mut app, 'x'), 0);
+        assert!(!app.todo_list.todo_list.items[0].completed);
+        assert!(app.status_message.is_some());
+
+        app.handle_key_event(KeyCode::Char('\'')).unwrap();
+        app.handle_key_event(KeyCode::Esc).unwrap();
+        assert_eq!(app.mode, AppMode::Selection);
+        assert_eq!(app.selected_index, 0);
+    }
+
+    #[test]
+    fn test_indent_colors() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        for (text, completed, level) in [("A", false, 0), ("B", false, 1), ("C", true, 1)] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), completed, level));
+        }
+        let mut todo_app = TodoApp::new(PathBuf::new(), todo_list);
+        let mut app = App::new(todo_app);
+        app.selected_index = 3;
+
+        // Off by default
+        let mut terminal = Terminal::new(TestBackend::new(20, 7)).unwrap();
+        terminal.draw(|f| ui(f, &app)).unwrap();
+        assert_eq!(terminal.backend().buffer()[(9, 2)].symbol(), "B");
+        assert_eq!(terminal.backend().buffer()[(9, 2)].fg, Color::Reset);
+
+        todo_app = app.todo_list;
+        todo_app.config.indent_colors = true;
+        let mut app = App::new(todo_app);
+        app.selected_index = 3;
+        terminal.draw(|f| ui(f, &app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        assert_eq!(buffer[(7, 1)].fg, Color::Reset);
+        assert_eq!(buffer[(9, 2)].fg, Color::Cyan);
+        // Completed and selected items keep their own styles
+        assert_eq!(buffer[(9, 3)].fg, Color::DarkGray);
+        app.selected_index = 1;
+        terminal.draw(|f| ui(f, &app)).unwrap();
+        assert_eq!(terminal.backend().buffer()[(9, 2)].fg, Color::White);
+    }
+
+    #[test]
+    fn test_reload_picks_up_external_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut todo_list = TodoList::new(Local::now().date_naive());
+        for text in ["A", "B", "C"] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), false, 0));
+        }
+        let mut app = App::new(TodoApp::new(dir.path().to_path_buf(), todo_list));
+
+        // Nothing saved yet
+        assert!(app.handle_key_event(KeyCode::Char('r')).is_err());
+
+        app.todo_list.save_todo_list().unwrap();
+        app.selected_index = 2;
+        let path = app.todo_list.file_path();
+        let edited = app
+            .todo_list
+            .todo_list
+            .to_markdown()
+            .replace("* [ ] B\n* [ ] C\n", "");
+        std::fs::write(&path, edited.replace("A", "A edited")).unwrap();
+
+        app.handle_key_event(KeyCode::Char('r')).unwrap();
+        assert_eq!(app.mode, AppMode::Selection);
+        assert_eq!(app.todo_list.todo_list.items.len(), 1);
+        assert_eq!(app.todo_list.todo_list.items[0].text, "A edited");
+        assert_eq!(app.selected_index, 1);
+
+        // Changes that didn't make it to the file need a confirmation
+        app.todo_list.todo_list.items[0].text = "unsaved".to_string();
+        app.handle_key_event(KeyCode::Char('r')).unwrap();
+        assert_eq!(app.mode, AppMode::ConfirmReload);
+        app.handle_key_event(KeyCode::Esc).unwrap();
+        assert_eq!(app.mode, AppMode::Selection);
+        assert_eq!(app.todo_list.todo_list.items[0].text, "unsaved");
+
+        app.handle_key_event(KeyCode::Char('r')).unwrap();
+        app.handle_key_event(KeyCode::Char('y')).unwrap();
+        assert_eq!(app.mode, AppMode::Selection);
+        assert_eq!(app.todo_list.todo_list.items[0].text, "A edited");
+
+        // Problems with the file show up in the status bar
+        std::fs::write(&path, edited.replace("* [ ] A", "   * [ ] A")).unwrap();
+        app.handle_key_event(KeyCode::Char('r')).unwrap();
+        let message = app.status_message.as_ref().unwrap();
+        assert!(
+            message.text.ends_with(
+                "line 3: indentation of 3 spaces isn't a multiple of 2, read as level 1"
+            )
+        );
+    }
+
+    #[test]
+    fn test_mode_border() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("Item".to_string(), false, 0));
+        let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+
+        let mut terminal = Terminal::new(TestBackend::new(40, 4)).unwrap();
+        let mut draw = |app: &App| {
+            terminal.draw(|f| ui(f, app)).unwrap();
+            let buffer = terminal.backend().buffer();
+            let title: String = (0..40).map(|x| buffer[(x, 0)].symbol()).collect();
+            (title, buffer[(0, 0)].fg)
+        };
+
+        let (title, border) = draw(&app);
+        assert!(title.starts_with("┌TODO 2025-08-14─"));
+        assert_eq!(border, Color::Reset);
+
+        app.mode = AppMode::Edit;
+        let (title, border) = draw(&app);
+        assert!(title.starts_with("┌TODO 2025-08-14 — EDIT─"));
+        assert_eq!(border, Color::Green);
+
+        app.mode = AppMode::Delete;
+        let (title, border) = draw(&app);
+        assert!(title.starts_with("┌TODO 2025-08-14 — DELETE─"));
+        assert_eq!(border, Color::Red);
+
+        app.todo_list.config.mode_border = false;
+        let (title, border) = draw(&app);
+        assert!(title.starts_with("┌TODO 2025-08-14─"));
+        assert_eq!(border, Color::Reset);
+    }
+
+    #[test]
+    fn test_plain_ascii_renders_only_ascii() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list.title = Some("Work".to_string());
+        let items = &mut todo_list.items;
+        items.push(TodoItem::new(
+            "Read [docs](https://example.com)".to_string(),
+            false,
+            0,
+        ));
+        items.push(TodoItem::new(
+            "A rather long item that gets cut off".to_string(),
+            false,
+            1,
+        ));
+        items.push(TodoItem::new("Done".to_string(), true, 1));
+        items.push(TodoItem::note("Some note".to_string(), 0));
+        let mut todo_app = TodoApp::new(PathBuf::new(), todo_list);
+        todo_app.config.plain_ascii = true;
+        todo_app.config.celebrate = true;
+        let mut app = App::new(todo_app);
+        app.wrap_text = false;
+
+        let assert_ascii = |screen: Vec<String>| {
+            for row in &screen {
+                assert!(row.is_ascii(), "{:?}", row);
+            }
+            screen
+        };
+        let screen = assert_ascii(render(&app, 30, 7));
+        assert!(screen[0].starts_with("+Work - 2025-08-14-"));
+        assert!(screen[1].starts_with("|>* [ ] Read docs"));
+        assert!(screen[2].starts_with("|   * [ ] A rather long"));
+        assert!(screen[2].contains("long it~"));
+        assert!(screen[6].contains("k:Up"));
+
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        let screen = assert_ascii(render(&app, 30, 7));
+        assert!(screen[0].starts_with("+Work - 2025-08-14 - EDIT-"));
+        app.handle_key_event(KeyCode::Esc).unwrap();
+
+        app.handle_key_event(KeyCode::Char('x')).unwrap();
+        app.handle_key_event(KeyCode::Down).unwrap();
+        app.handle_key_event(KeyCode::Char('x')).unwrap();
+        assert_eq!(
+            app.status_message.as_ref().map(|m| m.text.as_str()),
+            Some("All done!")
+        );
+        assert_ascii(render(&app, 30, 7));
+    }
+
+    #[test]
+    fn test_checkbox_columns() {
+        // "* [ ] a": the box takes columns 2 to 4
+        let item = TodoItem::new("a".to_string(), false, 0);
+        let columns: Vec<usize> = (0..8).filter(|&c| is_checkbox_column(&item, c)).collect();
+        assert_eq!(columns, [2, 3, 4]);
+
+        // Two columns further right per level
+        let item = TodoItem::new("a".to_string(), true, 3);
+        let columns: Vec<usize> = (0..14).filter(|&c| is_checkbox_column(&item, c)).collect();
+        assert_eq!(columns, [8, 9, 10]);
+
+        // Notes have no box
+        let note = TodoItem::note("a".to_string(), 0);
+        assert!((0..8).all(|c| !is_checkbox_column(&note, c)));
+    }
+
+    #[test]
+    fn test_click_toggles_only_on_checkbox() {
+        let click = |column, row| MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list.items = vec![
+            TodoItem::new(
+                "Parent item wrapping onto a second line".to_string(),
+                false,
+                0,
+            ),
+            TodoItem::new("Child".to_string(), false, 1),
+        ];
+        let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+        let screen = render(&app, 30, 8);
+        assert!(screen[1].starts_with("│* [ ] Parent item wrapping"));
+        assert!(screen[3].starts_with("│  * [ ] Child"));
+
+        // The text only selects
+        app.handle_mouse_event(click(10, 3)).unwrap();
+        assert_eq!(app.selected_index, 1);
+        assert!(!app.todo_list.todo_list.items[1].completed);
+        // So does the second line of the parent, even below its box
+        app.handle_mouse_event(click(4, 2)).unwrap();
+        assert_eq!(app.selected_index, 0);
+        assert!(!app.todo_list.todo_list.items[0].completed);
+
+        // The box of the child, one level in
+        render(&app, 30, 8);
+        app.handle_mouse_event(click(5, 3)).unwrap();
+        assert_eq!(app.selected_index, 1);
+        assert!(app.todo_list.todo_list.items[1].completed);
+        // The box of the parent; clicks outside the list do nothing
+        render(&app, 30, 8);
+        app.handle_mouse_event(click(0, 1)).unwrap();
+        app.handle_mouse_event(click(3, 1)).unwrap();
+        assert!(app.todo_list.todo_list.items[0].completed);
+        assert_eq!(app.selected_index, 0);
+    }
+
+    #[test]
+    fn test_wrap_navigation() {
+        let mut app = numbered_app(3);
+        app.handle_key_event(KeyCode::Up).unwrap();
+        assert_eq!(app.selected_index, 0);
+
+        app.todo_list.config.wrap_navigation = true;
+        // Up from the first item goes to the insert row below the last item
+        app.handle_key_event(KeyCode::Up).unwrap();
+        assert_eq!(app.selected_index, 3);
+        app.handle_key_event(KeyCode::Char('k')).unwrap();
+        assert_eq!(app.selected_index, 2);
+        // Down from the last item stops at the insert row before wrapping
+        app.handle_key_event(KeyCode::Down).unwrap();
+        assert_eq!(app.selected_index, 3);
+        app.handle_key_event(KeyCode::Char('j')).unwrap();
+        assert_eq!(app.selected_index, 0);
+
+        // With no items the insert row is the only row
+        let mut app = numbered_app(0);
+        app.todo_list.config.wrap_navigation = true;
+        app.handle_key_event(KeyCode::Up).unwrap();
+        assert_eq!(app.selected_index, 0);
+        app.handle_key_event(KeyCode::Down).unwrap();
+        assert_eq!(app.selected_index, 0);
+    }
+
+    #[test]
+    fn test_show_file_path() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let config_dir = PathBuf::from("/home/me/todos");
+        let path = config_dir.join(TodoList::new(date).filename());
+        let app = App::new(TodoApp::new(config_dir.clone(), TodoList::new(date)));
+        assert!(!render(&app, 80, 6)[0].contains("todos"));
+
+        let mut todo_app = TodoApp::new(config_dir, TodoList::new(date));
+        todo_app.config.show_file_path = true;
+        let app = App::new(todo_app);
+        let screen = render(&app, 80, 6);
+        assert!(screen[0].starts_with("┌TODO 2025-08-14"));
+        assert!(screen[0].ends_with(&format!("{}┐", path.display())));
+
+        // Too narrow: the start of the path gives way
+        let screen = render(&app, 40, 6);
+        assert!(screen[0].ends_with("─…s/TODO-2025-08-14.md┐"));
+
+        assert_eq!(truncate_left("/a/b.md", 7, '…'), "/a/b.md");
+        assert_eq!(truncate_left("/a/b.md", 5, '~'), "~b.md");
+        assert_eq!(truncate_left("/a/b.md", 1, '…'), "…");
+        assert_eq!(truncate_left("/a/b.md", 0, '…'), "");
+    }
+
+    #[test]
+    fn test_selection_marker() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list.items.push(TodoItem::new(
+            "First item wrapping onto a second line".to_string(),
+            false,
+            0,
+        ));
+        todo_list
+            .items
+            .push(TodoItem::new("Second".to_string(), false, 0));
+        let mut todo_app = TodoApp::new(PathBuf::new(), todo_list);
+        todo_app.config.selection_marker = Some("▶".to_string());
+        let mut app = App::new(todo_app);
+
+        let screen = render(&app, 30, 6);
+        assert!(screen[1].starts_with("│▶* [ ] First item"));
+        assert!(screen[2].starts_with("│    onto a second line"));
+        assert!(screen[3].starts_with("│ * [ ] Second"));
+
+        app.handle_key_event(KeyCode::Down).unwrap();
+        let screen = render(&app, 30, 6);
+        assert!(screen[1].starts_with("│ * [ ] First item"));
+        assert!(screen[3].starts_with("│▶* [ ] Second"));
+
+        // Without a marker nothing moves
+        app.todo_list.config.selection_marker = None;
+        assert!(render(&app, 30, 6)[3].starts_with("│* [ ] Second"));
+    }
+
+    #[test]
+    fn test_no_color_draws_without_colors() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        for (text, completed, level) in [
+            ("Selected", false, 0),
+            ("Nested def:30", false, 1),
+            ("Done https://example.com", true, 0),
+        ] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), completed, level));
+        }
+        let mut app = App::new(TodoApp::new(PathBuf::new(), todo_list));
+        let colored = |app: &App| {
+            let mut terminal = Terminal::new(TestBackend::new(40, 6)).unwrap();
+            terminal.draw(|f| ui(f, app)).unwrap();
+            let buffer = terminal.backend().buffer();
+            buffer
+                .content()
+                .iter()
+                .any(|cell| cell.fg != Color::Reset || cell.bg != Color::Reset)
+        };
+        assert!(colored(&app));
+
+        app.todo_list.config.color = ColorMode::Never;
+        assert!(!colored(&app));
+        // The marker stands in for the highlight color
+        assert!(render(&app, 40, 6)[1].starts_with("│>* [ ] Selected"));
+    }
+
+    #[test]
+    fn test_edit_day_note() {
+        let dir = tempfile::tempdir().unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("Item".to_string(), false, 0));
+        let mut app = App::new(TodoApp::new(dir.path().to_path_buf(), todo_list));
+        assert!(render(&app, 30, 5)[1].starts_with("│* [ ] Item"));
+
+        app.handle_key_event(KeyCode::Char('n')).unwrap();
+        assert_eq!(app.mode, AppMode::EditNote);
+        for c in "focus".chars() {
+            app.handle_key_event(KeyCode::Char(c)).unwrap();
+        }
+        app.handle_key_event(KeyCode::Left).unwrap();
+        let screen = render(&app, 30, 5);
+        assert!(screen[1].starts_with("│> focu|s"));
+        assert!(screen[2].starts_with("│* [ ] Item"));
+
+        // Esc leaves the note as it was
+        app.handle_key_event(KeyCode::Esc).unwrap();
+        assert_eq!(app.todo_list.todo_list.note, None);
+        assert!(render(&app, 30, 5)[1].starts_with("│* [ ] Item"));
+
+        app.handle_key_event(KeyCode::Char('n')).unwrap();
+        app.handle_paste("focus:\nreport").unwrap();
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        assert_eq!(app.mode, AppMode::Selection);
+        assert_eq!(
+            app.todo_list.todo_list.note.as_deref(),
+            Some("focus: report")
+        );
+        let saved = std::fs::read_to_string(app.todo_list.file_path()).unwrap();
+        assert!(saved.contains("> focus: report\n"));
+        let screen = render(&app, 30, 5);
+        assert!(screen[1].starts_with("│> focus: report"));
+        assert!(screen[2].starts_with("│* [ ] Item"));
+
+        // Editing starts from the current note; clearing it removes it
+        app.handle_key_event(KeyCode::Char('n')).unwrap();
+        assert_eq!(app.edit_text, "focus: report");
+        app.edit_text.clear();
+        app.edit_cursor = 0;
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        assert_eq!(app.todo_list.todo_list.note, None);
+    }
+
+    #[test]
+    fn test_parse_due_input() {
+        let today = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let date = |d| NaiveDate::from_ymd_opt(2025, 8, d);
+        assert_eq!(parse_due_input(" ", today), Ok(None));
+        assert_eq!(parse_due_input("2025-08-20", today), Ok(date(20)));
+        assert_eq!(parse_due_input("Today", today), Ok(date(14)));
+        assert_eq!(parse_due_input("tomorrow", today), Ok(date(15)));
+        assert_eq!(parse_due_input("+3", today), Ok(date(17)));
+        for input in ["2025-02-30", "next week", "+", "-3", "+x"] {
+            assert!(parse_due_input(input, today).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_set_due_date_with_prompt() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut todo_list = TodoList::new(Local::now().date_naive());
+        todo_list
+            .items
+            .push(TodoItem::new("report".to_string(), false, 0));
+        let mut app = App::new(TodoApp::new(dir.path().to_path_buf(), todo_list));
+        let type_text = |app: &mut App, text: &str| {
+            for c in text.chars() {
+                app.handle_key_event(KeyCode::Char(c)).unwrap();
+            }
+        };
+
+        app.handle_key_event(KeyCode::Char('t')).unwrap();
+        assert_eq!(app.mode, AppMode::EditDue);
+        type_text(&mut app, "2025-08-20");
+        assert!(status_hints(&app, 80, 1).starts_with("Due: 2025-08-20|"));
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        assert_eq!(app.mode, AppMode::Selection);
+        assert_eq!(
+            app.todo_list.todo_list.items[0].text,
+            "report due:2025-08-20"
+        );
+        let saved = std::fs::read_to_string(app.todo_list.file_path()).unwrap();
+        assert!(saved.contains("* [ ] report due:2025-08-20\n"));
+
+        // Bad dates are rejected and the prompt stays open to fix them
+        app.handle_key_event(KeyCode::Char('t')).unwrap();
+        assert_eq!(app.edit_text, "2025-08-20");
+        app.handle_key_event(KeyCode::Backspace).unwrap();
+        type_text(&mut app, "x");
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        assert_eq!(app.mode, AppMode::EditDue);
+        assert!(app.status_message.as_ref().unwrap().is_error);
+        assert_eq!(
+            app.todo_list.todo_list.items[0].text,
+            "report due:2025-08-20"
+        );
+
+        // An empty date clears it
+        app.handle_key_event(KeyCode::Home).unwrap();
+        for _ in 0..10 {
+            app.handle_key_event(KeyCode::Delete).unwrap();
+        }
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        assert_eq!(app.mode, AppMode::Selection);
+        assert_eq!(app.todo_list.todo_list.items[0].text, "report");
+
+        // Esc leaves it alone
+        app.handle_key_event(KeyCode::Char('t')).unwrap();
+        type_text(&mut app, "+1");
+        app.handle_key_event(KeyCode::Esc).unwrap();
+        assert_eq!(app.todo_list.todo_list.items[0].text, "report");
+    }
+
+    #[test]
+    fn test_max_edit_length() {
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_app = TodoApp::new(PathBuf::new(), TodoList::new(date));
+        todo_app.config.max_edit_length = Some(5);
+        let mut app = App::new(todo_app);
+        app.mode = AppMode::Edit;
+        app.todo_list
+            .todo_list
+            .items
+            .push(TodoItem::new(String::new(), false, 0));
+
+        for c in "abcd".chars() {
+            app.handle_key_event(KeyCode::Char(c)).unwrap();
+        }
+        app.handle_paste("äöü").unwrap();
+        assert_eq!(app.edit_text, "abcdä");
+        assert!(app.status_message.is_some());
+
+        // Full: further input is ignored, wherever the cursor is
+        app.handle_key_event(KeyCode::Home).unwrap();
+        app.handle_key_event(KeyCode::Char('x')).unwrap();
+        app.handle_paste("more").unwrap();
+        assert_eq!(app.edit_text, "abcdä");
+        assert_eq!(app.edit_cursor, 0);
+        assert!(app.status_message.is_some());
+
+        // Deleting makes room again
+        app.handle_key_event(KeyCode::Delete).unwrap();
+        app.handle_key_event(KeyCode::Char('x')).unwrap();
+        assert_eq!(app.edit_text, "xbcdä");
+        assert!(app.status_message.is_none());
+
+        // A grapheme that doesn't fit as a whole is left out
+        app.handle_key_event(KeyCode::End).unwrap();
+        app.handle_key_event(KeyCode::Backspace).unwrap();
+        app.handle_key_event(KeyCode::Backspace).unwrap();
+        app.handle_paste("e\u{301}\u{302}").unwrap();
+        assert_eq!(app.edit_text, "xbc");
+        app.handle_paste("e\u{301}f").unwrap();
+        assert_eq!(app.edit_text, "xbce\u{301}");
+    }
+
+    #[test]
+    fn test_outline_paste_creates_subtree() {
+        let dir = tempfile::tempdir().unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        for (text, level) in [
+            ("Holidays", 0),
+            ("Trip", 1),
+            ("existing child", 2),
+            ("After", 0),
+        ] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), false, level));
+        }
+        let mut todo_app = TodoApp::new(dir.path().to_path_buf(), todo_list);
+        todo_app.config.multiline_paste = MultilinePaste::Outline;
+        todo_app.todo_list.format.done_markers = vec!["x".to_string(), "v".to_string()];
+        let mut app = App::new(todo_app);
+        app.selected_index = 1;
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        app.handle_key_event(KeyCode::End).unwrap();
+        app.handle_key_event(KeyCode::Char(' ')).unwrap();
+
+        app.handle_paste(
+            "  to Rome\n    book flights\n\n      compare prices\n    - [x] pack\n  * [v] next trip\n",
+        )
+        .unwrap();
+        assert_eq!(app.mode, AppMode::Edit);
+        assert_eq!(app.edit_text, "Trip to Rome");
+        app.handle_key_event(KeyCode::Enter).unwrap();
+
+        let items: Vec<(&str, usize, bool)> = app
+            .todo_list
+            .todo_list
+            .items
+            .iter()
+            .map(|item| (item.text.as_str(), item.indent_level, item.completed))
+            .collect();
+        assert_eq!(
+            items,
+            [
+                ("Holidays", 0, false),
+                ("Trip to Rome", 1, false),
+                ("existing child", 2, false),
+                ("book flights", 2, false),
+                ("compare prices", 3, false),
+                ("pack", 2, true),
+                ("next trip", 1, true),
+                ("After", 0, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failed_save_shows_banner_until_saved() {
+        let dir = tempfile::tempdir().unwrap();
+        let date = Local::now().date_naive();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("Item".to_string(), false, 0));
+
+        // Saving fails while the directory is missing
+        let missing = dir.path().join("missing");
+        let mut app = App::new(TodoApp::new(missing.clone(), todo_list));
+        let result = app.handle_key_event(KeyCode::Char('x'));
+        app.report_error(result.unwrap_err());
+        assert!(app.last_save_error.is_some());
+        assert!(app.status_message.is_none());
+
+        let screen = render(&app, 40, 6);
+        assert!(screen[4].starts_with("SAVE FAILED: "));
+        // The status bar stays below it
+        assert!(screen[5].starts_with("Sel"));
+
+        // Still there after other keys
+        app.handle_key_event(KeyCode::Char('w')).unwrap();
+        app.clear_save_error_if_saved();
+        assert!(app.last_save_error.is_some());
+
+        std::fs::create_dir(&missing).unwrap();
+        app.handle_key_event(KeyCode::Char('x')).unwrap();
+        app.clear_save_error_if_saved();
+        assert!(app.last_save_error.is_none());
+        assert!(render(&app, 40, 6)[5].starts_with("Sel"));
+        assert!(!render(&app, 40, 6)[4].contains("SAVE FAILED"));
+    }
+
+    #[test]
+    fn test_other_errors_with_unsaved_changes_are_not_save_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        todo_list
+            .items
+            .push(TodoItem::new("Item".to_string(), false, 0));
+        let mut app = App::new(TodoApp::new(dir.path().to_path_buf(), todo_list));
+        app.todo_list.todo_list.items[0].text = "unsaved".to_string();
+        assert!(app.todo_list.has_unsaved_changes());
+
+        // Reloading a list that was never saved fails, but not at saving
+        app.handle_key_event(KeyCode::Char('r')).unwrap();
+        let result = app.handle_key_event(KeyCode::Char('y'));
+        app.report_error(result.unwrap_err());
+        assert!(app.last_save_error.is_none());
+        let message = app.status_message.as_ref().unwrap();
+        assert!(message.is_error);
+        assert!(message.text.ends_with("has not been saved yet"));
+    }
+
+    #[test]
+    fn test_force_quit_from_any_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 8, 14).unwrap();
+        let mut todo_list = TodoList::new(date);
+        for text in ["A", "B"] {
+            todo_list
+                .items
+                .push(TodoItem::new(text.to_string(), false, 0));
+        }
+        let todo_app = TodoApp::new(dir.path().to_path_buf(), todo_list);
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        let texts = |app: &App| -> Vec<String> {
+            let items = &app.todo_list.todo_list.items;
+            items.iter().map(|item| item.text.clone()).collect()
+        };
+
+        // A new item still being written is dropped, like with Esc
+        let mut app = App::new(todo_app);
+        app.handle_key_event(KeyCode::Char('i')).unwrap();
+        app.handle_key_event(KeyCode::Char('q')).unwrap();
+        assert_eq!(app.edit_text, "q");
+        app.handle_key_event(ctrl('q')).unwrap();
+        assert!(app.should_quit());
+        assert_eq!(app.mode, AppMode::Selection);
+        assert_eq!(texts(&app), ["A", "B"]);
+
+        // Nothing gets deleted when quitting from delete mode
+        let mut app = App::new(app.todo_list);
+        app.handle_key_event(KeyCode::Char('d')).unwrap();
+        app.handle_key_event(KeyCode::Char('q')).unwrap();
+        assert!(!app.should_quit());
+        app.handle_key_event(ctrl('c')).unwrap();
+        assert!(app.should_quit());
+        assert_eq!(texts(&app), ["A", "B"]);
+
+        // Can be turned off
+        let mut app = App::new(app.todo_list);
+        app.todo_list.config.force_quit = false;
+        app.handle_key_event(KeyCode::Enter).unwrap();
+        app.handle_key_event(ctrl('q')).unwrap();
+        assert!(!app.should_quit());
+        assert_eq!(app.mode, AppMode::Edit);
+    }
 }
@@ -0,0 +1,200 @@
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::config::{ColorMode, Config, Theme};
+
+/// Styles for the roles things play in the terminal UI
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    /// Main line of the highlighted item
+    pub selected: Style,
+    /// Items about to be deleted
+    pub deleting: Style,
+    pub completed: Style,
+    /// The virtual item below the last one
+    pub insert_marker: Style,
+    /// Labels of Markdown links
+    pub link: Style,
+    pub status: Style,
+    pub status_error: Style,
+    /// List border while an item is edited
+    pub edit_border: Style,
+    /// List border while deleting items
+    pub delete_border: Style,
+    /// Text colors for items deferred a few, several and many times
+    pub deferred: [Color; 3],
+    /// Text colors for open items by indentation level, repeating for
+    /// deeper levels
+    pub indent_levels: [Color; 4],
+}
+
+impl Palette {
+    pub fn for_theme(theme: Theme) -> Self {
+        match theme {
+            Theme::Dark => Self {
+                selected: Style::default().bg(Color::DarkGray).fg(Color::White),
+                deleting: Style::default().bg(Color::Red).fg(Color::White),
+                completed: Style::default().fg(Color::DarkGray),
+                insert_marker: Style::default().bg(Color::DarkGray).fg(Color::Yellow),
+                link: Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::UNDERLINED),
+                status: Style::default().bg(Color::Blue).fg(Color::White),
+                status_error: Style::default().bg(Color::Red).fg(Color::White),
+                edit_border: Style::default().fg(Color::Green),
+                delete_border: Style::default().fg(Color::Red),
+                deferred: [Color::Yellow, Color::LightRed, Color::Red],
+                indent_levels: [Color::Reset, Color::Cyan, Color::Green, Color::Magenta],
+            },
+            // Light terminals turn dark gray and blue into low-contrast
+            // smudges, so this sticks to dark text on light backgrounds
+            Theme::Light => Self {
+                selected: Style::default()
+                    .bg(Color::Rgb(0xd0, 0xd0, 0xd0))
+                    .fg(Color::Black),
+                deleting: Style::default().bg(Color::Red).fg(Color::White),
+                completed: Style::default().fg(Color::Rgb(0x70, 0x70, 0x70)),
+                insert_marker: Style::default()
+                    .bg(Color::Rgb(0xd0, 0xd0, 0xd0))
+                    .fg(Color::Blue),
+                link: Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::UNDERLINED),
+                status: Style::default()
+                    .bg(Color::Rgb(0xe4, 0xe4, 0xe4))
+                    .fg(Color::Black),
+                status_error: Style::default().bg(Color::Red).fg(Color::White),
+                edit_border: Style::default().fg(Color::Rgb(0x00, 0x80, 0x00)),
+                delete_border: Style::default().fg(Color::Red),
+                deferred: [Color::Magenta, Color::Red, Color::Rgb(0x8b, 0x00, 0x00)],
+                indent_levels: [
+                    Color::Reset,
+                    Color::Blue,
+                    Color::Rgb(0x00, 0x80, 0x00),
+                    Color::Rgb(0x80, 0x00, 0x80),
+                ],
+            },
+        }
+    }
+
+    /// Text attributes instead of colors, for `ColorMode::Never`
+    pub fn monochrome() -> Self {
+        let reversed = Style::default().add_modifier(Modifier::REVERSED);
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        Self {
+            selected: reversed,
+            deleting: reversed.add_modifier(Modifier::BOLD),
+            completed: Style::default().add_modifier(Modifier::DIM),
+            insert_marker: Style::default().add_modifier(Modifier::ITALIC),
+            link: Style::default().add_modifier(Modifier::UNDERLINED),
+            status: reversed,
+            status_error: reversed.add_modifier(Modifier::BOLD),
+            edit_border: bold,
+            delete_border: bold,
+            deferred: [Color::Reset; 3],
+            indent_levels: [Color::Reset; 4],
+        }
+    }
+
+    /// Colors of the configured theme, or none if colors are off
+    pub fn for_config(config: &Config) -> Self {
+        if config.color == ColorMode::Never {
+            Self::monochrome()
+        } else {
+            Self::for_theme(config.theme)
+        }
+    }
+
+    /// Warning color for open items that have been carried over `deferred`
+    /// times, getting more alarming the longer they are put off
+    pub fn deferral_color(&self, deferred: u32) -> Option<Color> {
+        match deferred {
+            0..=1 => None,
+            2..=3 => Some(self.deferred[0]),
+            4..=6 => Some(self.deferred[1]),
+            _ => Some(self.deferred[2]),
+        }
+    }
+
+    /// Text color for open items at `indent_level`
+    pub fn indent_color(&self, indent_level: usize) -> Color {
+        self.indent_levels[indent_level % self.indent_levels.len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dark_keeps_the_original_colors() {
+        let palette = Palette::for_theme(Theme::Dark);
+        assert_eq!(palette.completed, Style::default().fg(Color::DarkGray));
+        assert_eq!(
+            palette.status,
+            Style::default().bg(Color::Blue).fg(Color::White)
+        );
+        assert_eq!(palette.link.fg, Some(Color::Cyan));
+        assert_eq!(palette.deferral_color(1), None);
+        assert_eq!(palette.deferral_color(2), Some(Color::Yellow));
+        assert_eq!(palette.deferral_color(5), Some(Color::LightRed));
+        assert_eq!(palette.deferral_color(30), Some(Color::Red));
+    }
+
+    #[test]
+    fn test_indent_colors_cycle() {
+        let palette = Palette::for_theme(Theme::Dark);
+        assert_eq!(palette.indent_color(0), Color::Reset);
+        assert_eq!(palette.indent_color(1), Color::Cyan);
+        assert_eq!(palette.indent_color(2), Color::Green);
+        assert_eq!(palette.indent_color(4), Color::Reset);
+        assert_eq!(palette.indent_color(5), Color::Cyan);
+    }
+
+    #[test]
+    fn test_monochrome_has_no_colors() {
+        let palette = Palette::monochrome();
+        let styles = [
+            palette.selected,
+            palette.deleting,
+            palette.completed,
+            palette.insert_marker,
+            palette.link,
+            palette.status,
+            palette.status_error,
+            palette.edit_border,
+            palette.delete_border,
+        ];
+        for style in styles {
+            assert_eq!((style.fg, style.bg), (None, None));
+        }
+        assert_eq!(palette.deferral_color(30), Some(Color::Reset));
+        assert_eq!(palette.indent_color(1), Color::Reset);
+
+        let config = Config {
+            color: ColorMode::Never,
+            ..Config::default()
+        };
+        assert_eq!(Palette::for_config(&config), palette);
+        assert_eq!(
+            Palette::for_config(&Config::default()),
+            Palette::for_theme(Theme::Dark)
+        );
+    }
+
+    #[test]
+    fn test_light_avoids_washed_out_colors() {
+        let palette = Palette::for_theme(Theme::Light);
+        assert_eq!(
+            palette.completed,
+            Style::default().fg(Color::Rgb(0x70, 0x70, 0x70))
+        );
+        assert_eq!(palette.status.fg, Some(Color::Black));
+        assert_eq!(palette.selected.fg, Some(Color::Black));
+        assert_eq!(palette.link.fg, Some(Color::Blue));
+        assert_eq!(palette.deferral_color(2), Some(Color::Magenta));
+        for style in [palette.completed, palette.status, palette.selected] {
+            assert_ne!(style.fg, Some(Color::DarkGray));
+            assert_ne!(style.bg, Some(Color::Blue));
+        }
+    }
+}